@@ -30,37 +30,6 @@ macro_rules! pass_req_arg {
     };
 }
 
-use bpaf::Parser;
-use std::{cell::RefCell, rc::Rc};
-
-/// Stash successfully parsed value in a RefCell
-pub fn remember_opt<P, T>(parser: P, cell: &Rc<RefCell<T>>) -> impl Parser<T>
-where
-    P: Parser<T>,
-    T: Clone + 'static,
-{
-    let cell = cell.clone();
-    parser.map(move |val| {
-        *cell.borrow_mut() = val.clone();
-        val
-    })
-}
-
-/// Stash successfully parsed value in a RefCell.
-///
-/// Unlike [`remember_opt`] this one parses a required value
-pub fn remember_req<P, T>(parser: P, cell: &Rc<RefCell<Option<T>>>) -> impl Parser<T>
-where
-    P: Parser<T>,
-    T: Clone + 'static,
-{
-    let cell = cell.clone();
-    parser.map(move |val| {
-        *cell.borrow_mut() = Some(val.clone());
-        val
-    })
-}
-
 pub fn unique_match<I, T>(mut iter: I, name: &str) -> Result<T, String>
 where
     I: Iterator<Item = T>,
@@ -135,6 +135,10 @@ pub enum Exec {
 }
 
 impl Exec {
+    /// Stand-in target for parsers that only use it for completion, passed to `and_then` to
+    /// describe them in help
+    pub const PLACEHOLDER: Exec = Exec::Bin { pkg: "", name: "" };
+
     pub fn matches(&self, package: Option<&str>, target: &Target) -> bool {
         let (&pkg, &name, kind) = match self {
             Exec::Bin { pkg, name } => (pkg, name, "bin"),
@@ -1,5 +1,4 @@
-use std::cell::RefCell;
-use std::{collections::BTreeSet, rc::Rc};
+use std::collections::BTreeSet;
 
 use bpaf::*;
 
@@ -81,21 +80,16 @@ pub fn complete_target_kind<S: AsRef<str>>(
     suggest_available(input, targets)
 }
 
-pub fn parse_runnable(package: Rc<RefCell<Option<&'static str>>>) -> impl Parser<Exec> {
-    let bin = parse_bin(package.clone());
-    let example = parse_example(package.clone());
+pub fn parse_runnable(package: Option<&'static str>) -> impl Parser<Exec> {
+    let bin = parse_bin(package);
+    let example = parse_example(package);
 
-    let package0 = package.clone();
     const RUNNABLE: &[&str] = &["bin", "example"];
     let pos = positional::<String>("EXE")
-        .complete(move |i| complete_target_kind(&[i], *package.borrow(), RUNNABLE))
+        .complete(move |i| complete_target_kind(&[i], package, RUNNABLE))
         .parse::<_, _, String>(move |name| {
             unique_match(
-                matching_targets(
-                    MatchKind::exact(*package0.borrow()),
-                    MatchKind::Exact(&name),
-                    RUNNABLE,
-                ),
+                matching_targets(MatchKind::exact(package), MatchKind::Exact(&name), RUNNABLE),
                 &name,
             )
         });
@@ -103,19 +97,19 @@ pub fn parse_runnable(package: Rc<RefCell<Option<&'static str>>>) -> impl Parser
     construct!([pos, bin, example])
 }
 
-pub fn parse_testable(package: Rc<RefCell<Option<&'static str>>>) -> impl Parser<Exec> {
-    let bin = parse_bin(package.clone());
-    let example = parse_example(package.clone());
-    let test = parse_exec_target('t', "test", "use this test", &["test"], package.clone());
+pub fn parse_testable(package: Option<&'static str>) -> impl Parser<Exec> {
+    let bin = parse_bin(package);
+    let example = parse_example(package);
+    let test = parse_exec_target('t', "test", "use this test", &["test"], package);
     let bench = parse_exec_target('b', "bench", "use this benchmark", &["bench"], package);
     construct!([bin, example, test, bench])
 }
 
-fn parse_bin(package: Rc<RefCell<Option<&'static str>>>) -> impl Parser<Exec> {
+fn parse_bin(package: Option<&'static str>) -> impl Parser<Exec> {
     parse_exec_target('b', "bin", "use this binary", &["bin"], package)
 }
 
-fn parse_example(package: Rc<RefCell<Option<&'static str>>>) -> impl Parser<Exec> {
+fn parse_example(package: Option<&'static str>) -> impl Parser<Exec> {
     parse_exec_target('e', "example", "use this example", &["example"], package)
 }
 
@@ -124,21 +118,16 @@ fn parse_exec_target(
     long_name: &'static str,
     help: &'static str,
     kinds: &'static [&'static str],
-    package: Rc<RefCell<Option<&'static str>>>,
+    package: Option<&'static str>,
 ) -> impl Parser<Exec> {
-    let package0 = package.clone();
     long(long_name)
         .short(short_name)
         .help(help)
         .argument::<String>("NAME")
-        .complete(move |i| complete_target_kind(&[i], *package.borrow(), kinds))
+        .complete(move |i| complete_target_kind(&[i], package, kinds))
         .parse::<_, _, String>(move |name| {
             unique_match(
-                matching_targets(
-                    MatchKind::exact(*package0.borrow()),
-                    MatchKind::Exact(&name),
-                    kinds,
-                ),
+                matching_targets(MatchKind::exact(package), MatchKind::Exact(&name), kinds),
                 &name,
             )
         })
@@ -1,11 +1,10 @@
 use crate::{
     metadata::{bpaf_passthough_for, Exec},
     opts::parse_runnable,
-    remember_opt, remember_req,
     shared::{cargo_opts, parse_package, CargoOpts},
 };
 use bpaf::*;
-use std::{cell::RefCell, ffi::OsString, process::Command, rc::Rc};
+use std::{ffi::OsString, process::Command};
 
 #[derive(Debug, Clone)]
 pub struct Run {
@@ -77,27 +76,31 @@ fn complete_binary_args(input: &[OsString], exec: Option<Exec>) -> Vec<(String,
     }
 }
 
-pub fn parse_run() -> impl Parser<Run> {
-    let cur_pkg = Rc::new(RefCell::new(None));
-    let cur_exec = Rc::new(RefCell::new(None));
-
-    let package = remember_opt(parse_package("Package with the target to run"), &cur_pkg);
-    let runnable = remember_req(parse_runnable(cur_pkg), &cur_exec);
-
-    let args = positional::<OsString>("args")
+fn parse_args(runnable: Option<Exec>) -> impl Parser<Vec<OsString>> {
+    positional::<OsString>("args")
         .help("Cauwugo will pass arguments after -- to the child process")
         .strict()
         .many()
-        .complete(move |input| complete_binary_args(input, *cur_exec.borrow()));
+        .complete(move |input| complete_binary_args(input, runnable))
+}
+
+pub fn parse_run() -> impl Parser<Run> {
+    let cargo_opts = cargo_opts();
+    let target = parse_package("Package with the target to run").and_then(None, |package| {
+        parse_runnable(package).and_then(Exec::PLACEHOLDER, move |runnable| {
+            parse_args(Some(runnable)).map(move |args| (package, runnable, args))
+        })
+    });
 
-    construct!(Run {
-        cargo_opts(),
-        package,
-        runnable,
-        args,
-    })
-    .to_options()
-    .descr("Run a binary or example of the local package")
-    .command("run")
-    .short('r')
+    construct!(cargo_opts, target)
+        .map(|(cargo_opts, (package, runnable, args))| Run {
+            cargo_opts,
+            package,
+            runnable,
+            args,
+        })
+        .to_options()
+        .descr("Run a binary or example of the local package")
+        .command("run")
+        .short('r')
 }
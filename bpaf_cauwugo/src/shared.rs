@@ -1,5 +1,5 @@
 use bpaf::*;
-use std::{cell::RefCell, process::Command, rc::Rc};
+use std::process::Command;
 
 use crate::{
     metadata::{Exec, METADATA},
@@ -97,17 +97,14 @@ pub fn parse_package(help: &'static str) -> impl Parser<Option<&'static str>> {
         .optional()
 }
 
-pub fn parse_features(cur_pkg: Rc<RefCell<Option<&'static str>>>) -> impl Parser<Vec<String>> {
+pub fn parse_features(cur_pkg: Option<&'static str>) -> impl Parser<Vec<String>> {
     short('F')
         .long("feature")
         .help("Feature to activate, one at a time")
         .argument("FEATURES")
         .many()
         .complete(move |i| {
-            match cur_pkg
-                .borrow()
-                .and_then(|p| METADATA.packages.iter().find(|pkg| pkg.name == p))
-            {
+            match cur_pkg.and_then(|p| METADATA.packages.iter().find(|pkg| pkg.name == p)) {
                 Some(pkg) => suggest_available(i, pkg.features.keys().map(|s| s.as_str())),
                 None => vec![("<FEATURE>".to_owned(), None)],
             }
@@ -115,14 +112,13 @@ pub fn parse_features(cur_pkg: Rc<RefCell<Option<&'static str>>>) -> impl Parser
 }
 
 pub fn package_and_testables() -> impl Parser<PackageAndTestables> {
-    let cur_pkg = Rc::new(RefCell::new(None));
-    let testables = parse_testable(cur_pkg.clone()).many();
-    let features = parse_features(cur_pkg.clone());
-    let package = parse_package("Package to check").map(move |p| {
-        *cur_pkg.borrow_mut() = p;
-        p
-    });
+    parse_package("Package to check").and_then(None, testables_for)
+}
 
+fn testables_for(cur_pkg: Option<&'static str>) -> impl Parser<PackageAndTestables> {
+    let package = pure(cur_pkg);
+    let testables = parse_testable(cur_pkg).many();
+    let features = parse_features(cur_pkg);
     construct!(PackageAndTestables {
         package,
         testables,
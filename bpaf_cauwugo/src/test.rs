@@ -1,4 +1,4 @@
-use std::{cell::RefCell, process::Command, rc::Rc};
+use std::process::Command;
 
 use bpaf::*;
 
@@ -106,29 +106,28 @@ fn complete_subtest_name(input: &str, current_test: Option<Exec>) -> Vec<(String
 }
 
 pub fn parse_specific() -> impl Parser<Test> {
-    let cur_exec = Rc::new(RefCell::new(None));
-    let cur_exec2 = cur_exec.clone();
     let test = positional::<String>("TEST")
         .help("Test file name")
         .complete(move |i| complete_target_kind(&[i], None, TESTABLE))
-        .parse::<_, _, String>(move |name| {
+        .parse::<_, _, String>(|name| {
             let mut iter = matching_targets(MatchKind::Any, MatchKind::Exact(&name), TESTABLE);
             match (iter.next(), iter.next()) {
                 (None, _) => Err(format!("{} is not a known test name", name)),
                 (Some(_), Some(_)) => Err(format!("{} is not a unique test name", name)),
-                (Some(exec), None) => {
-                    *cur_exec.borrow_mut() = Some(exec);
-                    Ok(exec)
-                }
+                (Some(exec), None) => Ok(exec),
             }
         });
 
-    let name = positional::<String>("NAME")
-        .help("Test name in a file")
-        .complete(move |i| complete_subtest_name(i, *cur_exec2.borrow()))
-        .optional();
+    test.and_then(Exec::PLACEHOLDER, |test| {
+        parse_subtest_name(Some(test)).map(move |name| Test::Specific(Specific { test, name }))
+    })
+}
 
-    construct!(Specific { test, name }).map(Test::Specific)
+fn parse_subtest_name(test: Option<Exec>) -> impl Parser<Option<String>> {
+    positional::<String>("NAME")
+        .help("Test name in a file")
+        .complete(move |i| complete_subtest_name(i, test))
+        .optional()
 }
 
 pub fn parse_test() -> impl Parser<Test> {
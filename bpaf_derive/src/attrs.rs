@@ -41,6 +41,9 @@ pub enum Consumer {
     Switch {
         span: Span,
     },
+    NegatableSwitch {
+        span: Span,
+    },
     Flag {
         present: Expr,
         absent: Expr,
@@ -84,6 +87,7 @@ impl Consumer {
     pub fn span(&self) -> Span {
         match self {
             Consumer::Switch { span }
+            | Consumer::NegatableSwitch { span }
             | Consumer::Flag { span, .. }
             | Consumer::ReqFlag { span, .. }
             | Consumer::Any { span, .. }
@@ -98,6 +102,7 @@ impl Consumer {
     pub(crate) fn help_placement(&self) -> HelpPlacement {
         match self {
             Consumer::Switch { .. }
+            | Consumer::NegatableSwitch { .. }
             | Consumer::Flag { .. }
             | Consumer::ReqFlag { .. }
             | Consumer::Argument { .. } => HelpPlacement::AtName,
@@ -119,6 +124,7 @@ impl Consumer {
     pub(crate) fn needs_name(&self) -> bool {
         match self {
             Consumer::Switch { .. }
+            | Consumer::NegatableSwitch { .. }
             | Consumer::Flag { .. }
             | Consumer::ReqFlag { .. }
             | Consumer::Argument { .. } => true,
@@ -212,6 +218,7 @@ impl ToTokens for PostParse {
             PostParse::Many { .. } => quote!(many()),
             PostParse::Collect { .. } => quote!(collect()),
             PostParse::Count { .. } => quote!(count()),
+            PostParse::Delimited { delimiter, .. } => quote!(delimited(#delimiter)),
             PostParse::Some_ { msg, .. } => quote!(some(#msg)),
             PostParse::Map { f, .. } => quote!(map(#f)),
             PostParse::Optional { .. } => quote!(optional()),
@@ -237,6 +244,8 @@ impl ToTokens for PostDecor {
             PostDecor::Last { .. } => quote!(last()),
             PostDecor::GroupHelp { doc, .. } => quote!(group_help(#doc)),
             PostDecor::Guard { check, msg, .. } => quote!(guard(#check, #msg)),
+            PostDecor::Range { range, .. } => quote!(range(#range)),
+            PostDecor::WithSource { .. } => quote!(with_source()),
             PostDecor::Hide { .. } => quote!(hide()),
             PostDecor::CustomUsage { usage, .. } => quote!(custom_usage(#usage)),
             PostDecor::HideUsage { .. } => quote!(hide_usage()),
@@ -252,6 +261,7 @@ pub(crate) enum PostParse {
     Many { span: Span },
     Collect { span: Span },
     Count { span: Span },
+    Delimited { span: Span, delimiter: Box<Expr> },
     Some_ { span: Span, msg: Box<Expr> },
     Map { span: Span, f: Box<Expr> },
     Optional { span: Span },
@@ -268,6 +278,7 @@ impl PostParse {
             | Self::Many { span }
             | Self::Collect { span }
             | Self::Count { span }
+            | Self::Delimited { span, .. }
             | Self::Some_ { span, .. }
             | Self::Map { span, .. }
             | Self::Optional { span }
@@ -319,6 +330,13 @@ pub(crate) enum PostDecor {
         check: Box<Expr>,
         msg: Box<Expr>,
     },
+    Range {
+        span: Span,
+        range: Box<Expr>,
+    },
+    WithSource {
+        span: Span,
+    },
     Hide {
         span: Span,
     },
@@ -343,6 +361,8 @@ impl PostDecor {
             | Self::FallbackWith { span, .. }
             | Self::GroupHelp { span, .. }
             | Self::Guard { span, .. }
+            | Self::Range { span, .. }
+            | Self::WithSource { span }
             | Self::Hide { span }
             | Self::CustomUsage { span, .. }
             | Self::HideUsage { span } => *span,
@@ -431,6 +451,8 @@ impl Consumer {
             }
         } else if kw == "switch" {
             Consumer::Switch { span }
+        } else if kw == "negatable_switch" {
+            Consumer::NegatableSwitch { span }
         } else if kw == "flag" {
             let (present, absent) = parse_arg2(input)?;
             Consumer::Flag {
@@ -473,6 +495,9 @@ impl PostParse {
             Self::Collect { span }
         } else if kw == "count" {
             Self::Count { span }
+        } else if kw == "delimited" {
+            let delimiter = parse_arg(input)?;
+            Self::Delimited { span, delimiter }
         } else if kw == "map" {
             let f = parse_arg(input)?;
             Self::Map { span, f }
@@ -526,6 +551,11 @@ impl PostDecor {
         } else if kw == "guard" {
             let (check, msg) = parse_arg2(input)?;
             Self::Guard { span, check, msg }
+        } else if kw == "range" {
+            let range = parse_expr(input)?;
+            Self::Range { span, range }
+        } else if kw == "with_source" {
+            Self::WithSource { span }
         } else if kw == "hide" {
             Self::Hide { span }
         } else if kw == "hide_usage" {
@@ -207,6 +207,57 @@ fn derive_field_guard_const() {
     assert_eq!(input.to_token_stream().to_string(), output.to_string());
 }
 
+#[test]
+fn derive_field_range() {
+    let input: NamedField = parse_quote! {
+        #[bpaf(argument("N"), range(1..=64))]
+        jobs: u32
+    };
+    let output = quote! {
+        ::bpaf::long("jobs").argument::<u32>("N").range(1..=64)
+    };
+    assert_eq!(input.to_token_stream().to_string(), output.to_string());
+}
+
+#[test]
+fn derive_field_with_source() {
+    let input: NamedField = parse_quote! {
+        #[bpaf(argument("N"), fallback(4), with_source)]
+        jobs: (u32, ValueSource)
+    };
+    let output = quote! {
+        ::bpaf::long("jobs").argument::<u32>("N").fallback(4).with_source()
+    };
+    assert_eq!(input.to_token_stream().to_string(), output.to_string());
+}
+
+#[test]
+fn derive_field_with_source_optional() {
+    let input: NamedField = parse_quote! {
+        #[bpaf(with_source)]
+        jobs: (Option<u32>, ValueSource)
+    };
+    let output = quote! {
+        ::bpaf::long("jobs").argument::<u32>("ARG").optional().with_source()
+    };
+    assert_eq!(input.to_token_stream().to_string(), output.to_string());
+}
+
+#[test]
+fn derive_field_with_source_needs_value_source() {
+    let input = quote! {
+        #[bpaf(with_source)]
+        jobs: (u32, String)
+    };
+    field_trans_fail(input, "with_source needs a field of type (T, ValueSource)");
+
+    let input = quote! {
+        #[bpaf(with_source)]
+        jobs: u32
+    };
+    field_trans_fail(input, "with_source needs a field of type (T, ValueSource)");
+}
+
 #[test]
 fn derive_help() {
     let input: NamedField = parse_quote! {
@@ -320,6 +371,18 @@ fn many_catch() {
     assert_eq!(input.to_token_stream().to_string(), output.to_string());
 }
 
+#[test]
+fn delimited_many() {
+    let input: NamedField = parse_quote! {
+        #[bpaf(argument::<u16>("PORT"), delimited(','), many, map(flatten))]
+        ports: Vec<u16>
+    };
+    let output = quote! {
+        ::bpaf::long("ports").argument::<u16>("PORT").delimited(',').many().map(flatten)
+    };
+    assert_eq!(input.to_token_stream().to_string(), output.to_string());
+}
+
 #[test]
 fn collect_catch() {
     let input: NamedField = parse_quote! {
@@ -628,6 +691,18 @@ fn explicit_switch_argument() {
     assert_eq!(input.to_token_stream().to_string(), output.to_string());
 }
 
+#[test]
+fn explicit_negatable_switch_argument() {
+    let input: NamedField = parse_quote! {
+        #[bpaf(negatable_switch)]
+        item: bool
+    };
+    let output = quote! {
+        ::bpaf::long("item").negatable_switch()
+    };
+    assert_eq!(input.to_token_stream().to_string(), output.to_string());
+}
+
 #[test]
 fn explicit_req_flag_argument() {
     let input: NamedField = parse_quote! {
@@ -7,8 +7,8 @@ use syn::{
 
 use crate::{
     attrs::{
-        parse_bpaf_doc_attrs, Consumer, FieldAttrs, HelpPlacement, Name, Post, PostParse,
-        StrictName, TurboFish,
+        parse_bpaf_doc_attrs, Consumer, FieldAttrs, HelpPlacement, Name, Post, PostDecor,
+        PostParse, StrictName, TurboFish,
     },
     field::{split_type, Shape},
     help::Help,
@@ -25,6 +25,17 @@ pub(crate) struct StructField {
     pub help: Option<Help>,
 }
 
+/// `ValueSource` or a path that ends with it: `bpaf::ValueSource`
+fn is_value_source(ty: &Type) -> bool {
+    match ty {
+        Type::Path(p) if p.qself.is_none() => matches!(
+            p.path.segments.last(),
+            Some(s) if s.ident == "ValueSource" && s.arguments.is_empty()
+        ),
+        _ => false,
+    }
+}
+
 fn derive_consumer(name_present: bool, ty: &Type) -> Result<Consumer> {
     let span = ty.span();
     Ok(match split_type(ty) {
@@ -73,6 +84,7 @@ impl ToTokens for Consumer {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         match self {
             Consumer::Switch { .. } => quote!(switch()),
+            Consumer::NegatableSwitch { .. } => quote!(negatable_switch()),
             Consumer::Flag {
                 present, absent, ..
             } => quote!(flag(#present, #absent)),
@@ -176,6 +188,27 @@ impl StructField {
             help = None;
         }
 
+        // with_source produces a tuple, everything else is derived from the value type
+        let with_source = field_attrs
+            .postpr
+            .iter()
+            .any(|p| matches!(p, Post::Decor(PostDecor::WithSource { .. })));
+        let ty = if with_source {
+            match &ty {
+                Type::Tuple(tuple)
+                    if tuple.elems.len() == 2 && is_value_source(&tuple.elems[1]) =>
+                {
+                    tuple.elems[0].clone()
+                }
+                _ => {
+                    let msg = "with_source needs a field of type (T, ValueSource)";
+                    return Err(Error::new_spanned(ty, msg));
+                }
+            }
+        } else {
+            ty
+        };
+
         let derived_consumer = field_attrs.consumer.is_empty();
 
         let mut cons = match field_attrs.consumer.pop() {
@@ -288,7 +321,10 @@ impl StructField {
                 Shape::Bool => {
                     if name.is_none()
                         && naming.is_empty()
-                        && matches!(cons, Consumer::Switch { .. })
+                        && matches!(
+                            cons,
+                            Consumer::Switch { .. } | Consumer::NegatableSwitch { .. }
+                        )
                     {
                         let msg = "Can't derive consumer for unnamed boolean field, try adding one of #[bpaf(positional)], #[bpaf(long(\"name\")] or #[bpaf(short('n'))] annotations to it";
                         let err = Error::new_spanned(ty, msg);
@@ -29,12 +29,11 @@ pub struct Options {
 
 // Parses -user xxx
 fn user() -> impl Parser<Option<String>> {
-    // match only literal "-user"
-    let tag = literal("-user").anywhere();
-    let value = positional("USER").help("User name");
-    construct!(tag, value)
-        .adjacent()
-        .map(|pair| pair.1)
+    // long name spelled with a single dash: "-user"
+    long("user")
+        .single_dash()
+        .help("User name")
+        .argument("USER")
         .optional()
 }
 
@@ -1,19 +1,14 @@
-/// To parse comma separated values it's easier to treat them as strings
+/// Comma separated values can be parsed with `delimited`
 use bpaf::*;
-use std::str::FromStr;
 
 // --ports 1,2,3 --ports 4,5   => [1,2,3,4,5]
 fn args() -> impl Parser<Vec<u16>> {
     long("ports")
         .help("Comma separated list of ports")
-        .argument::<String>("PORTS")
-        .parse(|s| {
-            s.split(',')
-                .map(u16::from_str)
-                .collect::<Result<Vec<_>, _>>()
-        })
+        .argument::<u16>("PORT")
+        .delimited(',')
         .many()
-        .map(|nested| nested.into_iter().flatten().collect())
+        .map(|nested| nested.concat())
 }
 
 fn main() {
@@ -1,15 +1,8 @@
-/// To parse comma separated values it's easier to treat them as strings
+/// Comma separated values can be parsed with `delimited`
 use bpaf::*;
-use std::{num::ParseIntError, str::FromStr};
-
-fn split_and_parse(s: String) -> Result<Vec<u16>, ParseIntError> {
-    s.split(',')
-        .map(u16::from_str)
-        .collect::<Result<Vec<_>, _>>()
-}
 
 fn flatten_vec(vv: Vec<Vec<u16>>) -> Vec<u16> {
-    vv.into_iter().flatten().collect()
+    vv.concat()
 }
 
 #[derive(Debug, Clone, Bpaf)]
@@ -17,8 +10,8 @@ fn flatten_vec(vv: Vec<Vec<u16>>) -> Vec<u16> {
 struct Opts {
     #[bpaf(
         long,
-        argument::<String>("PORTS"),
-        parse(split_and_parse),
+        argument::<u16>("PORT"),
+        delimited(','),
         many,
         map(flatten_vec)
     )]
@@ -9,39 +9,34 @@ pub struct Options {
     extensions: Vec<(String, bool)>,
 }
 
-// matches literal name prefixed with - or +
-fn toggle_options(meta: &'static str, name: &'static str, help: &'static str) -> impl Parser<bool> {
-    any(meta, move |s: String| {
-        if let Some(suf) = s.strip_prefix('+') {
-            (suf == name).then_some(true)
-        } else if let Some(suf) = s.strip_prefix('-') {
-            (suf == name).then_some(false)
-        } else {
-            None
-        }
-    })
-    .help(help)
-    .anywhere()
+// +name turns the option on, -name turns it off
+fn toggle_options(name: &'static str, help: &'static str) -> impl Parser<bool> {
+    let on = long(name)
+        .prefix(NamePrefix::Plus)
+        .help(help)
+        .req_flag(true);
+    let off = long(name).single_dash().req_flag(false);
+    construct!([on, off])
 }
 
-// matches literal +ext and -ext followed by extension name
+// +ext and -ext followed by extension name
 fn extension() -> impl Parser<(String, bool)> {
-    let state = any("(+|-)ext", |s: String| match s.as_str() {
-        "-ext" => Some(false),
-        "+ext" => Some(true),
-        _ => None,
-    })
-    .anywhere();
-
-    let name = positional::<String>("EXT")
-        .help("Extension to enable or disable, see documentation for the full list");
-    construct!(state, name).adjacent().map(|(a, b)| (b, a))
+    let on = long("ext")
+        .prefix(NamePrefix::Plus)
+        .help("Extension to enable, see documentation for the full list")
+        .argument::<String>("EXT")
+        .map(|name| (name, true));
+    let off = long("ext")
+        .single_dash()
+        .help("Extension to disable")
+        .argument::<String>("EXT")
+        .map(|name| (name, false));
+    construct!([on, off])
 }
 
 pub fn options() -> OptionParser<Options> {
-    let backing = toggle_options("(+|-)backing", "backing", "Set backing status").fallback(false);
-    let xinerama =
-        toggle_options("(+|-)xinerama", "xinerama", "Set Xinerama status").fallback(true);
+    let backing = toggle_options("backing", "Set backing status").fallback(false);
+    let xinerama = toggle_options("xinerama", "Set Xinerama status").fallback(true);
     let turbo = short('t')
         .long("turbo")
         .help("Engage the turbo mode")
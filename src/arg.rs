@@ -1,5 +1,7 @@
 use std::ffi::{OsStr, OsString};
 
+use crate::item::NamePrefix;
+
 /// Preprocessed command line argument
 ///
 /// [`OsString`] in Short/Long correspond to orignal command line item used for errors
@@ -10,9 +12,9 @@ pub(crate) enum Arg {
     /// bool indicates if following item is also part of this Short (created
     Short(char, bool, OsString),
 
-    /// long flag: `--flag`
+    /// long flag: `--flag`, or `-flag` for declared single dash names
     /// bool tells if it looks like --key=val or not
-    Long(String, bool, OsString),
+    Long(NamePrefix, String, bool, OsString),
 
     /// "val" part of --key=val -k=val -kval
     ArgWord(OsString),
@@ -34,7 +36,7 @@ impl Arg {
     pub(crate) fn os_str(&self) -> &OsStr {
         match self {
             Arg::Short(_, _, s)
-            | Arg::Long(_, _, s)
+            | Arg::Long(_, _, _, s)
             | Arg::ArgWord(s)
             | Arg::Word(s)
             | Arg::PosWord(s) => s.as_ref(),
@@ -44,13 +46,13 @@ impl Arg {
     pub(crate) fn match_short(&self, val: char) -> bool {
         match self {
             Arg::Short(s, _, _) => *s == val,
-            Arg::ArgWord(_) | Arg::Long(_, _, _) | Arg::Word(_) | Arg::PosWord(_) => false,
+            Arg::ArgWord(_) | Arg::Long(_, _, _, _) | Arg::Word(_) | Arg::PosWord(_) => false,
         }
     }
 
-    pub(crate) fn match_long(&self, val: &str) -> bool {
+    pub(crate) fn match_long(&self, prefix: NamePrefix, val: &str) -> bool {
         match self {
-            Arg::Long(s, _, _) => *s == val,
+            Arg::Long(p, s, _, _) => *p == prefix && *s == val,
             Arg::Short(_, _, _) | Arg::ArgWord(_) | Arg::Word(_) | Arg::PosWord(_) => false,
         }
     }
@@ -78,7 +80,7 @@ impl std::fmt::Display for Arg {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Arg::Short(s, _, _) => write!(f, "-{}", s),
-            Arg::Long(l, _, _) => write!(f, "--{}", l),
+            Arg::Long(p, l, _, _) => write!(f, "{}{}", p.as_str(), l),
             Arg::ArgWord(w) | Arg::Word(w) | Arg::PosWord(w) => {
                 write!(f, "{}", w.to_string_lossy())
             }
@@ -86,10 +88,38 @@ impl std::fmt::Display for Arg {
     }
 }
 
-#[derive(Eq, PartialEq, Debug)]
+#[derive(Eq, PartialEq, Debug, Clone, Copy)]
 pub(crate) enum ArgType {
     Short,
     Long,
+    /// long name with one of the alternative prefixes: `-name`, `+name` or `/name`
+    Prefixed(NamePrefix),
+}
+
+impl ArgType {
+    /// Decide if a name with this type is one of the names declared with alternative prefixes
+    ///
+    /// `-name` declared as a single dash name wins over a group of short names,
+    /// `+name` and `/name` that are not declared are not names at all
+    fn resolve(self, prefixed: &[(NamePrefix, &str)], name: impl Fn(&str) -> bool) -> Option<Self> {
+        let declared = |prefix| prefixed.iter().any(|(p, n)| *p == prefix && name(n));
+        match self {
+            ArgType::Short if declared(NamePrefix::SingleDash) => {
+                Some(ArgType::Prefixed(NamePrefix::SingleDash))
+            }
+            ArgType::Prefixed(prefix) if !declared(prefix) => None,
+            ArgType::Short | ArgType::Long | ArgType::Prefixed(_) => Some(self),
+        }
+    }
+
+    /// Check if there are any names declared with this prefix, for items that might be names
+    fn expected(prefixed: &[(NamePrefix, &str)], prefix: NamePrefix) -> Option<Self> {
+        if prefixed.iter().any(|(p, _)| *p == prefix) {
+            Some(ArgType::Prefixed(prefix))
+        } else {
+            None
+        }
+    }
 }
 
 /// split [`OsString`] into argument specific bits
@@ -114,7 +144,14 @@ pub(crate) enum ArgType {
 /// Notation -fbar is ambigous and could mean either `-f -b -a -r` or `-f=bar`, resolve it into
 /// [`Arg::Ambiguity`] and let subparser disambiguate it later depending on available short flag and
 /// arguments
-pub(crate) fn split_os_argument(input: &std::ffi::OsStr) -> Option<(ArgType, String, Option<Arg>)> {
+///
+/// Notation `-name` and `-name=val` where `name` is declared in `prefixed` as a single dash name
+/// is always a [`ArgType::Prefixed`] long name and never goes through disambiguation. Similarly
+/// `+name` and `/name` are names only if they are declared in `prefixed`.
+pub(crate) fn split_os_argument(
+    input: &std::ffi::OsStr,
+    prefixed: &[(NamePrefix, &str)],
+) -> Option<(ArgType, String, Option<Arg>)> {
     #[cfg(any(unix, windows))]
     {
         // OsString are sequences of smaller smaller elements - bytes in unix and
@@ -142,9 +179,23 @@ pub(crate) fn split_os_argument(input: &std::ffi::OsStr) -> Option<(ArgType, Str
             Some(os_from_vec(vec).to_str()?.to_owned())
         }
 
+        // compare a name with a declared one without decoding it
+        fn same_name(name: &[Elt], declared: &str) -> bool {
+            #[cfg(unix)]
+            {
+                declared.as_bytes() == name
+            }
+            #[cfg(windows)]
+            {
+                declared.encode_utf16().eq(name.iter().copied())
+            }
+        }
+
         // but in either case dashes and equals are just literal values just with different width
         const DASH: Elt = b'-' as Elt;
         const EQUALS: Elt = b'=' as Elt;
+        const PLUS: Elt = b'+' as Elt;
+        const SLASH: Elt = b'/' as Elt;
 
         // preallocate something to store the name. oversized but avoids extra allocations/copying
         let mut name = Vec::with_capacity(input.len());
@@ -161,25 +212,27 @@ pub(crate) fn split_os_argument(input: &std::ffi::OsStr) -> Option<(ArgType, Str
             items = std::os::windows::ffi::OsStrExt::encode_wide(input);
         }
 
-        // first item must be dash, otherwise it's positional or a flag value
-        if items.next()? != DASH {
-            return None;
-        }
-
-        // second item may or may not be, but should be present
-        let ty;
-        match items.next()? {
-            DASH => ty = ArgType::Long,
-            val => {
-                ty = ArgType::Short;
-                name.push(val);
-            }
-        }
+        // first item must be dash or one of declared prefixes,
+        // otherwise it's positional or a flag value
+        let mut ty = match items.next()? {
+            // second item may or may not be, but should be present
+            DASH => match items.next()? {
+                DASH => ArgType::Long,
+                val => {
+                    name.push(val);
+                    ArgType::Short
+                }
+            },
+            PLUS => ArgType::expected(prefixed, NamePrefix::Plus)?,
+            SLASH => ArgType::expected(prefixed, NamePrefix::Slash)?,
+            _ => return None,
+        };
 
         // keep collecting until = or the end of the input
         loop {
             match items.next() {
                 Some(EQUALS) => {
+                    ty = ty.resolve(prefixed, |n| same_name(&name, n))?;
                     if ty == ArgType::Short && name.len() > 1 {
                         let mut body = name.drain(1..).collect::<Vec<_>>();
                         body.push(EQUALS);
@@ -195,6 +248,7 @@ pub(crate) fn split_os_argument(input: &std::ffi::OsStr) -> Option<(ArgType, Str
                     if name.is_empty() {
                         return None;
                     }
+                    ty = ty.resolve(prefixed, |n| same_name(&name, n))?;
                     return Some((ty, str_from_vec(name)?, None));
                 }
             }
@@ -209,7 +263,7 @@ pub(crate) fn split_os_argument(input: &std::ffi::OsStr) -> Option<(ArgType, Str
     }
     #[cfg(not(any(unix, windows)))]
     {
-        split_os_argument_fallback(input)
+        split_os_argument_fallback(input, prefixed)
     }
 }
 
@@ -218,6 +272,7 @@ pub(crate) fn split_os_argument(input: &std::ffi::OsStr) -> Option<(ArgType, Str
 #[cfg(any(all(not(windows), not(unix)), test))]
 pub(crate) fn split_os_argument_fallback(
     input: &std::ffi::OsStr,
+    prefixed: &[(NamePrefix, &str)],
 ) -> Option<(ArgType, String, Option<Arg>)> {
     // fallback supports only valid utf8 os strings, matches old behavior
     let string = input.to_str()?;
@@ -225,26 +280,28 @@ pub(crate) fn split_os_argument_fallback(
     let mut chars = string.chars();
     let mut name = String::with_capacity(string.len());
 
-    // first character must be dash, otherwise it's positional or a flag value
-    if chars.next()? != '-' {
-        return None;
-    }
-
-    // second character may or may not be
-    let ty;
-    match chars.next()? {
-        '-' => ty = ArgType::Long,
-        val => {
-            ty = ArgType::Short;
-            name.push(val);
-        }
-    }
+    // first character must be dash or one of declared prefixes,
+    // otherwise it's positional or a flag value
+    let mut ty = match chars.next()? {
+        // second character may or may not be
+        '-' => match chars.next()? {
+            '-' => ArgType::Long,
+            val => {
+                name.push(val);
+                ArgType::Short
+            }
+        },
+        '+' => ArgType::expected(prefixed, NamePrefix::Plus)?,
+        '/' => ArgType::expected(prefixed, NamePrefix::Slash)?,
+        _ => return None,
+    };
 
     // collect the argument's name up to '=' or until the end
     // if it's a flag
     loop {
         match chars.next() {
             Some('=') => {
+                ty = ty.resolve(prefixed, |n| n == name)?;
                 if ty == ArgType::Short && name.len() > 1 {
                     let mut body = name.drain(1..).collect::<String>();
                     body.push('=');
@@ -261,6 +318,7 @@ pub(crate) fn split_os_argument_fallback(
                 if name.is_empty() {
                     return None;
                 }
+                ty = ty.resolve(prefixed, |n| n == name)?;
                 return Some((ty, name, None));
             }
         }
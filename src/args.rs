@@ -1,11 +1,12 @@
-use std::ffi::OsString;
+use std::{borrow::Cow, ffi::OsString};
 
 pub(crate) use crate::arg::*;
 use crate::{
-    error::{Message, MissingItem},
-    item::Item,
+    error::{Message, MissingItem, ResponseFileError},
+    item::{Item, NamePrefix, ShortLong},
     meta_help::Metavar,
     parsers::NamedArg,
+    shell_words::{self, CommandLineError},
     Error,
 };
 
@@ -37,6 +38,11 @@ pub struct Args<'a> {
     name: Option<String>,
     #[cfg(feature = "autocomplete")]
     c_rev: Option<usize>,
+    /// Expand `@path` items, see [`expand_response_files`](crate::OptionParser::expand_response_files)
+    pub(crate) response_files: bool,
+    /// Width of caret diagnostics if enabled, see
+    /// [`caret_diagnostics`](crate::OptionParser::caret_diagnostics)
+    pub(crate) carets: Option<usize>,
 }
 
 impl Args<'_> {
@@ -91,6 +97,8 @@ impl<const N: usize> From<&'static [&'static str; N]> for Args<'_> {
             #[cfg(feature = "autocomplete")]
             c_rev: None,
             name: None,
+            response_files: false,
+            carets: None,
         }
     }
 }
@@ -102,6 +110,8 @@ impl<'a> From<&'a [&'a std::ffi::OsStr]> for Args<'a> {
             #[cfg(feature = "autocomplete")]
             c_rev: None,
             name: None,
+            response_files: false,
+            carets: None,
         }
     }
 }
@@ -113,6 +123,8 @@ impl<'a> From<&'a [&'a str]> for Args<'a> {
             #[cfg(feature = "autocomplete")]
             c_rev: None,
             name: None,
+            response_files: false,
+            carets: None,
         }
     }
 }
@@ -124,6 +136,8 @@ impl<'a> From<&'a [String]> for Args<'a> {
             #[cfg(feature = "autocomplete")]
             c_rev: None,
             name: None,
+            response_files: false,
+            carets: None,
         }
     }
 }
@@ -135,6 +149,8 @@ impl<'a> From<&'a [OsString]> for Args<'a> {
             #[cfg(feature = "autocomplete")]
             c_rev: None,
             name: None,
+            response_files: false,
+            carets: None,
         }
     }
 }
@@ -155,6 +171,65 @@ impl Args<'_> {
             #[cfg(feature = "autocomplete")]
             c_rev: None,
             name,
+            response_files: false,
+            carets: None,
+        }
+    }
+
+    /// Split a string into command line arguments the same way POSIX shell does it
+    ///
+    /// Whitespace separates arguments, single and double quotes and backslashes work as in
+    /// shell, backslash followed by a newline is removed. There's no program name in the
+    /// string, all the words are arguments, use [`set_name`](Args::set_name) to set one.
+    ///
+    /// Unterminated quotes and trailing backslashes are reported as
+    /// [`CommandLineError`](crate::CommandLineError) with the offset in characters.
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// let parser = long("name").argument::<String>("NAME").to_options();
+    /// let args = Args::from_command_line("--name 'Bob \"B\" Smith'").unwrap();
+    /// assert_eq!(parser.run_inner(args).unwrap(), "Bob \"B\" Smith");
+    ///
+    /// let err = Args::from_command_line("--name 'Bob").err().unwrap();
+    /// assert_eq!(err.offset(), 7);
+    /// ```
+    ///
+    /// # Errors
+    /// When quotes are not balanced or input ends with a backslash
+    pub fn from_command_line(line: &str) -> Result<Self, CommandLineError> {
+        Ok(Self::from_words(shell_words::split(line, false)?))
+    }
+
+    /// Split a string user is still typing into command line arguments
+    ///
+    /// Similar to [`from_command_line`](Args::from_command_line) but accepts unterminated
+    /// quotes and backslashes, trailing whitespace starts a new empty argument. Use it with
+    /// [`set_comp`](Args::set_comp) to complete a partial command line.
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// let parser = long("name").argument::<String>("NAME").to_options();
+    /// let r = parser
+    ///     .run_inner(Args::from_partial_command_line("--na").set_comp(0))
+    ///     .unwrap_err()
+    ///     .unwrap_stdout();
+    /// assert_eq!(r, "--name");
+    /// ```
+    #[must_use]
+    pub fn from_partial_command_line(line: &str) -> Self {
+        // in partial mode splitting never fails
+        Self::from_words(shell_words::split(line, true).unwrap_or_default())
+    }
+
+    fn from_words(words: Vec<String>) -> Self {
+        Self {
+            items: Box::new(words.into_iter().map(OsString::from)),
+            #[cfg(feature = "autocomplete")]
+            c_rev: None,
+            name: None,
+            response_files: false,
+            carets: None,
         }
     }
 }
@@ -185,6 +260,77 @@ impl ItemState {
     }
 }
 
+/// Long names accepted at one command level, used to expand abbreviations
+///
+/// Every subcommand gets a table of its own so a prefix is matched against names of the
+/// command being parsed
+#[derive(Debug, Default)]
+pub(crate) struct LongNames<'a> {
+    /// names of flags and arguments, each paired with the first name of the item it belongs to
+    pub(crate) names: Vec<(&'a str, &'a str)>,
+    /// names of hidden items, they are accepted as is but not abbreviated
+    pub(crate) exact: Vec<&'a str>,
+    /// names of arguments, a word that follows one of them is a value and not a command
+    pub(crate) arguments: Vec<&'a str>,
+    /// names of subcommands along with their tables
+    pub(crate) commands: Vec<(Vec<Cow<'a, str>>, LongNames<'a>)>,
+}
+
+impl<'a> LongNames<'a> {
+    fn command(&self, word: &str) -> Option<&Self> {
+        self.commands
+            .iter()
+            .find(|(names, _)| names.iter().any(|name| name == word))
+            .map(|(_, level)| level)
+    }
+}
+
+/// Expand a unique prefix of a known long name into the full name
+///
+/// `levels` go from the top level parser to the innermost command, names of the innermost
+/// command are tried first. Returns `Ok(None)` if the name is known or doesn't match anything,
+/// `Err` contains all the candidates for an ambiguous prefix. A prefix of several aliases of
+/// the same item is not ambiguous
+fn expand_long_prefix<'a>(
+    long: &str,
+    levels: &[&LongNames<'a>],
+) -> Result<Option<&'a str>, Vec<&'a str>> {
+    if levels.iter().any(|level| {
+        level.names.iter().any(|(name, _)| *name == long) || level.exact.contains(&long)
+    }) {
+        return Ok(None);
+    }
+    for level in levels.iter().rev() {
+        let mut candidates = level
+            .names
+            .iter()
+            .copied()
+            .filter(|(name, _)| name.starts_with(long))
+            .collect::<Vec<_>>();
+        candidates.sort_unstable();
+        candidates.dedup();
+        match candidates.as_slice() {
+            [] => {}
+            [(name, owner), rest @ ..] if rest.iter().all(|(_, o)| o == owner) => {
+                return Ok(Some(name))
+            }
+            _ => return Err(candidates.into_iter().map(|(name, _)| name).collect()),
+        }
+    }
+    Ok(None)
+}
+
+/// Check if the last item is a name of an argument that expects a value in the next word
+fn expects_value(items: &[Arg], levels: &[&LongNames], short_args: &[char]) -> bool {
+    match items.last() {
+        Some(Arg::Short(c, false, _)) => short_args.contains(c),
+        Some(Arg::Long(NamePrefix::DoubleDash, name, false, _)) => levels
+            .iter()
+            .any(|level| level.arguments.contains(&name.as_str())),
+        _ => false,
+    }
+}
+
 fn disambiguate_short(
     mut os: OsString,
     short: String,
@@ -245,20 +391,131 @@ fn disambiguate_short(
     None
 }
 
+/// Response files that are currently being expanded, innermost file goes last
+#[derive(Default)]
+struct ResponseFiles {
+    stack: Vec<ResponseFile>,
+}
+
+struct ResponseFile {
+    /// canonical path, used to detect cycles
+    path: std::path::PathBuf,
+    /// name as given by the user, used for error messages
+    name: String,
+    /// remaining words along with line numbers they came from
+    words: std::vec::IntoIter<(usize, OsString)>,
+    /// line number of the last word taken from this file
+    line: usize,
+}
+
+impl ResponseFiles {
+    /// Take the next item either from the innermost response file or from the command line
+    fn next<I>(&mut self, args: &mut I) -> Option<OsString>
+    where
+        I: Iterator<Item = OsString>,
+    {
+        while let Some(file) = self.stack.last_mut() {
+            if let Some((line, word)) = file.words.next() {
+                file.line = line;
+                return Some(word);
+            }
+            self.stack.pop();
+        }
+        args.next()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.stack.is_empty()
+    }
+
+    /// Start expanding `@path`, returns `Ok(false)` if item doesn't refer to a response file
+    fn expand(&mut self, os: &std::ffi::OsStr) -> Result<bool, Message> {
+        let name = match os.to_str().and_then(|s| s.strip_prefix('@')) {
+            Some(name) if !name.is_empty() => name,
+            _ => return Ok(false),
+        };
+
+        // errors in nested files are reported at the line that refers to them
+        let location = self.stack.last().map(|f| (f.name.clone(), f.line));
+        let read_error =
+            |e: std::io::Error| ResponseFileError::Read(name.to_owned(), e.to_string());
+
+        // relative paths in response files are relative to the file that mentions them
+        let mut target = std::path::PathBuf::from(name);
+        if target.is_relative() {
+            if let Some(dir) = self.stack.last().and_then(|f| f.path.parent()) {
+                target = dir.join(target);
+            }
+        }
+        let path = match std::fs::canonicalize(target) {
+            Ok(path) => path,
+            Err(e) => return Err(Message::ResponseFile(location, read_error(e))),
+        };
+        if let Some(start) = self.stack.iter().position(|f| f.path == path) {
+            let via = self.stack[start + 1..]
+                .iter()
+                .map(|f| f.name.clone())
+                .collect();
+            let err = ResponseFileError::Cycle(name.to_owned(), via);
+            return Err(Message::ResponseFile(location, err));
+        }
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) => return Err(Message::ResponseFile(location, read_error(e))),
+        };
+
+        let mut words = Vec::new();
+        for (ix, line) in contents.lines().enumerate() {
+            match split_response_line(line) {
+                Ok(ws) => words.extend(ws.into_iter().map(|w| (ix + 1, OsString::from(w)))),
+                Err(err) => {
+                    return Err(Message::ResponseFile(Some((name.to_owned(), ix + 1)), err))
+                }
+            }
+        }
+
+        self.stack.push(ResponseFile {
+            path,
+            name: name.to_owned(),
+            words: words.into_iter(),
+            line: 0,
+        });
+        Ok(true)
+    }
+}
+
+/// Split a line from a response file into words, see [`shell_words::split`]
+fn split_response_line(line: &str) -> Result<Vec<String>, ResponseFileError> {
+    shell_words::split(line, false).map_err(|e| ResponseFileError::Words(e.error))
+}
+
 pub use inner::State;
 /// Hides [`State`] internal implementation
 mod inner {
-    use std::{ops::Range, rc::Rc};
-
-    use crate::{error::Message, item::Item, Args};
-
-    use super::{split_os_argument, Arg, ArgType, ItemState};
+    use std::{ffi::OsString, ops::Range, rc::Rc, sync::Arc};
+
+    use crate::{
+        diagnostic::CommandLine,
+        error::{Current, Message},
+        item::{Item, NamePrefix},
+        messages::Catalog,
+        Args, NamedArg, ValueSource,
+    };
+
+    use super::{
+        expand_long_prefix, expects_value, split_os_argument, Arg, ArgType, ItemState, LongNames,
+        ResponseFiles,
+    };
     #[derive(Clone, Debug)]
     #[doc(hidden)]
     pub struct State {
         /// list of all available command line arguments, in `Rc` for cheap cloning
         pub(crate) items: Rc<[Arg]>,
 
+        /// index of the command line argument each item comes from, items from response files
+        /// point to the `@path` argument
+        origins: Rc<[usize]>,
+
         item_state: Vec<ItemState>,
 
         /// performance optimization mostly - tracks removed item and gives cheap is_empty and len
@@ -270,9 +527,39 @@ mod inner {
         /// item
         pub current: Option<usize>,
 
+        /// name of the item that took the current value, reset when anything else is consumed
+        pub(crate) current_name: Option<String>,
+
         /// path to current command, "deeper" parser should win in or_else branches
         pub(crate) path: Vec<String>,
 
+        /// where the last value came from if not from the command line, see
+        /// [`with_source`](crate::Parser::with_source)
+        pub(crate) source: Option<ValueSource>,
+
+        /// indices of names consumed so far together with items they belong to, see
+        /// [`check_relations`](State::check_relations)
+        names: Vec<(usize, NamedArg)>,
+
+        /// names mentioned in relations, only items with relations of their own or spelled
+        /// like one of those names are kept in `names`
+        pub(crate) related: Rc<[String]>,
+
+        /// deprecated items consumed so far together with migration hints
+        pub(crate) warnings: Vec<(usize, &'static str)>,
+
+        /// catalog to render help and errors with, see
+        /// [`messages`](crate::OptionParser::messages)
+        pub(crate) messages: Catalog,
+
+        /// keep evaluating [`construct!`](crate::construct!) fields after a failure, see
+        /// [`accumulate_errors`](crate::OptionParser::accumulate_errors)
+        pub(crate) accumulate: bool,
+
+        /// original command line for caret diagnostics, see
+        /// [`caret_diagnostics`](crate::OptionParser::caret_diagnostics)
+        pub(crate) command_line: Option<Arc<CommandLine>>,
+
         #[cfg(feature = "autocomplete")]
         comp: Option<crate::complete_gen::Complete>,
 
@@ -319,6 +606,8 @@ mod inner {
             args: Args,
             short_flags: &[char],
             short_args: &[char],
+            long_names: &LongNames,
+            prefixed: &[(NamePrefix, &str)],
             err: &mut Option<Message>,
         ) -> State {
             let mut items = Vec::new();
@@ -331,7 +620,25 @@ mod inner {
                 name: args.name.as_deref(),
             };
 
-            for os in args.items {
+            let mut argv = args.items;
+            let mut command_line = None;
+            if let Some(width) = args.carets {
+                let words = argv.collect::<Vec<_>>();
+                let line = CommandLine::new(args.name.as_deref(), &words, width);
+                command_line = Some(Arc::new(line));
+                argv = Box::new(words.into_iter());
+            }
+            let argv_len = argv.len();
+            let mut origins = Vec::new();
+            let mut origin = 0;
+            let mut response_files = ResponseFiles::default();
+            // abbreviations are expanded using names of the innermost command seen so far
+            let mut levels = vec![long_names];
+            while let Some(os) = response_files.next(&mut argv) {
+                // items pushed so far come from the previous argument
+                origins.resize(items.len(), origin);
+                origin = argv_len - argv.len() - 1;
+
                 if pos_only {
                     items.push(Arg::PosWord(os));
                     continue;
@@ -342,7 +649,32 @@ mod inner {
                     continue;
                 }
 
-                match split_os_argument(&os) {
+                #[cfg(feature = "autocomplete")]
+                let completing = comp_scanner.revision.is_some();
+                #[cfg(not(feature = "autocomplete"))]
+                let completing = false;
+
+                // the last item is what user is typing right now, during completion it should
+                // be left alone so completion can suggest something for it
+                let typing = completing && response_files.is_empty() && argv.len() == 0;
+
+                if args.response_files && !typing {
+                    match response_files.expand(&os) {
+                        Ok(true) => continue,
+                        Ok(false) => {}
+                        // during completion broken response files are treated as plain words
+                        Err(_) if completing => {
+                            items.push(Arg::Word(os));
+                            continue;
+                        }
+                        Err(msg) => {
+                            *err = Some(msg);
+                            break;
+                        }
+                    }
+                }
+
+                match split_os_argument(&os, prefixed) {
                     // -f and -fbar, but also -vvvvv
                     Some((ArgType::Short, short, None)) => {
                         if let Some(msg) = super::disambiguate_short(
@@ -362,8 +694,36 @@ mod inner {
                         items.push(arg);
                     }
                     // --key and --key=val
-                    Some((ArgType::Long, long, arg)) => {
-                        items.push(Arg::Long(long, arg.is_some(), os));
+                    Some((ArgType::Long, mut long, arg)) => {
+                        if !typing {
+                            match expand_long_prefix(&long, &levels) {
+                                Ok(None) => {}
+                                Ok(Some(full)) => long = full.to_owned(),
+                                // during completion ambiguous prefixes are left as is
+                                Err(_) if completing => {}
+                                Err(candidates) => {
+                                    let candidates =
+                                        candidates.into_iter().map(String::from).collect();
+                                    *err = Some(Message::AmbiguousPrefix(items.len(), candidates));
+                                    items.push(Arg::Long(
+                                        NamePrefix::DoubleDash,
+                                        long,
+                                        arg.is_some(),
+                                        os,
+                                    ));
+                                    break;
+                                }
+                            }
+                        }
+                        items.push(Arg::Long(NamePrefix::DoubleDash, long, arg.is_some(), os));
+                        if let Some(arg) = arg {
+                            items.push(arg);
+                        }
+                    }
+                    // -name, +name, /name and -name=val, declared single dash names win over
+                    // short names
+                    Some((ArgType::Prefixed(prefix), long, arg)) => {
+                        items.push(Arg::Long(prefix, long, arg.is_some(), os));
                         if let Some(arg) = arg {
                             items.push(arg);
                         }
@@ -374,6 +734,12 @@ mod inner {
                         if os == "--" {
                             double_dash_marker = Some(items.len());
                             pos_only = true;
+                        } else if !expects_value(&items, &levels, short_args) {
+                            let level =
+                                levels.last().and_then(|l| l.command(&os.to_string_lossy()));
+                            if let Some(level) = level {
+                                levels.push(level);
+                            }
                         }
                         items.push(if pos_only {
                             Arg::PosWord(os)
@@ -384,6 +750,8 @@ mod inner {
                 }
             }
 
+            origins.resize(items.len(), origin);
+
             let mut item_state = vec![ItemState::Unparsed; items.len()];
             let mut remaining = items.len();
             if let Some(ix) = double_dash_marker {
@@ -410,8 +778,17 @@ mod inner {
                 remaining,
                 scope: 0..items.len(),
                 items: items.into(),
+                origins: origins.into(),
                 current: None,
+                current_name: None,
                 path,
+                source: None,
+                names: Vec::new(),
+                related: Vec::new().into(),
+                warnings: Vec::new(),
+                messages: Catalog::default(),
+                accumulate: false,
+                command_line,
                 #[cfg(feature = "autocomplete")]
                 comp,
             }
@@ -430,11 +807,46 @@ mod inner {
         pub(crate) fn remove(&mut self, index: usize) {
             if self.scope.contains(&index) && self.item_state[index].present() {
                 self.current = Some(index);
+                self.current_name = None;
                 self.remaining -= 1;
                 self.item_state[index] = ItemState::Parsed;
             }
         }
 
+        /// Item that produced the last value, used to report which item failed to parse
+        pub(crate) fn current_item(&self) -> Current {
+            Current(self.current, self.current_name.clone())
+        }
+
+        /// Command line argument index of an item, items from response files point to `@path`
+        pub(crate) fn origin(&self, ix: usize) -> usize {
+            self.origins[ix]
+        }
+
+        /// Command line argument indices of items consumed since `before` and the original text
+        /// of the item that produced the value - current one or the last consumed one
+        pub(crate) fn span_since(&self, before: &Self) -> (Vec<usize>, Option<OsString>) {
+            let consumed = (0..self.items.len())
+                .filter(|ix| before.item_state[*ix].present() && !self.item_state[*ix].present())
+                .collect::<Vec<_>>();
+
+            let mut indices = consumed
+                .iter()
+                .map(|ix| self.origins[*ix])
+                .collect::<Vec<_>>();
+            indices.dedup();
+
+            let os = self
+                .current
+                .filter(|ix| consumed.contains(ix))
+                .or_else(|| consumed.last().copied())
+                .map(|ix| match &self.items[ix] {
+                    Arg::ArgWord(s) | Arg::Word(s) | Arg::PosWord(s) => s.clone(),
+                    arg @ (Arg::Short(_, _, _) | Arg::Long(_, _, _, _)) => arg.to_string().into(),
+                });
+            (indices, os)
+        }
+
         pub(crate) fn pick_winner(&self, other: &Self) -> (bool, Option<usize>) {
             for (ix, (me, other)) in self
                 .item_state
@@ -449,6 +861,68 @@ mod inner {
             (true, None)
         }
 
+        /// Remember a name consumed at `ix` by `named` item, warn if the item is deprecated
+        pub(crate) fn name_used(&mut self, ix: usize, named: &NamedArg) {
+            if let Some(hint) = named.deprecated {
+                self.warnings.push((ix, hint));
+            }
+            if named.relations.is_empty() && !self.related.iter().any(|n| named.is_spelled(n)) {
+                return;
+            }
+            let named = NamedArg {
+                short: named.short.clone(),
+                long: named.long.clone(),
+                prefix: named.prefix,
+                env: Vec::new(),
+                help: None,
+                relations: named.relations.clone(),
+                deprecated: None,
+                short_aliases: named.short_aliases.clone(),
+                long_aliases: named.long_aliases.clone(),
+            };
+            self.names.push((ix, named));
+        }
+
+        /// Number of names consumed so far, passed back to
+        /// [`check_relations`](State::check_relations)
+        pub(crate) fn names_used(&self) -> usize {
+            self.names.len()
+        }
+
+        /// Check relations between the names consumed since `start`
+        ///
+        /// Names are forgotten after the check so that names consumed by a subcommand
+        /// don't affect relations of its parent
+        pub(crate) fn check_relations(&mut self, start: usize) -> Option<Message> {
+            let names = &self.names[start.min(self.names.len())..];
+            let find = |name: &str| {
+                names
+                    .iter()
+                    .find(|(_, named)| named.is_spelled(name))
+                    .map(|(ix, _)| *ix)
+            };
+            for (ix, named) in names {
+                let relations = &named.relations;
+                for name in &relations.conflicts {
+                    if let Some(other) = find(name) {
+                        let (winner, loser) = if *ix < other {
+                            (*ix, other)
+                        } else {
+                            (other, *ix)
+                        };
+                        return Some(Message::Conflict(winner, loser));
+                    }
+                }
+                for name in &relations.requires {
+                    if find(name).is_none() {
+                        return Some(Message::Requires(*ix, name.clone()));
+                    }
+                }
+            }
+            self.names.truncate(start);
+            None
+        }
+
         /// find first saved conflict
         pub(crate) fn conflict(&self) -> Option<(usize, usize)> {
             let (ix, _item) = self.items_iter().next()?;
@@ -657,12 +1131,43 @@ impl State {
             .find(|arg| named.matches_arg(arg.1, false))
         {
             self.remove(ix);
+            self.name_used(ix, named);
             true
         } else {
             false
         }
     }
 
+    /// remove all the occurrences of a negatable switch, see
+    /// [`negatable_switch`](NamedArg::negatable_switch)
+    ///
+    /// Returns `Some(true)` if the last one was `named`, `Some(false)` if it was `negated`.
+    /// Only a switch that ends up turned on counts as a use of `named` for relations and
+    /// deprecation warnings.
+    pub(crate) fn take_negatable_flag(
+        &mut self,
+        named: &NamedArg,
+        negated: &NamedArg,
+    ) -> Option<bool> {
+        let mut last = None;
+        while let Some((ix, present)) = self.items_iter().find_map(|(ix, arg)| {
+            if named.matches_arg(arg, false) {
+                Some((ix, true))
+            } else if negated.matches_arg(arg, false) {
+                Some((ix, false))
+            } else {
+                None
+            }
+        }) {
+            self.remove(ix);
+            last = Some((ix, present));
+        }
+        if let Some((ix, true)) = last {
+            self.name_used(ix, named);
+        }
+        last.map(|(_, present)| present)
+    }
+
     /// get a short or long arguments
     ///
     /// Returns Ok(None) if flag isn't present
@@ -671,7 +1176,7 @@ impl State {
         &mut self,
         named: &NamedArg,
         adjacent: bool,
-        metavar: Metavar,
+        metavar: &Metavar,
     ) -> Result<Option<OsString>, Error> {
         let (key_ix, _arg) = match self
             .items_iter()
@@ -684,12 +1189,14 @@ impl State {
         let val_ix = key_ix + 1;
         let val = match self.get(val_ix) {
             Some(Arg::Word(w) | Arg::ArgWord(w)) => w,
-            _ => return Err(Error(Message::NoArgument(key_ix, metavar))),
+            _ => return Err(Error(Message::NoArgument(key_ix, metavar.clone()))),
         };
         let val = val.clone();
         self.current = Some(val_ix);
         self.remove(key_ix);
         self.remove(val_ix);
+        self.current_name = ShortLong::try_from(named).ok().map(|n| n.spelled());
+        self.name_used(key_ix, named);
         Ok(Some(val))
     }
 
@@ -699,7 +1206,7 @@ impl State {
     /// returns Err if first positional argument is a flag
     pub(crate) fn take_positional_word(
         &mut self,
-        metavar: Metavar,
+        metavar: &Metavar,
     ) -> Result<(usize, bool, OsString), Error> {
         match self.items_iter().find_map(|(ix, arg)| match arg {
             Arg::Word(w) => Some((ix, false, w)),
@@ -710,6 +1217,7 @@ impl State {
                 let w = w.clone();
                 self.current = Some(ix);
                 self.remove(ix);
+                self.current_name = Some(metavar.0.to_string());
                 Ok((ix, strict, w))
             }
             None => {
@@ -717,7 +1225,8 @@ impl State {
                 let missing = MissingItem {
                     item: Item::Positional {
                         help: None,
-                        metavar,
+                        metavar: metavar.clone(),
+                        possible: Vec::new(),
                     },
                     position: scope.start,
                     scope,
@@ -729,7 +1238,7 @@ impl State {
 
     /// take a static string argument from the first present argument
     pub(crate) fn take_cmd(&mut self, word: &str) -> bool {
-        if let Some((ix, Arg::Word(w) | Arg::Short(_, _, w) | Arg::Long(_, false, w))) =
+        if let Some((ix, Arg::Word(w) | Arg::Short(_, _, w) | Arg::Long(_, _, false, w))) =
             self.items_iter().next()
         {
             if w == word {
@@ -739,6 +1248,7 @@ impl State {
             }
         }
         self.current = None;
+        self.current_name = None;
         false
     }
 
@@ -752,15 +1262,16 @@ impl State {
 mod tests {
     use super::*;
     use crate::meta_help::Metavar;
+    use crate::shell_words::WordsError;
     use crate::{long, short};
-    const M: Metavar = Metavar("M");
+    const M: Metavar = Metavar(std::borrow::Cow::Borrowed("M"));
 
     #[allow(clippy::fallible_impl_from)] // this is for tests only, panic is okay
     impl<const N: usize> From<&'static [&'static str; N]> for State {
         fn from(value: &'static [&'static str; N]) -> Self {
             let args = Args::from(value);
             let mut msg = None;
-            let res = State::construct(args, &[], &[], &mut msg);
+            let res = State::construct(args, &[], &[], &LongNames::default(), &[], &mut msg);
             if let Some(err) = &msg {
                 panic!("Couldn't construct state: {:?}/{:?}", err, res);
             }
@@ -771,7 +1282,7 @@ mod tests {
     #[test]
     fn long_arg() {
         let mut a = State::from(&["--speed", "12"]);
-        let s = a.take_arg(&long("speed"), false, M).unwrap().unwrap();
+        let s = a.take_arg(&long("speed"), false, &M).unwrap().unwrap();
         assert_eq!(s, "12");
         assert!(a.is_empty());
     }
@@ -781,7 +1292,7 @@ mod tests {
         let flag = a.take_flag(&long("speed"));
         assert!(flag);
         assert!(!a.is_empty());
-        let s = a.take_positional_word(M).unwrap();
+        let s = a.take_positional_word(&M).unwrap();
         assert_eq!(s.2, "12");
         assert!(a.is_empty());
     }
@@ -790,7 +1301,7 @@ mod tests {
     fn multiple_short_flags() {
         let args = Args::from(&["-vvv"]);
         let mut err = None;
-        let mut a = State::construct(args, &['v'], &[], &mut err);
+        let mut a = State::construct(args, &['v'], &[], &LongNames::default(), &[], &mut err);
         assert!(a.take_flag(&short('v')));
         assert!(a.take_flag(&short('v')));
         assert!(a.take_flag(&short('v')));
@@ -801,7 +1312,7 @@ mod tests {
     #[test]
     fn long_arg_with_equality() {
         let mut a = State::from(&["--speed=12"]);
-        let s = a.take_arg(&long("speed"), false, M).unwrap().unwrap();
+        let s = a.take_arg(&long("speed"), false, &M).unwrap().unwrap();
         assert_eq!(s, "12");
         assert!(a.is_empty());
     }
@@ -809,7 +1320,7 @@ mod tests {
     #[test]
     fn long_arg_with_equality_and_minus() {
         let mut a = State::from(&["--speed=-12"]);
-        let s = a.take_arg(&long("speed"), true, M).unwrap().unwrap();
+        let s = a.take_arg(&long("speed"), true, &M).unwrap().unwrap();
         assert_eq!(s, "-12");
         assert!(a.is_empty());
     }
@@ -817,7 +1328,7 @@ mod tests {
     #[test]
     fn short_arg_with_equality() {
         let mut a = State::from(&["-s=12"]);
-        let s = a.take_arg(&short('s'), false, M).unwrap().unwrap();
+        let s = a.take_arg(&short('s'), false, &M).unwrap().unwrap();
         assert_eq!(s, "12");
         assert!(a.is_empty());
     }
@@ -825,7 +1336,7 @@ mod tests {
     #[test]
     fn short_arg_with_equality_and_minus() {
         let mut a = State::from(&["-s=-12"]);
-        let s = a.take_arg(&short('s'), false, M).unwrap().unwrap();
+        let s = a.take_arg(&short('s'), false, &M).unwrap().unwrap();
         assert_eq!(s, "-12");
         assert!(a.is_empty());
     }
@@ -833,7 +1344,7 @@ mod tests {
     #[test]
     fn short_arg_with_equality_and_minus_is_adjacent() {
         let mut a = State::from(&["-s=-12"]);
-        let s = a.take_arg(&short('s'), true, M).unwrap().unwrap();
+        let s = a.take_arg(&short('s'), true, &M).unwrap().unwrap();
         assert_eq!(s, "-12");
         assert!(a.is_empty());
     }
@@ -841,7 +1352,7 @@ mod tests {
     #[test]
     fn short_arg_without_equality() {
         let mut a = State::from(&["-s", "12"]);
-        let s = a.take_arg(&short('s'), false, M).unwrap().unwrap();
+        let s = a.take_arg(&short('s'), false, &M).unwrap().unwrap();
         assert_eq!(s, "12");
         assert!(a.is_empty());
     }
@@ -868,7 +1379,7 @@ mod tests {
     fn command_with_flags() {
         let mut a = State::from(&["cmd", "-s", "v"]);
         assert!(a.take_cmd("cmd"));
-        let s = a.take_arg(&short('s'), false, M).unwrap().unwrap();
+        let s = a.take_arg(&short('s'), false, &M).unwrap().unwrap();
         assert_eq!(s, "v");
         assert!(a.is_empty());
     }
@@ -877,7 +1388,7 @@ mod tests {
     fn command_and_positional() {
         let mut a = State::from(&["cmd", "pos"]);
         assert!(a.take_cmd("cmd"));
-        let w = a.take_positional_word(M).unwrap();
+        let w = a.take_positional_word(&M).unwrap();
         assert_eq!(w.2, "pos");
         assert!(a.is_empty());
     }
@@ -886,7 +1397,7 @@ mod tests {
     fn positionals_after_double_dash1() {
         let mut a = State::from(&["-v", "--", "-x"]);
         assert!(a.take_flag(&short('v')));
-        let w = a.take_positional_word(M).unwrap();
+        let w = a.take_positional_word(&M).unwrap();
         assert_eq!(w.2, "-x");
         assert!(a.is_empty());
     }
@@ -895,7 +1406,7 @@ mod tests {
     fn positionals_after_double_dash2() {
         let mut a = State::from(&["-v", "--", "-x"]);
         assert!(a.take_flag(&short('v')));
-        let w = a.take_positional_word(M).unwrap();
+        let w = a.take_positional_word(&M).unwrap();
         assert_eq!(w.2, "-x");
         assert!(a.is_empty());
     }
@@ -903,9 +1414,9 @@ mod tests {
     #[test]
     fn positionals_after_double_dash3() {
         let mut a = State::from(&["-v", "12", "--", "-x"]);
-        let w = a.take_arg(&short('v'), false, M).unwrap().unwrap();
+        let w = a.take_arg(&short('v'), false, &M).unwrap().unwrap();
         assert_eq!(w, "12");
-        let w = a.take_positional_word(M).unwrap();
+        let w = a.take_positional_word(&M).unwrap();
         assert_eq!(w.2, "-x");
         assert!(a.is_empty());
     }
@@ -914,7 +1425,14 @@ mod tests {
     fn ambiguity_towards_flag() {
         let args = Args::from(&["-abc"]);
         let mut err = None;
-        let mut a = State::construct(args, &['a', 'b', 'c'], &[], &mut err);
+        let mut a = State::construct(
+            args,
+            &['a', 'b', 'c'],
+            &[],
+            &LongNames::default(),
+            &[],
+            &mut err,
+        );
 
         assert!(a.take_flag(&short('a')));
         assert!(a.take_flag(&short('b')));
@@ -925,9 +1443,9 @@ mod tests {
     fn ambiguity_towards_argument() {
         let args = Args::from(&["-abc"]);
         let mut err = None;
-        let mut a = State::construct(args, &[], &['a'], &mut err);
+        let mut a = State::construct(args, &[], &['a'], &LongNames::default(), &[], &mut err);
 
-        let r = a.take_arg(&short('a'), false, M).unwrap().unwrap();
+        let r = a.take_arg(&short('a'), false, &M).unwrap().unwrap();
         assert_eq!(r, "bc");
     }
 
@@ -935,7 +1453,14 @@ mod tests {
     fn ambiguity_towards_error() {
         let args = Args::from(&["-abc"]);
         let mut err = None;
-        let _a = State::construct(args, &['a', 'b', 'c'], &['a'], &mut err);
+        let _a = State::construct(
+            args,
+            &['a', 'b', 'c'],
+            &['a'],
+            &LongNames::default(),
+            &[],
+            &mut err,
+        );
         assert!(err.is_some());
     }
 
@@ -946,4 +1471,29 @@ mod tests {
         let is_ambig = matches!(a.peek(), Some(Arg::Word(_)));
         assert!(is_ambig);
     }
+
+    #[test]
+    fn response_file_line_splitting() {
+        let words =
+            split_response_line(r#"  --name 'Bob "B" Smith' "a \"b\" \c" x\ y ''"#).unwrap();
+        assert_eq!(
+            words,
+            ["--name", "Bob \"B\" Smith", "a \"b\" \\c", "x y", ""]
+        );
+
+        let words = split_response_line("").unwrap();
+        assert!(words.is_empty());
+
+        let err = split_response_line("--name 'Bob").unwrap_err();
+        assert!(matches!(
+            err,
+            ResponseFileError::Words(WordsError::UnterminatedQuote)
+        ));
+
+        let err = split_response_line("--name Bob\\").unwrap_err();
+        assert!(matches!(
+            err,
+            ResponseFileError::Words(WordsError::DanglingEscape)
+        ));
+    }
 }
@@ -133,13 +133,21 @@ impl Doc {
 
 impl Doc {
     pub(crate) fn write_shortlong(&mut self, name: &ShortLong) {
+        self.write_negatable(name, false);
+    }
+
+    /// Write a name, adding `[no-]` to long names if `negatable` is set
+    pub(crate) fn write_negatable(&mut self, name: &ShortLong, negatable: bool) {
         match name {
             ShortLong::Short(s) => {
                 self.write_char('-', Style::Literal);
                 self.write_char(*s, Style::Literal);
             }
-            ShortLong::Long(l) | ShortLong::Both(_, l) => {
-                self.write_str("--", Style::Literal);
+            ShortLong::Long(p, l) | ShortLong::Both(_, p, l) => {
+                self.write_str(p.as_str(), Style::Literal);
+                if negatable {
+                    self.write_str("[no-]", Style::Literal);
+                }
                 self.write_str(l, Style::Literal);
             }
         }
@@ -147,8 +155,12 @@ impl Doc {
 
     pub(crate) fn write_item(&mut self, item: &Item) {
         match item {
-            Item::Positional { metavar, help: _ } => {
-                self.metavar(*metavar);
+            Item::Positional {
+                metavar,
+                help: _,
+                possible: _,
+            } => {
+                self.metavar(metavar);
             }
             Item::Command {
                 name: _,
@@ -156,25 +168,35 @@ impl Doc {
                 help: _,
                 meta: _,
                 info: _,
+                aliases: _,
             } => {
                 self.write_str("COMMAND ...", Style::Metavar);
             }
             Item::Flag {
                 name,
                 shorts: _,
+                longs: _,
                 env: _,
                 help: _,
-            } => self.write_shortlong(name),
+                negatable,
+                relations: _,
+                aliases: _,
+            } => self.write_negatable(name, *negatable),
             Item::Argument {
                 name,
                 shorts: _,
+                longs: _,
                 metavar,
+                delimiter,
                 env: _,
                 help: _,
+                relations: _,
+                possible: _,
+                aliases: _,
             } => {
                 self.write_shortlong(name);
                 self.write_char('=', Style::Text);
-                self.metavar(*metavar);
+                self.delimited_metavar(metavar, *delimiter);
             }
             Item::Any {
                 metavar,
@@ -220,11 +242,23 @@ impl Doc {
                     go(m, f);
                     f.write_str("...", Style::Text);
                 }
+                Meta::Bounded(m, min, max) => {
+                    go(m, f);
+                    let bounds = match max {
+                        Some(max) if max == min => format!("{{{}}}", min),
+                        Some(max) => format!("{{{},{}}}", min, max),
+                        None => format!("{{{},}}", min),
+                    };
+                    f.write_str(&bounds, Style::Text);
+                }
 
-                Meta::Adjacent(m) | Meta::Subsection(m, _) | Meta::Suffix(m, _) => {
+                Meta::Adjacent(m)
+                | Meta::Subsection(m, _)
+                | Meta::Suffix(m, _)
+                | Meta::Note(m, _) => {
                     go(m, f);
                 }
-                Meta::Skip => {} // => f.write_str("no parameters expected", Style::Text),
+                Meta::Skip | Meta::Hidden(_) => {} // => f.write_str("no parameters expected", Style::Text),
                 Meta::CustomUsage(_, u) => {
                     f.doc(u);
                 }
@@ -488,6 +522,7 @@ fn extract_sections<'a>(
             help: _,
             meta,
             info,
+            aliases: _,
         } = item
         {
             path.push((*name).to_string());
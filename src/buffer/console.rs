@@ -292,6 +292,22 @@ impl Doc {
                         Block::DefinitionList | Block::Meta | Block::Mono => {}
                         Block::TermRef => {
                             if color == Color::Monochrome {
+                                // backtick starts the text so it goes after pending line breaks
+                                if !res.is_empty() && (pending_newline || pending_blank_line) {
+                                    if !res.ends_with('\n') {
+                                        res.push('\n');
+                                    }
+                                    if pending_blank_line && !res.ends_with("\n\n") {
+                                        res.push('\n');
+                                    }
+                                    char_pos = 0;
+                                    pending_newline = false;
+                                    pending_blank_line = false;
+                                }
+                                if let Some(missing) = margin.checked_sub(char_pos) {
+                                    res.push_str(&PADDING[..missing]);
+                                    char_pos = margin;
+                                }
                                 res.push('`');
                                 char_pos += 1;
                             }
@@ -302,7 +318,17 @@ impl Doc {
                     #[cfg(test)]
                     assert_eq!(stack.pop(), Some(block));
 
-                    margins.pop();
+                    // only blocks with their own margin push one
+                    if !matches!(
+                        block,
+                        Block::InlineBlock
+                            | Block::DefinitionList
+                            | Block::Meta
+                            | Block::Mono
+                            | Block::TermRef
+                    ) {
+                        margins.pop();
+                    }
                     match block {
                         Block::ItemBody => {
                             pending_margin = false;
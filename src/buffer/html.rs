@@ -26,7 +26,7 @@ fn collect_html(app: String, meta: &Meta, info: &Info) -> Doc {
     if sections.len() > 1 {
         buf.token(Token::BlockStart(Block::Block));
         buf.token(Token::BlockStart(Block::Header));
-        buf.text("Command summary");
+        buf.text(info.messages.command_summary());
         buf.token(Token::BlockEnd(Block::Header));
         buf.token(Token::BlockEnd(Block::Block));
 
@@ -52,8 +52,9 @@ fn collect_html(app: String, meta: &Meta, info: &Info) -> Doc {
             &section.path,
             section.info,
             section.meta,
-            &section.info.meta(),
+            &section.info.help_meta(&*info.messages),
             false,
+            &*info.messages,
         );
         buf.doc(&b);
     }
@@ -62,13 +62,14 @@ impl<T> OptionParser<T> {
         let mut path = vec![app.as_ref().to_string()];
 
         extract_sections(&root, &self.info, &mut path, &mut sections);
+        let messages = &*self.info.messages;
 
         let mut buf = Doc::default();
 
         if sections.len() > 1 {
             buf.token(Token::BlockStart(Block::Block));
             buf.token(Token::BlockStart(Block::Header));
-            buf.text("SYNOPSIS");
+            buf.text(messages.manpage_synopsis());
             buf.token(Token::BlockEnd(Block::Header));
             buf.token(Token::BlockEnd(Block::Block));
 
@@ -102,7 +103,7 @@ impl<T> OptionParser<T> {
 
             if let Some(descr) = &section.info.descr {
                 buf.token(Token::BlockStart(Block::Header));
-                buf.text("NAME");
+                buf.text(messages.manpage_name());
                 buf.token(Token::BlockEnd(Block::Header));
 
                 buf.text(app.as_ref());
@@ -111,7 +112,7 @@ impl<T> OptionParser<T> {
             }
 
             buf.token(Token::BlockStart(Block::Header));
-            buf.text("SYNOPSIS");
+            buf.text(messages.manpage_synopsis());
             buf.token(Token::BlockEnd(Block::Header));
             buf.write_path(&section.path);
             buf.write_meta(section.meta, true);
@@ -124,9 +125,9 @@ impl<T> OptionParser<T> {
 
             let mut items = HelpItems::default();
             items.append_meta(section.meta);
-            let help_meta = section.info.meta();
+            let help_meta = section.info.help_meta(messages);
             items.append_meta(&help_meta);
-            buf.write_help_item_groups(items, false);
+            buf.write_help_item_groups(items, false, messages);
 
             if let Some(footer) = &section.info.footer {
                 buf.token(Token::BlockStart(Block::Block));
@@ -17,11 +17,12 @@
 use crate::{
     args::{Arg, State},
     complete_shell::{render_bash, render_fish, render_simple, render_test, render_zsh},
-    item::ShortLong,
+    item::{NamePrefix, ShortLong},
+    meta_help::Metavar,
     parsers::NamedArg,
     Doc, ShellComp,
 };
-use std::ffi::OsStr;
+use std::{borrow::Cow, ffi::OsStr};
 
 #[derive(Clone, Debug)]
 pub(crate) struct Complete {
@@ -47,15 +48,25 @@ impl Complete {
 impl State {
     /// Add a new completion hint for flag, if needed
     pub(crate) fn push_flag(&mut self, named: &NamedArg) {
+        if named.deprecated.is_some() {
+            return;
+        }
         let depth = self.depth();
         if let Some(comp) = self.comp_mut() {
+            let extra = CompExtra {
+                depth,
+                group: None,
+                help: named.help.as_ref().and_then(Doc::to_completion),
+            };
             if let Ok(name) = ShortLong::try_from(named) {
                 comp.comps.push(Comp::Flag {
-                    extra: CompExtra {
-                        depth,
-                        group: None,
-                        help: named.help.as_ref().and_then(Doc::to_completion),
-                    },
+                    extra: extra.clone(),
+                    name,
+                });
+            }
+            for name in named.visible_aliases() {
+                comp.comps.push(Comp::Flag {
+                    extra: extra.clone(),
                     name,
                 });
             }
@@ -63,17 +74,28 @@ impl State {
     }
 
     /// Add a new completion hint for an argument, if needed
-    pub(crate) fn push_argument(&mut self, named: &NamedArg, metavar: &'static str) {
+    pub(crate) fn push_argument(&mut self, named: &NamedArg, metavar: &Metavar) {
+        if named.deprecated.is_some() {
+            return;
+        }
         let depth = self.depth();
         if let Some(comp) = self.comp_mut() {
+            let extra = CompExtra {
+                depth,
+                group: None,
+                help: named.help.as_ref().and_then(Doc::to_completion),
+            };
             if let Ok(name) = ShortLong::try_from(named) {
                 comp.comps.push(Comp::Argument {
-                    extra: CompExtra {
-                        depth,
-                        group: None,
-                        help: named.help.as_ref().and_then(Doc::to_completion),
-                    },
-                    metavar,
+                    extra: extra.clone(),
+                    metavar: metavar.0.clone(),
+                    name,
+                });
+            }
+            for name in named.visible_aliases() {
+                comp.comps.push(Comp::Argument {
+                    extra: extra.clone(),
+                    metavar: metavar.0.clone(),
                     name,
                 });
             }
@@ -84,11 +106,20 @@ impl State {
     ///
     /// `is_argument` is set to true when we are trying to parse the value and false if
     /// when meta
-    pub(crate) fn push_metavar(
+    pub(crate) fn push_metavar(&mut self, meta: &Metavar, help: &Option<Doc>, is_argument: bool) {
+        self.push_element_metavar(meta, help, is_argument, String::new());
+    }
+
+    /// Add a new completion hint for an element of a delimited list
+    ///
+    /// `prefix` contains elements before the one being completed, together with the delimiter,
+    /// completed values are appended to it
+    pub(crate) fn push_element_metavar(
         &mut self,
-        meta: &'static str,
+        meta: &Metavar,
         help: &Option<Doc>,
         is_argument: bool,
+        prefix: String,
     ) {
         let depth = self.depth();
         if let Some(comp) = self.comp_mut() {
@@ -100,8 +131,9 @@ impl State {
 
             comp.comps.push(Comp::Metavariable {
                 extra,
-                meta,
+                meta: meta.0.clone(),
                 is_argument,
+                prefix,
             });
         }
     }
@@ -109,7 +141,7 @@ impl State {
     /// Add a new completion hint for command, if needed
     pub(crate) fn push_command(
         &mut self,
-        name: &'static str,
+        name: Cow<'static, str>,
         short: Option<char>,
         help: &Option<Doc>,
     ) {
@@ -231,13 +263,13 @@ pub(crate) enum Comp {
     Argument {
         extra: CompExtra,
         name: ShortLong,
-        metavar: &'static str,
+        metavar: Cow<'static, str>,
     },
 
     ///
     Command {
         extra: CompExtra,
-        name: &'static str,
+        name: Cow<'static, str>,
         short: Option<char>,
     },
 
@@ -253,9 +285,11 @@ pub(crate) enum Comp {
 
     Metavariable {
         extra: CompExtra,
-        meta: &'static str,
+        meta: Cow<'static, str>,
         /// AKA not positional
         is_argument: bool,
+        /// part of a delimited list before the element being completed
+        prefix: String,
     },
 
     Shell {
@@ -290,6 +324,15 @@ impl Comp {
         }
     }
 
+    /// completed values for an element of a delimited list must keep elements before it
+    pub(crate) fn value_prefix(&self) -> &str {
+        if let Comp::Metavariable { prefix, .. } = self {
+            prefix
+        } else {
+            ""
+        }
+    }
+
     pub(crate) fn set_group(&mut self, group: String) {
         let extra = match self {
             Comp::Flag { extra, .. }
@@ -338,7 +381,7 @@ impl Arg {
                     Some((self, s))
                 }
             }
-            Arg::Long(_, _, s) | Arg::ArgWord(s) | Arg::Word(s) | Arg::PosWord(s) => {
+            Arg::Long(_, _, _, s) | Arg::ArgWord(s) | Arg::Word(s) | Arg::PosWord(s) => {
                 Some((self, s))
             }
         }
@@ -356,7 +399,7 @@ fn pair_to_os_string<'a>(pair: (&'a Arg, &'a OsStr)) -> Option<(&'a Arg, &'a str
 enum Prefix<'a> {
     NA,
     Short(char),
-    Long(&'a str),
+    Long(NamePrefix, &'a str),
 }
 
 impl State {
@@ -386,7 +429,7 @@ impl State {
         // and use it's value if it was a composite short/long argument
         let preceeding = items.next();
         let (pos_only, full_lit) = match preceeding {
-            Some((Arg::Short(_, true, _os) | Arg::Long(_, true, _os), full_lit)) => {
+            Some((Arg::Short(_, true, _os) | Arg::Long(_, _, true, _os), full_lit)) => {
                 (false, full_lit)
             }
             Some((Arg::PosWord(_), _)) => (true, lit),
@@ -394,13 +437,13 @@ impl State {
         };
 
         let is_named = match cur {
-            Arg::Short(_, _, _) | Arg::Long(_, _, _) => true,
+            Arg::Short(_, _, _) | Arg::Long(_, _, _, _) => true,
             Arg::ArgWord(_) | Arg::Word(_) | Arg::PosWord(_) => false,
         };
 
         let prefix = match preceeding {
             Some((Arg::Short(s, true, _os), _lit)) => Prefix::Short(*s),
-            Some((Arg::Long(l, true, _os), _lit)) => Prefix::Long(l.as_str()),
+            Some((Arg::Long(p, l, true, _os), _lit)) => Prefix::Long(*p, l.as_str()),
             _ => Prefix::NA,
         };
 
@@ -428,15 +471,15 @@ impl State {
 }
 
 /// Try to expand short string names into long names if possible
-fn preferred_name(name: ShortLong) -> String {
+fn preferred_name(name: &ShortLong) -> String {
     match name {
         ShortLong::Short(s) => format!("-{}", s),
-        ShortLong::Long(l) | ShortLong::Both(_, l) => format!("--{}", l),
+        ShortLong::Long(p, l) | ShortLong::Both(_, p, l) => format!("{}{}", p.as_str(), l),
     }
 }
 
 // check if argument can possibly match the argument passed in and returns a preferrable replacement
-fn arg_matches(arg: &str, name: ShortLong) -> Option<String> {
+fn arg_matches(arg: &str, name: &ShortLong) -> Option<String> {
     // "" and "-" match any flag
     if arg.is_empty() || arg == "-" {
         return Some(preferred_name(name));
@@ -447,11 +490,11 @@ fn arg_matches(arg: &str, name: ShortLong) -> Option<String> {
     // separately check for short and long names, fancy strip prefix things is here to avoid
     // allocations and cloning
     match name {
-        ShortLong::Long(_) => {}
-        ShortLong::Short(s) | ShortLong::Both(s, _) => {
+        ShortLong::Long(_, _) => {}
+        ShortLong::Short(s) | ShortLong::Both(s, _, _) => {
             can_match |= arg
                 .strip_prefix('-')
-                .and_then(|a| a.strip_prefix(s))
+                .and_then(|a| a.strip_prefix(*s))
                 .map_or(false, str::is_empty);
         }
     }
@@ -459,8 +502,10 @@ fn arg_matches(arg: &str, name: ShortLong) -> Option<String> {
     // and long string too
     match name {
         ShortLong::Short(_) => {}
-        ShortLong::Long(l) | ShortLong::Both(_, l) => {
-            can_match |= arg.strip_prefix("--").map_or(false, |s| l.starts_with(s));
+        ShortLong::Long(p, l) | ShortLong::Both(_, p, l) => {
+            can_match |= arg
+                .strip_prefix(p.as_str())
+                .map_or(false, |s| l.starts_with(s));
         }
     }
 
@@ -470,7 +515,7 @@ fn arg_matches(arg: &str, name: ShortLong) -> Option<String> {
         None
     }
 }
-fn cmd_matches(arg: &str, name: &'static str, short: Option<char>) -> Option<&'static str> {
+fn cmd_matches<'a>(arg: &str, name: &'a str, short: Option<char>) -> Option<&'a str> {
     // partial long name and exact short name match anything
     if name.starts_with(arg)
         || short.map_or(false, |s| {
@@ -542,7 +587,7 @@ impl Complete {
                 }
 
                 Comp::Flag { name, extra } => {
-                    if let Some(long) = arg_matches(arg, *name) {
+                    if let Some(long) = arg_matches(arg, name) {
                         items.push(ShowComp {
                             pretty: long.clone(),
                             subst: long,
@@ -556,7 +601,7 @@ impl Complete {
                     metavar,
                     extra,
                 } => {
-                    if let Some(long) = arg_matches(arg, *name) {
+                    if let Some(long) = arg_matches(arg, name) {
                         items.push(ShowComp {
                             pretty: format!("{}={}", long, metavar),
                             subst: long,
@@ -576,7 +621,7 @@ impl Complete {
                         subst: match prefix {
                             Prefix::NA => body.clone(),
                             Prefix::Short(s) => format!("-{}={}", s, body),
-                            Prefix::Long(l) => format!("--{}={}", l, body),
+                            Prefix::Long(p, l) => format!("{}{}={}", p.as_str(), l, body),
                         },
                     });
                 }
@@ -585,13 +630,14 @@ impl Complete {
                     extra,
                     meta,
                     is_argument,
+                    prefix: _,
                 } => {
                     if !is_argument && !pos_only && arg.starts_with('-') {
                         continue;
                     }
                     items.push(ShowComp {
                         subst: String::new(),
-                        pretty: (*meta).to_string(),
+                        pretty: meta.to_string(),
                         extra,
                     });
                 }
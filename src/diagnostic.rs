@@ -0,0 +1,241 @@
+//! Caret diagnostics: the command line is printed back with offending items underlined
+//!
+//! See [`caret_diagnostics`](crate::OptionParser::caret_diagnostics).
+
+use std::ffi::OsString;
+
+use crate::{buffer::Doc, shell_words::quote};
+
+/// Goes in front of every line of a diagnostic
+const GUTTER: &str = "  | ";
+
+/// Replaces arguments skipped to fit the command line in `max_width`
+const ELLIPSIS: &str = "...";
+
+/// Labels are wrapped to at least this many characters even if there's less space left
+const MIN_LABEL: usize = 20;
+
+/// Command line as the user typed it, shell quoted
+#[derive(Debug)]
+pub(crate) struct CommandLine {
+    /// Program name, if known, followed by the arguments
+    words: Vec<String>,
+    /// Number of words before the first argument
+    skip: usize,
+    /// Width the diagnostic should fit in
+    max_width: usize,
+}
+
+/// Item to underline: command line index, character to underline it with and a label
+pub(crate) struct Span<'a> {
+    pub(crate) index: usize,
+    pub(crate) mark: char,
+    pub(crate) label: &'a str,
+}
+
+impl CommandLine {
+    pub(crate) fn new(name: Option<&str>, args: &[OsString], max_width: usize) -> Self {
+        // control characters would break the layout, they are escaped the same way as in Rust
+        let show = |word: &str| {
+            let mut res = String::new();
+            for c in quote(word).chars() {
+                if c.is_control() {
+                    res.extend(c.escape_debug());
+                } else {
+                    res.push(c);
+                }
+            }
+            res
+        };
+        let mut words = Vec::with_capacity(args.len() + 1);
+        words.extend(name.map(show));
+        let skip = words.len();
+        words.extend(args.iter().map(|a| show(&a.to_string_lossy())));
+        Self {
+            words,
+            skip,
+            max_width,
+        }
+    }
+
+    /// Print the command line and underline `spans`, the first span of a word wins
+    ///
+    /// ```text
+    ///   |
+    ///   | app --json --yaml
+    ///   |     ------ ^^^^^^
+    ///   |     |
+    ///   |     conflicts with this
+    /// ```
+    pub(crate) fn render(&self, doc: &mut Doc, spans: &[Span]) {
+        let mut marked = Vec::new();
+        for span in spans {
+            let word = span.index + self.skip;
+            if word < self.words.len() && marked.iter().all(|(w, _)| *w != word) {
+                marked.push((word, span));
+            }
+        }
+        if marked.is_empty() {
+            return;
+        }
+        marked.sort_by_key(|(word, _)| *word);
+
+        let width = self.max_width.saturating_sub(GUTTER.len());
+        let keep = self.visible(&marked.iter().map(|m| m.0).collect::<Vec<_>>(), width);
+
+        // command line itself, remembering where each word starts
+        let mut line = String::new();
+        let mut cols = vec![0; self.words.len()];
+        let mut col = 0;
+        for (word, text) in self.pieces(&keep) {
+            if col > 0 {
+                line.push(' ');
+                col += 1;
+            }
+            if let Some(word) = word {
+                cols[word] = col;
+            }
+            line.push_str(text);
+            col += text.chars().count();
+        }
+
+        let mut marks = String::new();
+        for (word, span) in &marked {
+            pad(&mut marks, cols[*word]);
+            for _ in self.words[*word].chars() {
+                marks.push(span.mark);
+            }
+        }
+
+        // the rightmost label goes next to the marks if it fits, the rest hang below
+        let mut hanging = marked
+            .iter()
+            .filter(|(_, span)| !span.label.is_empty())
+            .map(|(word, span)| (cols[*word], span.label))
+            .collect::<Vec<_>>();
+        let rightmost = cols[marked[marked.len() - 1].0];
+        if let Some((col, label)) = hanging.last() {
+            if *col == rightmost && marks.chars().count() + 1 + label.chars().count() <= width {
+                marks.push(' ');
+                marks.push_str(label);
+                hanging.pop();
+            }
+        }
+
+        let mut lines = vec![String::new(), line, marks];
+        if !hanging.is_empty() {
+            lines.push(pipes(&hanging));
+        }
+        for ix in (0..hanging.len()).rev() {
+            let (col, label) = hanging[ix];
+            for text in wrap(label, width.saturating_sub(col).max(MIN_LABEL)) {
+                let mut line = pipes(&hanging[..ix]);
+                pad(&mut line, col);
+                line.push_str(&text);
+                lines.push(line);
+            }
+        }
+
+        for line in lines {
+            // a new line followed by a space is kept as is by the renderer
+            doc.text("\n ");
+            doc.text(GUTTER.trim_end());
+            if !line.is_empty() {
+                doc.text(" ");
+                doc.text(&line);
+            }
+        }
+    }
+
+    /// Words to show: all of them if they fit, otherwise the first word and as much as
+    /// possible around the `marked` ones
+    fn visible(&self, marked: &[usize], width: usize) -> Vec<bool> {
+        let mut keep = vec![true; self.words.len()];
+        if self.line_width(&keep) <= width {
+            return keep;
+        }
+
+        keep.iter_mut().for_each(|k| *k = false);
+        keep[0] = true;
+        for word in marked {
+            keep[*word] = true;
+        }
+        loop {
+            let mut grew = false;
+            for &word in marked {
+                let mut lo = word;
+                while lo > 0 && keep[lo - 1] {
+                    lo -= 1;
+                }
+                let mut hi = word;
+                while hi + 1 < keep.len() && keep[hi + 1] {
+                    hi += 1;
+                }
+                for next in [hi + 1, lo.wrapping_sub(1)] {
+                    if next < keep.len() && !keep[next] {
+                        keep[next] = true;
+                        if self.line_width(&keep) <= width {
+                            grew = true;
+                        } else {
+                            keep[next] = false;
+                        }
+                    }
+                }
+            }
+            if !grew {
+                return keep;
+            }
+        }
+    }
+
+    /// Visible words with their indices, skipped ones are replaced with a single [`ELLIPSIS`]
+    fn pieces(&self, keep: &[bool]) -> Vec<(Option<usize>, &str)> {
+        let mut res = Vec::new();
+        for (ix, word) in self.words.iter().enumerate() {
+            if keep[ix] {
+                res.push((Some(ix), word.as_str()));
+            } else if !matches!(res.last(), Some((None, _))) {
+                res.push((None, ELLIPSIS));
+            }
+        }
+        res
+    }
+
+    fn line_width(&self, keep: &[bool]) -> usize {
+        let pieces = self.pieces(keep);
+        let words = pieces.iter().map(|(_, w)| w.chars().count()).sum::<usize>();
+        words + pieces.len().saturating_sub(1)
+    }
+}
+
+fn pad(line: &mut String, col: usize) {
+    for _ in line.chars().count()..col {
+        line.push(' ');
+    }
+}
+
+/// `|` under every hanging label
+fn pipes(hanging: &[(usize, &str)]) -> String {
+    let mut res = String::new();
+    for (col, _) in hanging {
+        pad(&mut res, *col);
+        res.push('|');
+    }
+    res
+}
+
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split(' ') {
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    lines.push(line);
+    lines
+}
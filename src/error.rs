@@ -1,11 +1,16 @@
-use std::ops::Range;
+use std::{borrow::Cow, ops::Range, sync::Arc};
 
 use crate::{
     args::{Arg, State},
     buffer::{Block, Color, Doc, Style, Token},
+    diagnostic::{CommandLine, Span},
     item::{Item, ShortLong},
+    json::{self, Value},
+    messages::{fill, Catalog, English, Messages},
     meta_help::Metavar,
-    meta_youmean::{Suggestion, Variant},
+    meta_youmean::{suggest_value, Suggestion, Variant},
+    shell_words::WordsError,
+    structs::Bounds,
     Meta,
 };
 
@@ -13,21 +18,57 @@ use crate::{
 #[derive(Debug)]
 pub struct Error(pub(crate) Message);
 
+/// Item that produced the value being parsed: its index, if the value comes from a command line,
+/// and the name of the parser that took it, see [`State::current_item`]
+#[derive(Debug, Clone)]
+pub(crate) struct Current(pub(crate) Option<usize>, pub(crate) Option<String>);
+
 impl Error {
     pub(crate) fn combine_with(self, other: Self) -> Self {
         Error(self.0.combine_with(other.0))
     }
+
+    /// Used by [`construct!`](crate::construct!) to evaluate all the fields before reporting
+    /// a failure: keeps the first error and with
+    /// [`accumulate_errors`](crate::OptionParser::accumulate_errors) the rest of them as well
+    #[doc(hidden)]
+    pub fn collect<T>(res: Result<T, Self>, failure: &mut Option<Self>, args: &State) -> Option<T> {
+        match res {
+            Ok(val) => Some(val),
+            Err(Error(err)) => {
+                *failure = Some(Error(match failure.take() {
+                    None => err,
+                    Some(Error(prev)) if args.accumulate => prev.accumulate(err),
+                    Some(Error(prev)) => prev,
+                }));
+                None
+            }
+        }
+    }
+
+    /// Counterpart of [`collect`](Error::collect), fails with the collected error if there's one
+    #[doc(hidden)]
+    pub fn finish<T>(val: Option<T>, failure: &mut Option<Self>) -> Result<T, Self> {
+        match (val, failure.take()) {
+            (_, Some(err)) => Err(err),
+            (Some(val), None) => Ok(val),
+            (None, None) => unreachable!("collect stores an error for every failed field"),
+        }
+    }
 }
 
 #[derive(Debug)]
 pub(crate) enum Message {
     // those can be caught ---------------------------------------------------------------
     /// Tried to consume an env variable with no fallback, variable was not set
-    NoEnv(&'static str),
+    NoEnv(Cow<'static, str>),
 
     /// User specified an error message on some
     ParseSome(&'static str),
 
+    /// Required item is missing, but it is hidden so there's no name to report
+    HiddenItem,
+
     /// User asked for parser to fail explicitly
     ParseFail(&'static str),
 
@@ -41,7 +82,7 @@ pub(crate) enum Message {
 
     // those cannot be caught-------------------------------------------------------------
     /// Parsing failed and this is the final output
-    ParseFailure(ParseFailure),
+    ParseError(ParseError),
 
     /// Tried to consume a strict positional argument, value was present but was not strictly
     /// positional
@@ -51,10 +92,14 @@ pub(crate) enum Message {
     NonStrictPos(usize, Metavar),
 
     /// Parser provided by user failed to parse a value
-    ParseFailed(Option<usize>, String),
+    ParseFailed(Current, String),
+
+    /// Failed to parse an element of a delimited list: its index starting from 0, the element
+    /// itself and the error message
+    ParseElementFailed(Current, usize, String, String),
 
     /// Parser provided by user failed to validate a value
-    GuardFailed(Option<usize>, &'static str),
+    GuardFailed(Current, &'static str),
 
     /// Argument requres a value but something else was passed,
     /// required: --foo <BAR>
@@ -70,6 +115,9 @@ pub(crate) enum Message {
     /// argument is ambigoups - parser can accept it as both a set of flags and a short flag with no =
     Ambiguity(usize, String),
 
+    /// Abbreviated long name matches more than one long name
+    AmbiguousPrefix(usize, Vec<String>),
+
     /// Suggested fixes for typos or missing input
     Suggestion(usize, Suggestion),
 
@@ -82,6 +130,58 @@ pub(crate) enum Message {
 
     /// Parameter is accepted but only once
     OnlyOnce(/* winner */ usize, usize),
+
+    /// Item requires another item that is not present: item and the name it requires
+    Requires(usize, Cow<'static, str>),
+
+    /// Repeated parser got fewer items than required: parser, bounds and number of items present
+    TooFew(Box<Meta>, Bounds, usize),
+
+    /// Repeated parser got more items than allowed: parser, bounds and the first extra item
+    TooMany(Box<Meta>, Bounds, usize),
+
+    /// Value is outside of a range: value, relation to the bound and the bound
+    OutOfRange(Current, String, Limit, String),
+
+    /// Value is not one of the possible values: value and possible values
+    NotPossible(Current, String, Vec<Cow<'static, str>>),
+
+    /// Key in a `KEY=VALUE` pair is used more than once: first and second pair along with
+    /// their argv indices and the item with the second pair, values from environment variables
+    /// have no index
+    DuplicateKey((Option<usize>, String), (Current, String)),
+
+    /// Parser failed inside of a [`context`](crate::Parser::context): failure, context and the
+    /// path to the command
+    Context(Box<Message>, &'static str, Vec<String>),
+
+    /// Response file couldn't be expanded, location is a file name and a line if the problem
+    /// comes from inside of a response file
+    ResponseFile(Option<(String, usize)>, ResponseFileError),
+
+    /// Several independent failures collected by
+    /// [`accumulate_errors`](crate::OptionParser::accumulate_errors), behaves as the first one
+    Many(Vec<Message>),
+}
+
+/// How a value relates to the [`range`](crate::Parser::range) bound it violates
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Limit {
+    AtLeast,
+    GreaterThan,
+    AtMost,
+    LessThan,
+}
+
+/// Problems with expanding `@path` items
+#[derive(Debug, Clone)]
+pub(crate) enum ResponseFileError {
+    /// Can't read a response file: file name and io error
+    Read(String, String),
+    /// Response file refers to itself: file name and files in between for indirect cycles
+    Cycle(String, Vec<String>),
+    /// Line can't be split into words
+    Words(WordsError),
 }
 
 impl Message {
@@ -89,21 +189,65 @@ impl Message {
         match self {
             Message::NoEnv(_)
             | Message::ParseSome(_)
+            | Message::HiddenItem
+            | Message::TooFew(_, _, _)
             | Message::ParseFail(_)
             | Message::Missing(_)
             | Message::PureFailed(_)
             | Message::NonStrictPos(_, _) => true,
             Message::StrictPos(_, _)
             | Message::ParseFailed(_, _)
+            | Message::ParseElementFailed(_, _, _, _)
+            | Message::NotPossible(_, _, _)
+            | Message::OutOfRange(_, _, _, _)
+            | Message::Context(_, _, _)
             | Message::GuardFailed(_, _)
             | Message::Unconsumed(_)
             | Message::Ambiguity(_, _)
+            | Message::AmbiguousPrefix(_, _)
             | Message::Suggestion(_, _)
             | Message::Conflict(_, _)
-            | Message::ParseFailure(_)
+            | Message::ParseError(_)
             | Message::Expected(_, _)
             | Message::OnlyOnce(_, _)
-            | Message::NoArgument(_, _) => false,
+            | Message::DuplicateKey(_, _)
+            | Message::TooMany(_, _, _)
+            | Message::Requires(_, _)
+            | Message::NoArgument(_, _)
+            | Message::ResponseFile(_, _) => false,
+            Message::Many(xs) => !xs.is_empty() && xs.iter().all(Message::can_catch),
+        }
+    }
+
+    /// Required item is missing, doesn't look inside of a [`Context`](Message::Context)
+    pub(crate) fn is_missing(&self) -> bool {
+        match self {
+            Message::Missing(_) => true,
+            Message::Many(xs) => !xs.is_empty() && xs.iter().all(Message::is_missing),
+            _ => false,
+        }
+    }
+
+    /// Keep both failures to report them together
+    fn accumulate(self, other: Self) -> Self {
+        match (self, other) {
+            // help output takes priority
+            (a @ Message::ParseError(_), _) => a,
+            (_, b @ Message::ParseError(_)) => b,
+
+            (Message::Many(mut a), Message::Many(mut b)) => {
+                a.append(&mut b);
+                Message::Many(a)
+            }
+            (Message::Many(mut a), b) => {
+                a.push(b);
+                Message::Many(a)
+            }
+            (a, Message::Many(mut b)) => {
+                b.insert(0, a);
+                Message::Many(b)
+            }
+            (a, b) => Message::Many(vec![a, b]),
         }
     }
 }
@@ -126,14 +270,31 @@ impl Message {
         #[allow(clippy::match_same_arms)]
         match (self, other) {
             // help output takes priority
-            (a @ Message::ParseFailure(_), _) => a,
-            (_, b @ Message::ParseFailure(_)) => b,
+            (a @ Message::ParseError(_), _) => a,
+            (_, b @ Message::ParseError(_)) => b,
 
             // combine missing elements
             (Message::Missing(mut a), Message::Missing(mut b)) => {
                 a.append(&mut b);
                 Message::Missing(a)
             }
+            (Message::Many(mut a), Message::Missing(mut b))
+                if matches!(a[0], Message::Missing(_)) =>
+            {
+                if let Message::Missing(a) = &mut a[0] {
+                    a.append(&mut b);
+                }
+                Message::Many(a)
+            }
+            (Message::Missing(mut a), Message::Many(mut b))
+                if matches!(b[0], Message::Missing(_)) =>
+            {
+                if let Message::Missing(b) = &mut b[0] {
+                    a.append(b);
+                    std::mem::swap(&mut a, b);
+                }
+                Message::Many(b)
+            }
 
             // otherwise earliest wins
             (a, b) => {
@@ -165,6 +326,8 @@ impl Message {
 /// [`ParseFailure::unwrap_stdout`] and [`ParseFailure::unwrap_stdout`] - both of which produce a
 /// an unformatted `String` that parser might produce if failure type is correct or panics
 /// otherwise.
+///
+/// To tell different failures apart without looking at the message use [`ParseError`].
 #[derive(Clone, Debug)]
 pub enum ParseFailure {
     /// Print this to stdout and exit with success code
@@ -207,6 +370,9 @@ impl ParseFailure {
     }
 
     /// Returns the exit code for the failure
+    ///
+    /// [`run`](crate::OptionParser::run) uses [`exit_codes`](crate::OptionParser::exit_codes)
+    /// instead, codes there can be configured.
     #[allow(clippy::must_use_candidate)]
     pub fn exit_code(self) -> i32 {
         match self {
@@ -223,6 +389,11 @@ impl ParseFailure {
 
     /// Prints a message to `stdout` or `stderr` appropriate to the failure.
     pub fn print_message(&self, max_width: usize) {
+        self.print_message_with(max_width, English.error_prefix());
+    }
+
+    /// Same as [`print_message`](ParseFailure::print_message), errors start with `prefix`
+    pub(crate) fn print_message_with(&self, max_width: usize, prefix: &str) {
         let color = Color::default();
         match self {
             ParseFailure::Stdout(msg, full) => {
@@ -236,13 +407,13 @@ impl ParseFailure {
                 let mut error;
                 #[cfg(not(feature = "color"))]
                 {
-                    error = "Error: ";
+                    error = prefix;
                 }
 
                 #[cfg(feature = "color")]
                 {
                     error = String::new();
-                    color.push_str(Style::Invalid, &mut error, "Error: ");
+                    color.push_str(Style::Invalid, &mut error, prefix);
                 }
 
                 eprintln!("{}{}", error, msg.render_console(true, color, max_width));
@@ -251,14 +422,554 @@ impl ParseFailure {
     }
 }
 
+/// Kind of an unsuccessful parsing outcome, see [`ParseError::kind`]
+///
+/// New kinds can be added in minor releases.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// User asked for `--help`, the message goes to `stdout`
+    Help,
+    /// User asked for `--version`, the message goes to `stdout`
+    Version,
+    /// Shell asked for completion, output goes to `stdout` unchanged
+    Completion,
+    /// Required item is missing: ``expected `--token=TOKEN`, pass `--help` for usage information``
+    MissingItem,
+    /// Item is not expected in this context: ``--foo` is not expected in this context``
+    UnexpectedItem,
+    /// Unknown name that looks like a typo: ``no such flag: `--verbos`, did you mean `--verbose`?``
+    UnknownName,
+    /// Environment variable with no fallback is not set
+    MissingEnv,
+    /// Positional item is on the wrong side of `--`
+    PositionalPlacement,
+    /// Named argument is present without a value: ``--name` requires an argument `NAME``
+    MissingValue,
+    /// Value can't be parsed: ``couldn't parse `x`: invalid digit found in string``
+    InvalidValue,
+    /// Value is not one of the [possible values](crate::parsers::ParseArgument::possible_values)
+    InvalidChoice,
+    /// Value is outside of a [range](crate::Parser::range)
+    OutOfRange,
+    /// Value is rejected by a [`guard`](crate::Parser::guard)
+    ValidationFailed,
+    /// Failure with a message given by the parser author: [`fail`](crate::fail),
+    /// [`some`](crate::Parser::some), [`pure_with`](crate::pure_with)
+    Custom,
+    /// Short name can be both a set of flags and an argument with a value: `-foo`
+    AmbiguousShort,
+    /// [Abbreviated](crate::OptionParser::allow_abbreviations) long name matches several names
+    AmbiguousPrefix,
+    /// Items that [can't be used together](crate::NamedArg::conflicts_with) are present
+    Conflict,
+    /// Item is accepted only once but present multiple times
+    Duplicate,
+    /// Key in [`KEY=VALUE`](crate::NamedArg::key_value) pairs is set more than once
+    DuplicateKey,
+    /// Item [requires](crate::NamedArg::requires) another item that is not present
+    MissingRequirement,
+    /// Repeated item is present [fewer times](crate::parsers::ParseMany::at_least) than required
+    TooFew,
+    /// Repeated item is present [more times](crate::parsers::ParseMany::at_most) than allowed
+    TooMany,
+    /// [Response file](crate::OptionParser::expand_response_files) can't be expanded
+    ResponseFile,
+}
+
+impl ErrorKind {
+    /// Default exit code: `0` for help, version and completion, `1` for everything else
+    ///
+    /// Same as [`ParseFailure::exit_code`], see [`exit_codes`](crate::OptionParser::exit_codes)
+    /// to change it.
+    #[must_use]
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ErrorKind::Help | ErrorKind::Version | ErrorKind::Completion => 0,
+            _ => 1,
+        }
+    }
+
+    /// Exit code as defined in BSD `sysexits.h`
+    ///
+    /// - `EX_OK` (0) - help, version and completion
+    /// - `EX_DATAERR` (65) - values that fail to parse or validate
+    /// - `EX_NOINPUT` (66) - [response files](crate::OptionParser::expand_response_files)
+    ///   that can't be read
+    /// - `EX_CONFIG` (78) - missing environment variables
+    /// - `EX_USAGE` (64) - everything else
+    ///
+    /// Pass it to [`exit_codes`](crate::OptionParser::exit_codes) as is or use as a fallback.
+    #[must_use]
+    pub fn sysexits(self) -> i32 {
+        match self {
+            ErrorKind::Help | ErrorKind::Version | ErrorKind::Completion => 0,
+            ErrorKind::InvalidValue
+            | ErrorKind::InvalidChoice
+            | ErrorKind::OutOfRange
+            | ErrorKind::ValidationFailed => 65,
+            ErrorKind::ResponseFile => 66,
+            ErrorKind::MissingEnv => 78,
+            ErrorKind::MissingItem
+            | ErrorKind::UnexpectedItem
+            | ErrorKind::UnknownName
+            | ErrorKind::PositionalPlacement
+            | ErrorKind::MissingValue
+            | ErrorKind::Custom
+            | ErrorKind::AmbiguousShort
+            | ErrorKind::AmbiguousPrefix
+            | ErrorKind::Conflict
+            | ErrorKind::Duplicate
+            | ErrorKind::DuplicateKey
+            | ErrorKind::MissingRequirement
+            | ErrorKind::TooFew
+            | ErrorKind::TooMany => 64,
+        }
+    }
+
+    /// Name used in JSON output: `MissingItem` becomes `missing_item`
+    fn snake_case(self) -> String {
+        let mut res = String::new();
+        for c in format!("{:?}", self).chars() {
+            if c.is_uppercase() && !res.is_empty() {
+                res.push('_');
+            }
+            res.push(c.to_ascii_lowercase());
+        }
+        res
+    }
+}
+
+/// Unsuccessful command line parsing outcome in a structured form
+///
+/// Produced by [`OptionParser::run_structured`](crate::OptionParser::run_structured), lets you
+/// tell failures apart without looking at the message. [`render`](ParseError::render)
+/// turns it into the same [`ParseFailure`] [`run_inner`](crate::OptionParser::run_inner)
+/// produces.
+///
+/// ```rust
+/// # use bpaf::*;
+/// let token = long("token").argument::<String>("TOKEN");
+/// let port = long("port").argument::<u16>("PORT").fallback(80);
+/// let parser = construct!(token, port).to_options();
+///
+/// let err = parser.run_structured(&["--port", "80"]).unwrap_err();
+/// assert_eq!(err.kind(), ErrorKind::MissingItem);
+/// assert_eq!(err.names(), ["--token"]);
+///
+/// let err = parser.run_structured(&["--token", "t", "--port", "http"]).unwrap_err();
+/// assert_eq!(err.kind(), ErrorKind::InvalidValue);
+/// assert_eq!(err.index(), Some(3));
+/// assert_eq!(err.value(), Some("http"));
+/// assert_eq!(err.message(), Some("invalid digit found in string"));
+///
+/// let r = err.render().unwrap_stderr();
+/// assert_eq!(r, "couldn't parse `http`: invalid digit found in string");
+/// ```
+#[derive(Debug, Clone)]
+pub struct ParseError(Box<ErrorData>);
+
+// boxed to keep `Result<T, ParseError>` small
+#[derive(Debug, Clone)]
+struct ErrorData {
+    kind: ErrorKind,
+    index: Option<usize>,
+    related: Option<usize>,
+    names: Vec<String>,
+    value: Option<String>,
+    message: Option<String>,
+    path: Vec<String>,
+    context: Vec<&'static str>,
+    report: Report,
+    messages: Catalog,
+    others: Vec<ParseError>,
+    command_line: Option<Arc<CommandLine>>,
+    /// extra fields for [`to_json`](ParseError::to_json): help or version
+    details: Vec<(&'static str, Value)>,
+}
+
+impl ErrorData {
+    fn new(kind: ErrorKind, report: Report) -> Self {
+        Self {
+            kind,
+            index: None,
+            related: None,
+            names: Vec::new(),
+            value: None,
+            message: None,
+            path: Vec::new(),
+            context: Vec::new(),
+            report,
+            messages: Catalog::default(),
+            others: Vec::new(),
+            command_line: None,
+            details: Vec::new(),
+        }
+    }
+
+    fn add_context(&mut self, context: &'static str, path: &[String]) {
+        // path comes from the innermost context
+        if self.context.is_empty() {
+            self.path = path.to_vec();
+        }
+        self.context.insert(0, context);
+    }
+}
+
+impl ParseError {
+    /// Help or version message to print to `stdout`
+    pub(crate) fn stdout(kind: ErrorKind, doc: Doc, full: bool, path: &[String]) -> Self {
+        let mut err = ErrorData::new(kind, Report::Stdout(doc, full));
+        err.path = path.to_vec();
+        Self(Box::new(err))
+    }
+
+    /// Shell completion output
+    #[cfg(feature = "autocomplete")]
+    pub(crate) fn completion(comp: String, path: &[String]) -> Self {
+        let mut err = ErrorData::new(ErrorKind::Completion, Report::Completion(comp));
+        err.path = path.to_vec();
+        Self(Box::new(err))
+    }
+
+    /// Attach an extra field for [`to_json`](ParseError::to_json)
+    pub(crate) fn with_detail(mut self, name: &'static str, value: Value) -> Self {
+        self.0.details.push((name, value));
+        self
+    }
+
+    fn is_stdout(&self) -> bool {
+        match self.0.report {
+            Report::Stdout(..) => true,
+            #[cfg(feature = "autocomplete")]
+            Report::Completion(_) => true,
+            _ => false,
+        }
+    }
+
+    /// What went wrong
+    #[must_use]
+    pub fn kind(&self) -> ErrorKind {
+        self.0.kind
+    }
+
+    /// Index of the command line argument that caused the failure, if there's one
+    ///
+    /// Index starts from 0 and doesn't include the program name, same as indices in
+    /// [`Spanned`](crate::Spanned).
+    #[must_use]
+    pub fn index(&self) -> Option<usize> {
+        self.0.index
+    }
+
+    /// Index of another command line argument involved in the failure, if there's one
+    ///
+    /// The item [`index`](ParseError::index) conflicts with for
+    /// [`Conflict`](ErrorKind::Conflict), the first use of the item for
+    /// [`Duplicate`](ErrorKind::Duplicate) and [`DuplicateKey`](ErrorKind::DuplicateKey).
+    #[must_use]
+    pub fn related_index(&self) -> Option<usize> {
+        self.0.related
+    }
+
+    /// Names of items involved, as they are spelled on a command line
+    ///
+    /// Missing items for [`MissingItem`](ErrorKind::MissingItem), conflicting items for
+    /// [`Conflict`](ErrorKind::Conflict), suggested name for
+    /// [`UnknownName`](ErrorKind::UnknownName), the item that took a value that failed to parse
+    /// or validate and so on. Values built from several items have no single name.
+    #[must_use]
+    pub fn names(&self) -> &[String] {
+        &self.0.names
+    }
+
+    /// Text from the command line that caused the failure, if there's one
+    #[must_use]
+    pub fn value(&self) -> Option<&str> {
+        self.0.value.as_deref()
+    }
+
+    /// Message given by a parser author or by a [`FromStr`](std::str::FromStr) implementation
+    #[must_use]
+    pub fn message(&self) -> Option<&str> {
+        self.0.message.as_deref()
+    }
+
+    /// Path to the command the failure happened in, empty for the top level parser
+    #[must_use]
+    pub fn path(&self) -> &[String] {
+        &self.0.path
+    }
+
+    /// Descriptions given with [`context`](crate::Parser::context), outermost first
+    #[must_use]
+    pub fn context(&self) -> &[&'static str] {
+        &self.0.context
+    }
+
+    /// Other failures found with [`accumulate_errors`](crate::OptionParser::accumulate_errors)
+    ///
+    /// This failure is the first one, the rest are here in the order parsers are listed in
+    /// [`construct!`](crate::construct!).
+    #[must_use]
+    pub fn others(&self) -> &[ParseError] {
+        &self.0.others
+    }
+
+    /// Render the failure into a human readable message
+    #[must_use]
+    pub fn render(&self) -> ParseFailure {
+        match &self.0.report {
+            Report::Stdout(doc, full) => return ParseFailure::Stdout(doc.clone(), *full),
+            #[cfg(feature = "autocomplete")]
+            Report::Completion(comp) => return ParseFailure::Completion(comp.clone()),
+            _ => {}
+        }
+        if self.0.others.is_empty() {
+            return ParseFailure::Stderr(self.render_one());
+        }
+
+        // found 2 problems:
+        //   expected `--name=NAME`, pass `--help` for usage information
+        //   couldn't parse `x`: invalid digit found in string
+        let mut doc = Doc::default();
+        fill(&mut doc, self.0.messages.several_errors(), |doc, _| {
+            doc.text(&(self.0.others.len() + 1).to_string());
+        });
+        for err in std::iter::once(self).chain(&self.0.others) {
+            doc.token(Token::BlockStart(Block::Section3));
+            doc.doc(&err.render_one());
+            doc.token(Token::BlockEnd(Block::Section3));
+        }
+        ParseFailure::Stderr(doc)
+    }
+
+    /// Render the failure as a JSON object
+    ///
+    /// Object contains `kind` in `snake_case`, rendered `message` and fields with the same
+    /// names and meaning as methods of `ParseError`: `index`, `related_index`, `names`,
+    /// `value`, `path`, `context` and `others`, message given by a parser author goes into
+    /// `user_message`. Help adds a `help` object with `usage`, `description`, `header`,
+    /// `footer` and `items` accepted by the parser, version adds `version`.
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// let parser = long("port").argument::<u16>("PORT").to_options();
+    /// let err = parser.run_structured(&["--port", "http"]).unwrap_err();
+    /// let expected = r#"{"kind":"invalid_value","message":"couldn't parse `http`: invalid digit found in string","index":1,"related_index":null,"names":["--port"],"value":"http","user_message":"invalid digit found in string","path":[],"context":[],"others":[]}"#;
+    /// assert_eq!(err.to_json(), expected);
+    /// ```
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        self.json().to_string()
+    }
+
+    fn json(&self) -> Value {
+        let message = match &self.0.report {
+            Report::Stdout(doc, full) => json::text(doc, *full),
+            #[cfg(feature = "autocomplete")]
+            Report::Completion(comp) => comp.clone(),
+            _ => json::text(&self.render_message(), true),
+        };
+        let mut fields = vec![
+            ("kind", self.0.kind.snake_case().into()),
+            ("message", message.into()),
+            ("index", self.0.index.into()),
+            ("related_index", self.0.related.into()),
+            ("names", self.0.names.clone().into()),
+            ("value", self.0.value.clone().into()),
+            ("user_message", self.0.message.clone().into()),
+            ("path", self.0.path.clone().into()),
+            ("context", self.0.context.clone().into()),
+        ];
+        fields.extend(self.0.details.iter().cloned());
+        let others = self.0.others.iter().map(ParseError::json).collect();
+        fields.push(("others", Value::Array(others)));
+        Value::Object(fields)
+    }
+
+    fn render_one(&self) -> Doc {
+        let mut doc = self.render_message();
+        if let (Some(line), Some(index)) = (&self.0.command_line, self.0.index) {
+            let m = &*self.0.messages;
+            let mut spans = vec![Span {
+                index,
+                mark: '^',
+                label: self.0.message.as_deref().unwrap_or_default(),
+            }];
+            if let Some(related) = self.0.related {
+                let label = match self.0.kind {
+                    ErrorKind::Conflict => m.label_conflict(),
+                    _ => m.label_first_use(),
+                };
+                spans.push(Span {
+                    index: related,
+                    mark: '-',
+                    label,
+                });
+            }
+            line.render(&mut doc, &spans);
+        }
+        doc
+    }
+
+    /// Message with the context, but without caret diagnostics
+    fn render_message(&self) -> Doc {
+        let mut doc = self.0.report.render(&*self.0.messages);
+        if !self.0.context.is_empty() {
+            // `serve`: while reading database options: couldn't parse `http`: ...
+            let mut full = Doc::default();
+            if !self.0.path.is_empty() {
+                full.token(Token::BlockStart(Block::TermRef));
+                full.literal(&self.0.path.join(" "));
+                full.token(Token::BlockEnd(Block::TermRef));
+                full.text(": ");
+            }
+            for context in &self.0.context {
+                full.text(context);
+                full.text(": ");
+            }
+            full.doc(&doc);
+            doc = full;
+        }
+        doc
+    }
+}
+
+impl From<ParseError> for ParseFailure {
+    fn from(err: ParseError) -> Self {
+        err.render()
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.render() {
+            ParseFailure::Stdout(doc, full) => f.write_str(&doc.monochrome(full)),
+            ParseFailure::Completion(comp) => f.write_str(&comp),
+            ParseFailure::Stderr(doc) => f.write_str(&doc.monochrome(true)),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parts of a [`ParseError`] needed to render it, no longer refer to the command line
+#[derive(Debug, Clone)]
+enum Report {
+    /// Help or version message
+    Stdout(Doc, bool),
+    /// Shell completion output
+    #[cfg(feature = "autocomplete")]
+    Completion(String),
+    Unconsumed(String),
+    NoEnv(Cow<'static, str>),
+    StrictPos(Metavar),
+    NonStrictPos(Metavar),
+    /// Message from `some`, `fail` or `pure_with`
+    Text(String),
+    ParseFailed(Option<String>, String),
+    ParseElementFailed(Option<String>, usize, String, String),
+    GuardFailed(Option<String>, &'static str),
+    /// Argument, its metavar and a flag that follows it
+    NoArgument(String, Metavar, Option<String>),
+    /// Application name, short names and the whole item
+    Ambiguity(Option<String>, String, String),
+    AmbiguousPrefix(String, Vec<String>),
+    /// Actual item, what it looks like and a suggestion
+    Suggestion(String, Unknown, Suggestion),
+    Expected(Vec<Item>, Option<String>),
+    /// Winner and loser
+    Conflict(String, String),
+    OutOfRange(String, Limit, String),
+    NotPossible(String, Vec<Cow<'static, str>>),
+    TooFew(Box<Meta>, Bounds, usize),
+    TooMany(Box<Meta>, Bounds, String),
+    Requires(String, Cow<'static, str>),
+    OnlyOnce(String),
+    DuplicateKey(String, String),
+    ResponseFile(Option<(String, usize)>, ResponseFileError),
+    HiddenItem,
+}
+
+/// What an unknown item looks like, picks the message for [`Report::Suggestion`]
+#[derive(Debug, Clone, Copy)]
+enum Unknown {
+    Flag,
+    ArgumentValue,
+    Command,
+}
+
+/// Warning about a deprecated item at `ix`: `--threads` is deprecated, use --jobs instead
+pub(crate) fn render_deprecated(args: &State, ix: usize, hint: &str) -> Doc {
+    let mut doc = Doc::default();
+    fill(&mut doc, args.messages.deprecated(), |doc, n| match n {
+        0 => {
+            doc.token(Token::BlockStart(Block::TermRef));
+            doc.write(&args.items[ix], Style::Literal);
+            doc.token(Token::BlockEnd(Block::TermRef));
+        }
+        _ => doc.text(hint),
+    });
+    doc
+}
+
+/// Prints a warning to `stderr`, default warning handler for [`run`](crate::OptionParser::run)
+pub(crate) fn print_warning(msg: &Doc, max_width: usize, prefix: &str) {
+    let color = Color::default();
+    #[allow(unused_mut)]
+    let mut warning;
+    #[cfg(not(feature = "color"))]
+    {
+        warning = prefix;
+    }
+
+    #[cfg(feature = "color")]
+    {
+        warning = String::new();
+        color.push_str(Style::Emphasis, &mut warning, prefix);
+    }
+
+    eprintln!("{}{}", warning, msg.render_console(true, color, max_width));
+}
+
 fn check_conflicts(args: &State) -> Option<Message> {
     let (loser, winner) = args.conflict()?;
     Some(Message::Conflict(winner, loser))
 }
 
+/// expected `FILE` at least 2 times / at most 3 times / exactly once
+fn write_bounds(
+    doc: &mut Doc,
+    m: &dyn Messages,
+    meta: &Meta,
+    min: usize,
+    max: Option<usize>,
+    too_few: bool,
+) {
+    let (template, count) = match max {
+        Some(max) if max == min => (m.count_exactly(), min),
+        Some(max) if !too_few => (m.count_at_most(), max),
+        _ => (m.count_at_least(), min),
+    };
+    fill(doc, template, |doc, n| match n {
+        0 => {
+            doc.token(Token::BlockStart(Block::TermRef));
+            match Meta::first_item(meta) {
+                Some(item) => doc.write_item(item),
+                None => doc.write_meta(meta, true),
+            }
+            doc.token(Token::BlockEnd(Block::TermRef));
+        }
+        1 if count == 1 => doc.text(m.once()),
+        1 => fill(doc, m.times(), |doc, _| doc.text(&count.to_string())),
+        _ => {}
+    });
+}
+
 fn textual_part(args: &State, ix: Option<usize>) -> Option<std::borrow::Cow<str>> {
     match args.items.get(ix?)? {
-        Arg::Short(_, _, _) | Arg::Long(_, _, _) => None,
+        Arg::Short(_, _, _) | Arg::Long(_, _, _, _) => None,
         Arg::ArgWord(s) | Arg::Word(s) | Arg::PosWord(s) => Some(s.to_string_lossy()),
     }
 }
@@ -270,15 +981,16 @@ fn only_once(args: &State, cur: usize) -> Option<usize> {
     let mut iter = args.items[..cur].iter().rev();
     let offset = match args.items.get(cur)? {
         Arg::Short(s, _, _) => iter.position(|a| a.match_short(*s)),
-        Arg::Long(l, _, _) => iter.position(|a| a.match_long(l)),
+        Arg::Long(p, l, _, _) => iter.position(|a| a.match_long(*p, l)),
         Arg::ArgWord(_) | Arg::Word(_) | Arg::PosWord(_) => None,
     };
     Some(cur - offset? - 1)
 }
 
 impl Message {
+    /// Turn a message into a structured error, resolving command line indices into text
     #[allow(clippy::too_many_lines)] // it's a huge match with lots of simple cases
-    pub(crate) fn render(mut self, args: &State, meta: &Meta) -> ParseFailure {
+    pub(crate) fn into_error(mut self, args: &State, meta: &Meta) -> ParseError {
         // try to come up with a better error message for a few cases
         match self {
             Message::Unconsumed(ix) => {
@@ -296,327 +1008,669 @@ impl Message {
             _ => {}
         }
 
-        let mut doc = Doc::default();
+        let item = |ix: usize| args.items[ix].to_string();
+        let mut err = ErrorData::new(ErrorKind::Custom, Report::Text(String::new()));
+        err.path = args.path.clone();
+        err.messages = args.messages;
+        err.command_line = args.command_line.clone();
         match self {
-            // already rendered
-            Message::ParseFailure(f) => return f,
+            // already converted
+            Message::ParseError(err) => return err,
+
+            Message::Context(inner, context, path) => {
+                let mut err = inner.into_error(args, meta);
+                if err.is_stdout() {
+                    return err;
+                }
+                err.0.add_context(context, &path);
+                for other in &mut err.0.others {
+                    other.0.add_context(context, &path);
+                }
+                return err;
+            }
+
+            Message::Many(xs) => {
+                let mut errors = Vec::<ParseError>::new();
+                for x in xs {
+                    let mut err = x.into_error(args, meta);
+                    if err.is_stdout() {
+                        return err;
+                    }
+                    let nested = std::mem::take(&mut err.0.others);
+                    for err in std::iter::once(err).chain(nested) {
+                        // several branches can report the same missing item
+                        if !errors.iter().any(|e| e.to_string() == err.to_string()) {
+                            errors.push(err);
+                        }
+                    }
+                }
+                let mut errors = errors.into_iter();
+                if let Some(mut first) = errors.next() {
+                    first.0.others.extend(errors);
+                    return first;
+                }
+            }
 
             // this case is handled above
             Message::Missing(_) => {
                 // this one is unreachable
             }
 
-            // Error: --foo is not expected in this context
             Message::Unconsumed(ix) => {
-                let item = &args.items[ix];
-                doc.token(Token::BlockStart(Block::TermRef));
-                doc.write(item, Style::Invalid);
-                doc.token(Token::BlockEnd(Block::TermRef));
-                doc.text(" is not expected in this context");
+                err.kind = ErrorKind::UnexpectedItem;
+                err.index = Some(args.origin(ix));
+                err.value = Some(item(ix));
+                err.report = Report::Unconsumed(item(ix));
             }
 
-            // Error: environment variable FOO is not set
             Message::NoEnv(name) => {
-                doc.text("environment variable ");
-                doc.token(Token::BlockStart(Block::TermRef));
-                doc.invalid(name);
-                doc.token(Token::BlockEnd(Block::TermRef));
-                doc.text(" is not set");
+                err.kind = ErrorKind::MissingEnv;
+                err.names.push(name.to_string());
+                err.report = Report::NoEnv(name);
             }
 
-            // Error: FOO expected to be  in the right side of --
-            Message::StrictPos(_ix, metavar) => {
-                doc.text("expected ");
-                doc.token(Token::BlockStart(Block::TermRef));
-                doc.metavar(metavar);
-                doc.token(Token::BlockEnd(Block::TermRef));
-                doc.text(" to be on the right side of ");
-                doc.token(Token::BlockStart(Block::TermRef));
-                doc.literal("--");
-                doc.token(Token::BlockEnd(Block::TermRef));
+            Message::StrictPos(ix, metavar) => {
+                err.kind = ErrorKind::PositionalPlacement;
+                err.index = Some(args.origin(ix));
+                err.names.push(metavar.0.to_string());
+                err.report = Report::StrictPos(metavar);
             }
 
-            // Error: FOO expected to be on the left side of --
-            Message::NonStrictPos(_ix, metavar) => {
-                doc.text("expected ");
-                doc.token(Token::BlockStart(Block::TermRef));
-                doc.metavar(metavar);
-                doc.token(Token::BlockEnd(Block::TermRef));
-                doc.text(" to be on the left side of ");
-                doc.token(Token::BlockStart(Block::TermRef));
-                doc.literal("--");
-                doc.token(Token::BlockEnd(Block::TermRef));
-            }
-
-            // Error: <message from some or fail>
-            Message::ParseSome(s) | Message::ParseFail(s) => {
-                doc.text(s);
+            Message::NonStrictPos(ix, metavar) => {
+                err.kind = ErrorKind::PositionalPlacement;
+                err.index = Some(args.origin(ix));
+                err.names.push(metavar.0.to_string());
+                err.report = Report::NonStrictPos(metavar);
             }
 
-            // Error: couldn't parse FIELD: <FromStr message>
-            Message::ParseFailed(mix, s) => {
-                doc.text("couldn't parse");
-                if let Some(field) = textual_part(args, mix) {
-                    doc.text(" ");
-                    doc.token(Token::BlockStart(Block::TermRef));
-                    doc.invalid(&field);
-                    doc.token(Token::BlockEnd(Block::TermRef));
-                }
-                doc.text(": ");
-                doc.text(&s);
+            Message::HiddenItem => {
+                err.kind = ErrorKind::MissingItem;
+                err.report = Report::HiddenItem;
             }
 
-            // Error: ( FIELD:  | check failed: ) <message from guard>
-            Message::GuardFailed(mix, s) => {
-                if let Some(field) = textual_part(args, mix) {
-                    doc.token(Token::BlockStart(Block::TermRef));
-                    doc.invalid(&field);
-                    doc.token(Token::BlockEnd(Block::TermRef));
-                    doc.text(": ");
-                } else {
-                    doc.text("check failed: ");
-                }
-                doc.text(s);
+            Message::ParseSome(s) | Message::ParseFail(s) => {
+                err.message = Some(s.to_owned());
+                err.report = Report::Text(s.to_owned());
             }
 
-            // Error: --foo requires an argument FOO, got a flag --bar, try --foo=-bar to use it as an argument
-            // Error: --foo requires an argument FOO
-            Message::NoArgument(x, mv) => match args.get(x + 1) {
-                Some(Arg::Short(_, _, os) | Arg::Long(_, _, os)) => {
-                    let arg = &args.items[x];
-                    let os = &os.to_string_lossy();
-
-                    doc.token(Token::BlockStart(Block::TermRef));
-                    doc.write(arg, Style::Literal);
-                    doc.token(Token::BlockEnd(Block::TermRef));
-                    doc.text(" requires an argument ");
-                    doc.token(Token::BlockStart(Block::TermRef));
-                    doc.metavar(mv);
-                    doc.token(Token::BlockEnd(Block::TermRef));
-                    doc.text(", got a flag ");
-                    doc.token(Token::BlockStart(Block::TermRef));
-                    doc.write(os, Style::Invalid);
-                    doc.token(Token::BlockEnd(Block::TermRef));
-                    doc.text(", try ");
-                    doc.token(Token::BlockStart(Block::TermRef));
-                    doc.write(arg, Style::Literal);
-                    doc.literal("=");
-                    doc.write(os, Style::Literal);
-                    doc.token(Token::BlockEnd(Block::TermRef));
-                    doc.text(" to use it as an argument");
-                }
-                // "Some" part of this branch is actually unreachable
-                Some(Arg::ArgWord(_) | Arg::Word(_) | Arg::PosWord(_)) | None => {
-                    let arg = &args.items[x];
-                    doc.token(Token::BlockStart(Block::TermRef));
-                    doc.write(arg, Style::Literal);
-                    doc.token(Token::BlockEnd(Block::TermRef));
-                    doc.text(" requires an argument ");
-                    doc.token(Token::BlockStart(Block::TermRef));
-                    doc.metavar(mv);
-                    doc.token(Token::BlockEnd(Block::TermRef));
-                }
-            },
-            // Error: <message from pure_with>
             Message::PureFailed(s) => {
-                doc.text(&s);
+                err.message = Some(s.clone());
+                err.report = Report::Text(s);
             }
-            // Error: app supports -f as both an option and an option-argument, try to split -foo
-            // into invididual options (-f -o ..) or use -f=oo syntax to disambiguate
-            Message::Ambiguity(ix, name) => {
-                let mut chars = name.chars();
-                let first = chars.next().unwrap();
-                let rest = chars.as_str();
-                let second = chars.next().unwrap();
-                let s = args.items[ix].os_str().to_str().unwrap();
 
-                if let Some(name) = args.path.first() {
-                    doc.literal(name);
-                    doc.text(" supports ");
-                } else {
-                    doc.text("app supports ");
-                }
+            Message::ParseFailed(Current(mix, name), s) => {
+                let field = textual_part(args, mix).map(Cow::into_owned);
+                err.kind = ErrorKind::InvalidValue;
+                err.index = mix.map(|ix| args.origin(ix));
+                err.names.extend(name);
+                err.value = field.clone();
+                err.message = Some(s.clone());
+                err.report = Report::ParseFailed(field, s);
+            }
 
-                doc.token(Token::BlockStart(Block::TermRef));
-                doc.literal("-");
-                doc.write_char(first, Style::Literal);
-                doc.token(Token::BlockEnd(Block::TermRef));
-                doc.text(" as both an option and an option-argument, try to split ");
-                doc.token(Token::BlockStart(Block::TermRef));
-                doc.write(s, Style::Literal);
-                doc.token(Token::BlockEnd(Block::TermRef));
-                doc.text(" into individual options (");
-                doc.literal("-");
-                doc.write_char(first, Style::Literal);
-                doc.literal(" -");
-                doc.write_char(second, Style::Literal);
-                doc.literal(" ..");
-                doc.text(") or use ");
-                doc.token(Token::BlockStart(Block::TermRef));
-                doc.literal("-");
-                doc.write_char(first, Style::Literal);
-                doc.literal("=");
-                doc.literal(rest);
-                doc.token(Token::BlockEnd(Block::TermRef));
-                doc.text(" syntax to disambiguate");
+            Message::ParseElementFailed(Current(mix, name), ix, elt, s) => {
+                let field = textual_part(args, mix).map(Cow::into_owned);
+                err.kind = ErrorKind::InvalidValue;
+                err.index = mix.map(|ix| args.origin(ix));
+                err.names.extend(name);
+                err.value = Some(elt.clone());
+                err.message = Some(s.clone());
+                err.report = Report::ParseElementFailed(field, ix, elt, s);
             }
-            // Error: No such (flag|argument|command), did you mean  ...
+
+            Message::GuardFailed(Current(mix, name), s) => {
+                let field = textual_part(args, mix).map(Cow::into_owned);
+                err.kind = ErrorKind::ValidationFailed;
+                err.index = mix.map(|ix| args.origin(ix));
+                err.names.extend(name);
+                err.value = field.clone();
+                err.message = Some(s.to_owned());
+                err.report = Report::GuardFailed(field, s);
+            }
+
+            Message::NoArgument(x, mv) => {
+                let next = match args.get(x + 1) {
+                    Some(Arg::Short(_, _, os) | Arg::Long(_, _, _, os)) => {
+                        Some(os.to_string_lossy().into_owned())
+                    }
+                    // "Some" part of this branch is actually unreachable
+                    Some(Arg::ArgWord(_) | Arg::Word(_) | Arg::PosWord(_)) | None => None,
+                };
+                err.kind = ErrorKind::MissingValue;
+                err.index = Some(args.origin(x));
+                err.names.push(item(x));
+                err.report = Report::NoArgument(item(x), mv, next);
+            }
+
+            Message::Ambiguity(ix, name) => {
+                let first = name.chars().next().unwrap();
+                let value = args.items[ix].os_str().to_str().unwrap().to_owned();
+                err.kind = ErrorKind::AmbiguousShort;
+                err.index = Some(args.origin(ix));
+                err.names.push(format!("-{}", first));
+                err.value = Some(value.clone());
+                err.report = Report::Ambiguity(args.path.first().cloned(), name, value);
+            }
+
+            Message::AmbiguousPrefix(ix, candidates) => {
+                err.kind = ErrorKind::AmbiguousPrefix;
+                err.index = Some(args.origin(ix));
+                err.names = candidates.iter().map(|c| format!("--{}", c)).collect();
+                err.value = Some(item(ix));
+                err.report = Report::AmbiguousPrefix(item(ix), candidates);
+            }
+
             Message::Suggestion(ix, suggestion) => {
-                let actual = &args.items[ix].to_string();
-                match suggestion {
-                    Suggestion::Variant(v) => {
-                        let ty = match &args.items[ix] {
-                            _ if actual.starts_with('-') => "flag",
-                            Arg::Short(_, _, _) | Arg::Long(_, _, _) => "flag",
-                            Arg::ArgWord(_) => "argument value",
-                            Arg::Word(_) | Arg::PosWord(_) => "command or positional",
-                        };
-
-                        doc.text("no such ");
-                        doc.text(ty);
-                        doc.text(": ");
-                        doc.token(Token::BlockStart(Block::TermRef));
-                        doc.invalid(actual);
-                        doc.token(Token::BlockEnd(Block::TermRef));
-                        doc.text(", did you mean ");
-                        doc.token(Token::BlockStart(Block::TermRef));
+                let actual = item(ix);
+                let ty = match &args.items[ix] {
+                    _ if actual.starts_with('-') => Unknown::Flag,
+                    Arg::Short(_, _, _) | Arg::Long(_, _, _, _) => Unknown::Flag,
+                    Arg::ArgWord(_) => Unknown::ArgumentValue,
+                    Arg::Word(_) | Arg::PosWord(_) => Unknown::Command,
+                };
+                err.kind = ErrorKind::UnknownName;
+                err.index = Some(args.origin(ix));
+                err.names.push(suggestion.name());
+                err.value = Some(actual.clone());
+                err.report = Report::Suggestion(actual, ty, suggestion);
+            }
 
-                        match v {
-                            Variant::CommandLong(name) => doc.literal(name),
-                            Variant::Flag(ShortLong::Long(l) | ShortLong::Both(_, l)) => {
-                                doc.literal("--");
-                                doc.literal(l);
-                            }
-                            Variant::Flag(ShortLong::Short(s)) => {
-                                doc.literal("-");
-                                doc.write_char(s, Style::Literal);
-                            }
-                        };
+            Message::Expected(exp, actual) => {
+                err.kind = ErrorKind::MissingItem;
+                err.index = actual.map(|ix| args.origin(ix));
+                err.names = exp.iter().map(Item::name).collect();
+                err.value = actual.map(item);
+                err.report = Report::Expected(exp, actual.map(item));
+            }
 
-                        doc.token(Token::BlockEnd(Block::TermRef));
-                        doc.text("?");
+            Message::Conflict(winner, loser) => {
+                err.kind = ErrorKind::Conflict;
+                err.index = Some(args.origin(loser));
+                err.related = Some(args.origin(winner));
+                err.names = vec![item(loser), item(winner)];
+                err.report = Report::Conflict(item(winner), item(loser));
+            }
+
+            Message::OutOfRange(Current(mix, name), value, expected, bound) => {
+                err.kind = ErrorKind::OutOfRange;
+                err.index = mix.map(|ix| args.origin(ix));
+                err.names.extend(name);
+                err.value = Some(value.clone());
+                err.report = Report::OutOfRange(value, expected, bound);
+            }
+
+            Message::NotPossible(Current(mix, name), value, possible) => {
+                err.kind = ErrorKind::InvalidChoice;
+                err.index = mix.map(|ix| args.origin(ix));
+                err.names.extend(name);
+                err.value = Some(value.clone());
+                err.report = Report::NotPossible(value, possible);
+            }
+
+            Message::TooFew(meta, bounds, count) => {
+                err.kind = ErrorKind::TooFew;
+                err.names.extend(Meta::first_item(&meta).map(Item::name));
+                err.report = Report::TooFew(meta, bounds, count);
+            }
+
+            Message::TooMany(meta, bounds, extra) => {
+                err.kind = ErrorKind::TooMany;
+                err.index = Some(args.origin(extra));
+                err.names.extend(Meta::first_item(&meta).map(Item::name));
+                err.value = Some(item(extra));
+                err.report = Report::TooMany(meta, bounds, item(extra));
+            }
+
+            Message::Requires(ix, name) => {
+                err.kind = ErrorKind::MissingRequirement;
+                err.index = Some(args.origin(ix));
+                err.names = vec![item(ix), name.to_string()];
+                err.report = Report::Requires(item(ix), name);
+            }
+
+            Message::OnlyOnce(winner, loser) => {
+                err.kind = ErrorKind::Duplicate;
+                err.index = Some(args.origin(loser));
+                err.related = Some(args.origin(winner));
+                err.names.push(item(loser));
+                err.report = Report::OnlyOnce(item(loser));
+            }
+
+            Message::DuplicateKey((first_ix, first), (Current(second_ix, name), second)) => {
+                err.kind = ErrorKind::DuplicateKey;
+                err.index = second_ix.map(|ix| args.origin(ix));
+                err.related = first_ix.map(|ix| args.origin(ix));
+                err.names.extend(name);
+                err.value = Some(second.clone());
+                err.report = Report::DuplicateKey(first, second);
+            }
+
+            Message::ResponseFile(location, file_err) => {
+                err.kind = ErrorKind::ResponseFile;
+                match &file_err {
+                    ResponseFileError::Read(file, msg) => {
+                        err.value = Some(file.clone());
+                        err.message = Some(msg.clone());
+                    }
+                    ResponseFileError::Cycle(file, _) => err.value = Some(file.clone()),
+                    ResponseFileError::Words(words) => {
+                        err.message = Some(words.text(&*args.messages).to_owned());
                     }
-                    Suggestion::MissingDash(name) => {
-                        doc.text("no such flag: ");
+                }
+                err.report = Report::ResponseFile(location, file_err);
+            }
+        };
+        ParseError(Box::new(err))
+    }
+}
+
+impl Report {
+    #[allow(clippy::too_many_lines)] // it's a huge match with lots of simple cases
+    fn render(&self, m: &dyn Messages) -> Doc {
+        let mut doc = Doc::default();
+        match self {
+            // handled by ParseError::render
+            Report::Stdout(..) => {}
+            #[cfg(feature = "autocomplete")]
+            Report::Completion(_) => {}
+
+            // Error: --foo is not expected in this context
+            Report::Unconsumed(item) => {
+                fill(&mut doc, m.unexpected(), |doc, _| {
+                    term(doc, item, Style::Invalid);
+                });
+            }
+
+            // Error: environment variable FOO is not set
+            Report::NoEnv(name) => {
+                fill(&mut doc, m.env_missing(), |doc, _| {
+                    term(doc, name, Style::Invalid);
+                });
+            }
+
+            // Error: FOO expected to be  in the right side of --
+            // Error: FOO expected to be on the left side of --
+            Report::StrictPos(metavar) | Report::NonStrictPos(metavar) => {
+                let template = match self {
+                    Report::StrictPos(_) => m.strictly_positional(),
+                    _ => m.non_strictly_positional(),
+                };
+                fill(&mut doc, template, |doc, n| match n {
+                    0 => {
                         doc.token(Token::BlockStart(Block::TermRef));
-                        doc.literal("-");
-                        doc.literal(name);
+                        doc.metavar(metavar);
                         doc.token(Token::BlockEnd(Block::TermRef));
-                        doc.text(" (with one dash), did you mean ");
+                    }
+                    1 => term(doc, "--", Style::Literal),
+                    _ => {}
+                });
+            }
+
+            // Error: <message from some, fail or pure_with>
+            Report::Text(s) => {
+                doc.text(s);
+            }
+
+            // Error: couldn't parse FIELD: <FromStr message>
+            Report::ParseFailed(field, s) => {
+                let template = match field {
+                    Some(_) => m.parse_failed(),
+                    None => m.parse_failed_unnamed(),
+                };
+                fill(&mut doc, template, |doc, n| match (n, field) {
+                    (0, Some(field)) => term(doc, field, Style::Invalid),
+                    (1, _) => doc.text(s),
+                    _ => {}
+                });
+            }
+
+            // Error: couldn't parse element N (ELT) of FIELD: <FromStr message>
+            Report::ParseElementFailed(field, ix, elt, s) => {
+                let template = match field {
+                    Some(_) => m.parse_element_failed(),
+                    None => m.parse_element_failed_unnamed(),
+                };
+                fill(&mut doc, template, |doc, n| match (n, field) {
+                    (0, _) => doc.text(&(ix + 1).to_string()),
+                    (1, _) => term(doc, elt, Style::Invalid),
+                    (2, Some(field)) => term(doc, field, Style::Invalid),
+                    (3, _) => doc.text(s),
+                    _ => {}
+                });
+            }
+
+            // Error: ( FIELD:  | check failed: ) <message from guard>
+            Report::GuardFailed(field, s) => {
+                let template = match field {
+                    Some(_) => m.check_failed(),
+                    None => m.check_failed_unnamed(),
+                };
+                fill(&mut doc, template, |doc, n| match (n, field) {
+                    (0, Some(field)) => term(doc, field, Style::Invalid),
+                    (1, _) => doc.text(s),
+                    _ => {}
+                });
+            }
+
+            // Error: --foo requires an argument FOO, got a flag --bar, try --foo=-bar to use it as an argument
+            // Error: --foo requires an argument FOO
+            Report::NoArgument(arg, mv, next) => {
+                let template = match next {
+                    Some(_) => m.missing_value_got_flag(),
+                    None => m.missing_value(),
+                };
+                fill(&mut doc, template, |doc, n| match (n, next) {
+                    (0, _) => term(doc, arg, Style::Literal),
+                    (1, _) => {
                         doc.token(Token::BlockStart(Block::TermRef));
-                        doc.literal("--");
-                        doc.literal(name);
+                        doc.metavar(mv);
                         doc.token(Token::BlockEnd(Block::TermRef));
-                        doc.text("?");
                     }
-                    Suggestion::ExtraDash(name) => {
-                        doc.text("no such flag: ");
+                    (2, Some(os)) => term(doc, os, Style::Invalid),
+                    (3, Some(os)) => {
                         doc.token(Token::BlockStart(Block::TermRef));
-                        doc.literal("--");
-                        doc.write_char(name, Style::Literal);
+                        doc.write(arg, Style::Literal);
+                        doc.literal("=");
+                        doc.write(os, Style::Literal);
                         doc.token(Token::BlockEnd(Block::TermRef));
-                        doc.text(" (with two dashes), did you mean ");
+                    }
+                    _ => {}
+                });
+            }
+
+            // Error: app supports -f as both an option and an option-argument, try to split -foo
+            // into invididual options (-f -o ..) or use -f=oo syntax to disambiguate
+            Report::Ambiguity(app, name, s) => {
+                let mut chars = name.chars();
+                let first = chars.next().unwrap();
+                let rest = chars.as_str();
+                let second = chars.next().unwrap();
+
+                let template = match app {
+                    Some(_) => m.ambiguous_short(),
+                    None => m.ambiguous_short_unnamed(),
+                };
+                fill(&mut doc, template, |doc, n| match (n, app) {
+                    (0, Some(app)) => doc.literal(app),
+                    (1, _) => {
                         doc.token(Token::BlockStart(Block::TermRef));
                         doc.literal("-");
-                        doc.write_char(name, Style::Literal);
+                        doc.write_char(first, Style::Literal);
                         doc.token(Token::BlockEnd(Block::TermRef));
-                        doc.text("?");
                     }
-                    Suggestion::Nested(x, v) => {
-                        let ty = match v {
-                            Variant::CommandLong(_) => "subcommand",
-                            Variant::Flag(_) => "flag",
-                        };
-                        doc.text(ty);
-                        doc.text(" ");
-                        doc.token(Token::BlockStart(Block::TermRef));
-                        doc.literal(actual);
-                        doc.token(Token::BlockEnd(Block::TermRef));
-                        doc.text(
-                            " is not valid in this context, did you mean to pass it to command ",
-                        );
+                    (2, _) => term(doc, s, Style::Literal),
+                    (3, _) => {
+                        doc.literal("-");
+                        doc.write_char(first, Style::Literal);
+                        doc.literal(" -");
+                        doc.write_char(second, Style::Literal);
+                        doc.literal(" ..");
+                    }
+                    (4, _) => {
                         doc.token(Token::BlockStart(Block::TermRef));
-                        doc.literal(&x);
+                        doc.literal("-");
+                        doc.write_char(first, Style::Literal);
+                        doc.literal("=");
+                        doc.literal(rest);
                         doc.token(Token::BlockEnd(Block::TermRef));
-                        doc.text("?");
                     }
-                }
+                    _ => {}
+                });
             }
-            // Error: Expected (no arguments|--foo), got ..., pass --help
-            Message::Expected(exp, actual) => {
-                doc.text("expected ");
-                match exp.len() {
-                    0 => {
-                        doc.text("no arguments");
-                    }
+
+            // Error: `--ver` is ambiguous, it can be `--verbose` or `--version`
+            Report::AmbiguousPrefix(item, candidates) => {
+                fill(&mut doc, m.ambiguous_prefix(), |doc, n| match n {
+                    0 => term(doc, item, Style::Invalid),
                     1 => {
-                        doc.token(Token::BlockStart(Block::TermRef));
-                        doc.write_item(&exp[0]);
-                        doc.token(Token::BlockEnd(Block::TermRef));
-                    }
-                    2 => {
-                        doc.token(Token::BlockStart(Block::TermRef));
-                        doc.write_item(&exp[0]);
-                        doc.token(Token::BlockEnd(Block::TermRef));
-                        doc.text(" or ");
-                        doc.token(Token::BlockStart(Block::TermRef));
-                        doc.write_item(&exp[1]);
-                        doc.token(Token::BlockEnd(Block::TermRef));
+                        for (ix, name) in candidates.iter().enumerate() {
+                            if ix + 1 == candidates.len() {
+                                doc.text(m.list_or());
+                            } else if ix > 0 {
+                                doc.text(m.list_separator());
+                            }
+                            doc.token(Token::BlockStart(Block::TermRef));
+                            doc.literal("--");
+                            doc.literal(name);
+                            doc.token(Token::BlockEnd(Block::TermRef));
+                        }
                     }
-                    _ => {
+                    _ => {}
+                });
+            }
+
+            // Error: No such (flag|argument|command), did you mean  ...
+            Report::Suggestion(actual, ty, suggestion) => match suggestion {
+                Suggestion::Variant(v) => {
+                    let template = match ty {
+                        Unknown::Flag => m.no_such_flag(),
+                        Unknown::ArgumentValue => m.no_such_argument_value(),
+                        Unknown::Command => m.no_such_command(),
+                    };
+                    fill(&mut doc, template, |doc, n| match n {
+                        0 => term(doc, actual, Style::Invalid),
+                        1 => {
+                            doc.token(Token::BlockStart(Block::TermRef));
+                            match v {
+                                Variant::CommandLong(name) => doc.literal(name),
+                                Variant::Flag(ShortLong::Long(p, l) | ShortLong::Both(_, p, l)) => {
+                                    doc.literal(p.as_str());
+                                    doc.literal(l);
+                                }
+                                Variant::Flag(ShortLong::Short(s)) => {
+                                    doc.literal("-");
+                                    doc.write_char(*s, Style::Literal);
+                                }
+                            };
+                            doc.token(Token::BlockEnd(Block::TermRef));
+                        }
+                        _ => {}
+                    });
+                }
+                Suggestion::MissingDash(name) => {
+                    fill(&mut doc, m.single_dash(), |doc, n| {
+                        let dashes = if n == 0 { "-" } else { "--" };
                         doc.token(Token::BlockStart(Block::TermRef));
-                        doc.write_item(&exp[0]);
+                        doc.literal(dashes);
+                        doc.literal(name);
                         doc.token(Token::BlockEnd(Block::TermRef));
-                        doc.text(", ");
+                    });
+                }
+                Suggestion::ExtraDash(name) => {
+                    fill(&mut doc, m.double_dash(), |doc, n| {
+                        let dashes = if n == 0 { "--" } else { "-" };
                         doc.token(Token::BlockStart(Block::TermRef));
-                        doc.write_item(&exp[1]);
+                        doc.literal(dashes);
+                        doc.write_char(*name, Style::Literal);
                         doc.token(Token::BlockEnd(Block::TermRef));
-                        doc.text(", or more");
-                    }
+                    });
+                }
+                Suggestion::Nested(x, v) => {
+                    let template = match v {
+                        Variant::CommandLong(_) => m.command_of_command(),
+                        Variant::Flag(_) => m.flag_of_command(),
+                    };
+                    fill(&mut doc, template, |doc, n| match n {
+                        0 => term(doc, actual, Style::Literal),
+                        1 => term(doc, x, Style::Literal),
+                        _ => {}
+                    });
                 }
-                match actual {
-                    Some(actual) => {
-                        doc.text(", got ");
+            },
+
+            // Error: Expected (no arguments|--foo), got ..., pass --help
+            Report::Expected(exp, actual) => {
+                let template = match actual {
+                    Some(_) => m.expected_got(),
+                    None => m.expected(),
+                };
+                let item = |doc: &mut Doc, n: usize| {
+                    if let Some(item) = exp.get(n) {
                         doc.token(Token::BlockStart(Block::TermRef));
-                        doc.write(&args.items[actual], Style::Invalid);
+                        doc.write_item(item);
                         doc.token(Token::BlockEnd(Block::TermRef));
-                        doc.text(". Pass ");
-                    }
-                    None => {
-                        doc.text(", pass ");
                     }
-                }
-                doc.token(Token::BlockStart(Block::TermRef));
-                doc.literal("--help");
-                doc.token(Token::BlockEnd(Block::TermRef));
-                doc.text(" for usage information");
+                };
+                fill(&mut doc, template, |doc, n| match (n, actual) {
+                    (0, _) => match exp.len() {
+                        0 => doc.text(m.no_arguments()),
+                        1 => item(doc, 0),
+                        2 => fill(doc, m.one_of_two(), &item),
+                        _ => fill(doc, m.one_of_many(), &item),
+                    },
+                    (1, Some(actual)) => term(doc, actual, Style::Invalid),
+                    (2, _) => term(doc, "--help", Style::Literal),
+                    _ => {}
+                });
             }
 
             // Error: --intel cannot be used at the same time as --att
-            Message::Conflict(winner, loser) => {
-                doc.token(Token::BlockStart(Block::TermRef));
-                doc.write(&args.items[loser], Style::Literal);
-                doc.token(Token::BlockEnd(Block::TermRef));
-                doc.text(" cannot be used at the same time as ");
-                doc.token(Token::BlockStart(Block::TermRef));
-                doc.write(&args.items[winner], Style::Literal);
-                doc.token(Token::BlockEnd(Block::TermRef));
+            Report::Conflict(winner, loser) => {
+                fill(&mut doc, m.conflict(), |doc, n| match n {
+                    0 => term(doc, loser, Style::Literal),
+                    1 => term(doc, winner, Style::Literal),
+                    _ => {}
+                });
+            }
+
+            // Error: `100` is out of range, expected at most `64`
+            Report::OutOfRange(value, limit, bound) => {
+                let template = match limit {
+                    Limit::AtLeast => m.at_least(),
+                    Limit::GreaterThan => m.greater_than(),
+                    Limit::AtMost => m.at_most(),
+                    Limit::LessThan => m.less_than(),
+                };
+                fill(&mut doc, template, |doc, n| match n {
+                    0 => term(doc, value, Style::Invalid),
+                    1 => term(doc, bound, Style::Literal),
+                    _ => {}
+                });
+            }
+
+            // Error: `fsat` is not a valid value, did you mean `fast`?
+            // Error: `xyz` is not a valid value, expected one of `fast`, `slow`
+            Report::NotPossible(value, possible) => {
+                let suggestion = suggest_value(value, possible);
+                let template = match suggestion {
+                    Some(_) => m.not_possible_suggest(),
+                    None => m.not_possible(),
+                };
+                fill(&mut doc, template, |doc, n| match (n, suggestion) {
+                    (0, _) => term(doc, value, Style::Invalid),
+                    (1, Some(suggestion)) => term(doc, suggestion, Style::Literal),
+                    (1, None) => {
+                        for (ix, p) in possible.iter().enumerate() {
+                            if ix > 0 {
+                                doc.text(m.list_separator());
+                            }
+                            term(doc, p, Style::Literal);
+                        }
+                    }
+                    _ => {}
+                });
+            }
+
+            // Error: expected FILE at least 2 times, got 1
+            Report::TooFew(meta, bounds, count) => {
+                fill(&mut doc, m.too_few(), |doc, n| match n {
+                    0 => write_bounds(doc, m, meta, bounds.min, bounds.max, true),
+                    1 => doc.text(&count.to_string()),
+                    _ => {}
+                });
+            }
+
+            // Error: expected -v at most 2 times, -v is one too many
+            Report::TooMany(meta, bounds, extra) => {
+                fill(&mut doc, m.too_many(), |doc, n| match n {
+                    0 => write_bounds(doc, m, meta, bounds.min, bounds.max, false),
+                    1 => term(doc, extra, Style::Invalid),
+                    _ => {}
+                });
+            }
+
+            // Error: --json requires --output to be present
+            Report::Requires(item, name) => {
+                fill(&mut doc, m.requires(), |doc, n| match n {
+                    0 => term(doc, item, Style::Literal),
+                    1 => term(doc, name, Style::Literal),
+                    _ => {}
+                });
             }
 
             // Error: argument FOO cannot be used multiple times in this context
-            Message::OnlyOnce(_winner, loser) => {
-                doc.text("argument ");
-                doc.token(Token::BlockStart(Block::TermRef));
-                doc.write(&args.items[loser], Style::Literal);
-                doc.token(Token::BlockEnd(Block::TermRef));
-                doc.text(" cannot be used multiple times in this context");
+            Report::OnlyOnce(item) => {
+                fill(&mut doc, m.only_once(), |doc, _| {
+                    term(doc, item, Style::Literal);
+                });
             }
-        };
 
-        ParseFailure::Stderr(doc)
+            // Error: key `name` is set more than once: `name=Bob` and `name=Bill`
+            Report::DuplicateKey(first, second) => {
+                let key = first.split_once('=').map_or(first.as_str(), |kv| kv.0);
+                fill(&mut doc, m.duplicate_key(), |doc, n| match n {
+                    0 => term(doc, key, Style::Literal),
+                    1 => term(doc, first, Style::Invalid),
+                    2 => term(doc, second, Style::Invalid),
+                    _ => {}
+                });
+            }
+
+            // Error: args.txt:3: unterminated quote
+            // Error: couldn't read response file `args.txt`: No such file or directory
+            Report::ResponseFile(Some((file, line)), err) => {
+                let inner = Report::ResponseFile(None, err.clone()).render(m);
+                fill(&mut doc, m.response_file_location(), |doc, n| match n {
+                    0 => doc.text(file),
+                    1 => doc.text(&line.to_string()),
+                    2 => doc.doc(&inner),
+                    _ => {}
+                });
+            }
+            Report::ResponseFile(None, err) => match err {
+                ResponseFileError::Read(file, msg) => {
+                    fill(&mut doc, m.response_file_read(), |doc, n| match n {
+                        0 => term(doc, file, Style::Invalid),
+                        1 => doc.text(msg),
+                        _ => {}
+                    });
+                }
+                ResponseFileError::Cycle(file, via) if via.is_empty() => {
+                    fill(&mut doc, m.response_file_cycle(), |doc, _| {
+                        term(doc, file, Style::Invalid);
+                    });
+                }
+                ResponseFileError::Cycle(file, via) => {
+                    fill(&mut doc, m.response_file_cycle_via(), |doc, n| match n {
+                        0 => term(doc, file, Style::Invalid),
+                        1 => {
+                            for (ix, name) in via.iter().enumerate() {
+                                if ix > 0 {
+                                    doc.text(m.list_separator());
+                                }
+                                term(doc, name, Style::Invalid);
+                            }
+                        }
+                        _ => {}
+                    });
+                }
+                ResponseFileError::Words(words) => doc.text(words.text(m)),
+            },
+
+            // Error: parser requires an extra flag, argument or parameter, but ...
+            Report::HiddenItem => doc.text(m.hidden_item()),
+        }
+        doc
     }
 }
 
+/// Reference to an item on the command line: `--foo`
+fn term(doc: &mut Doc, text: &str, style: Style) {
+    doc.token(Token::BlockStart(Block::TermRef));
+    doc.write_str(text, style);
+    doc.token(Token::BlockEnd(Block::TermRef));
+}
+
 /// go over all the missing items, pick the left most scope
 pub(crate) fn summarize_missing(items: &[MissingItem], inner: &Meta, args: &State) -> Message {
     // missing items can belong to different scopes, pick the best scope to work with
@@ -625,7 +1679,7 @@ pub(crate) fn summarize_missing(items: &[MissingItem], inner: &Meta, args: &Stat
         .max_by_key(|item| (item.position, item.scope.start))
     {
         Some(x) => x,
-        None => return Message::ParseSome("parser requires an extra flag, argument or parameter, but its name is hidden by the author"),
+        None => return Message::HiddenItem,
     };
 
     let mut best_scope = best_item.scope.clone();
@@ -5,7 +5,10 @@ use std::{
     str::FromStr,
 };
 
-pub(crate) fn parse_os_str<T>(os: OsString) -> Result<T, String>
+use crate::messages::{fill_text, Messages};
+
+/// Parse a value, `m` is used for the message about invalid UTF-8
+pub(crate) fn parse_os_str<T>(os: OsString, m: &dyn Messages) -> Result<T, String>
 where
     T: FromStr + 'static,
     <T as std::str::FromStr>::Err: std::fmt::Display,
@@ -19,7 +22,7 @@ where
     } else {
         match os.to_str() {
             Some(s) => T::from_str(s).map_err(|e| e.to_string()),
-            None => Err(format!("{} is not a valid utf8", os.to_string_lossy())),
+            None => Err(fill_text(m.not_utf8(), &[&os.to_string_lossy()])),
         }
     }
 }
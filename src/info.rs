@@ -1,11 +1,14 @@
 //! Help message generation and rendering
 
 use crate::{
-    args::{Args, State},
-    error::Message,
+    args::{Args, LongNames, State},
+    error::{print_warning, render_deprecated, Message},
+    item::NamePrefix,
+    json,
+    messages::{fill, Catalog, Messages},
     meta_help::render_help,
     parsers::NamedArg,
-    short, Doc, Error, Meta, ParseFailure, Parser,
+    short, Doc, Error, ErrorKind, Meta, ParseError, ParseFailure, Parser,
 };
 
 /// Information about the parser
@@ -28,6 +31,20 @@ pub struct Info {
     pub version_arg: NamedArg,
     pub help_if_no_args: bool,
     pub max_width: usize,
+    /// Expand `@path` items, see [`expand_response_files`][OptionParser::expand_response_files]
+    pub response_files: bool,
+    /// Accept unique prefixes of long names, see [`allow_abbreviations`][OptionParser::allow_abbreviations]
+    pub abbreviations: bool,
+    /// Text of built-in messages, see [`messages`][OptionParser::messages]
+    pub(crate) messages: Catalog,
+    /// Report all the failures at once, see [`accumulate_errors`][OptionParser::accumulate_errors]
+    pub accumulate_errors: bool,
+    /// Underline failures in the command line, see [`caret_diagnostics`][OptionParser::caret_diagnostics]
+    pub caret_diagnostics: bool,
+    /// Print failures and help as JSON, see [`json_output`][OptionParser::json_output]
+    pub json_output: bool,
+    /// Exit code for each kind of failure, see [`exit_codes`][OptionParser::exit_codes]
+    pub exit_codes: fn(ErrorKind) -> i32,
 }
 
 impl Default for Info {
@@ -38,12 +55,17 @@ impl Default for Info {
             header: None,
             footer: None,
             usage: None,
-            help_arg: short('h').long("help").help("Prints help information"),
-            version_arg: short('V')
-                .long("version")
-                .help("Prints version information"),
+            help_arg: short('h').long("help"),
+            version_arg: short('V').long("version"),
             help_if_no_args: false,
             max_width: 100,
+            response_files: false,
+            abbreviations: false,
+            messages: Catalog::default(),
+            accumulate_errors: false,
+            caret_diagnostics: false,
+            json_output: false,
+            exit_codes: ErrorKind::exit_code,
         }
     }
 }
@@ -58,6 +80,8 @@ impl Default for Info {
 pub struct OptionParser<T> {
     pub(crate) inner: Box<dyn Parser<T>>,
     pub(crate) info: Info,
+    /// Handler for warnings, see [`on_warning`][OptionParser::on_warning]
+    pub(crate) on_warning: Option<Box<dyn Fn(Doc)>>,
 }
 
 impl<T> OptionParser<T> {
@@ -83,15 +107,37 @@ impl<T> OptionParser<T> {
     /// }
     /// ```
     #[must_use]
-    pub fn run(self) -> T
+    pub fn run(mut self) -> T
     where
         Self: Sized,
     {
-        match self.run_inner(Args::current_args()) {
+        let messages = self.info.messages;
+        let json = self.info.json_output || json::requested_by_env();
+        if self.on_warning.is_none() {
+            let max_width = self.info.max_width;
+            self.on_warning = Some(Box::new(move |w| {
+                if json {
+                    let warning = json::Value::Object(vec![
+                        ("kind", "warning".into()),
+                        ("message", json::text(&w, true).into()),
+                    ]);
+                    eprintln!("{}", warning);
+                } else {
+                    print_warning(&w, max_width, messages.warning_prefix());
+                }
+            }));
+        }
+        match self.run_structured(Args::current_args()) {
             Ok(t) => t,
             Err(err) => {
-                err.print_message(self.info.max_width);
-                std::process::exit(err.exit_code())
+                let failure = err.render();
+                match failure {
+                    ParseFailure::Stdout(..) if json => println!("{}", err.to_json()),
+                    ParseFailure::Stderr(_) if json => eprintln!("{}", err.to_json()),
+                    // shells expect completions as is
+                    _ => failure.print_message_with(self.info.max_width, messages.error_prefix()),
+                }
+                std::process::exit((self.info.exit_codes)(err.kind()))
             }
         }
     }
@@ -195,20 +241,100 @@ impl<T> OptionParser<T> {
     ///
     /// Exact string reperentations may change between versions including minor releases.
     pub fn run_inner<'a>(&self, args: impl Into<Args<'a>>) -> Result<T, ParseFailure>
+    where
+        Self: Sized,
+    {
+        self.run_structured(args).map_err(ParseFailure::from)
+    }
+
+    /// Execute the [`OptionParser`] and keep the failure in a structured form
+    ///
+    /// Same as [`run_inner`](OptionParser::run_inner), but instead of a rendered message you get
+    /// a [`ParseError`] that tells what kind of failure happened, where and to which items. Use
+    /// [`ParseError::render`] to get the message `run_inner` would produce.
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// let parser = long("token").argument::<String>("TOKEN").to_options();
+    ///
+    /// let err = parser.run_structured(&[]).unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::MissingItem);
+    /// assert_eq!(err.names(), ["--token"]);
+    ///
+    /// let err = parser.run_structured(&["--help"]).unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::Help);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// [`ParseError`] represents parsing errors, autocomplete results and generated `--help`
+    /// output, see [`ErrorKind`].
+    pub fn run_structured<'a>(&self, args: impl Into<Args<'a>>) -> Result<T, ParseError>
     where
         Self: Sized,
     {
         // prepare available short flags and arguments for disambiguation
         let mut short_flags = Vec::new();
         let mut short_args = Vec::new();
-        self.inner
-            .meta()
-            .collect_shorts(&mut short_flags, &mut short_args);
-        short_flags.extend(&self.info.help_arg.short);
-        short_flags.extend(&self.info.version_arg.short);
-        let args = args.into();
+        let meta = self.inner.meta();
+        meta.collect_shorts(&mut short_flags, &mut short_args);
+        short_flags.extend(self.info.help_arg.all_shorts());
+        short_flags.extend(self.info.version_arg.all_shorts());
+
+        // long names with alternative prefixes, single dash names take priority over groups of
+        // short names, others are recognized only if declared
+        let info_meta = self.info.meta();
+        let mut prefixed = Vec::new();
+        meta.collect_longs(&mut prefixed);
+        info_meta.collect_longs(&mut prefixed);
+
+        // names used in relations, only items spelled like them are tracked while parsing
+        let mut related = Vec::new();
+        meta.collect_related(&mut related);
+        #[cfg(debug_assertions)]
+        for name in &related {
+            let known = prefixed
+                .iter()
+                .any(|(p, l)| name.strip_prefix(p.as_str()) == Some(l))
+                || short_flags
+                    .iter()
+                    .chain(&short_args)
+                    .any(|s| name.strip_prefix('-') == Some(s.encode_utf8(&mut [0u8; 4])));
+            assert!(
+                known,
+                "bpaf usage BUG: {:?} is used in `conflicts_with` or `requires` but no item has \
+                such name",
+                name
+            );
+        }
+        let related = related.into_iter().map(String::from).collect::<Vec<_>>();
+
+        prefixed.retain(|(p, _)| *p != NamePrefix::DoubleDash);
+
+        // and long names for abbreviations, if enabled
+        let mut long_names = LongNames::default();
+        if self.info.abbreviations {
+            meta.collect_long_names(&mut long_names);
+            info_meta.collect_long_names(&mut long_names);
+        }
+
+        let mut args = args.into();
+        args.response_files = self.info.response_files;
+        if self.info.caret_diagnostics {
+            args.carets = Some(self.info.max_width);
+        }
         let mut err = None;
-        let mut state = State::construct(args, &short_flags, &short_args, &mut err);
+        let mut state = State::construct(
+            args,
+            &short_flags,
+            &short_args,
+            &long_names,
+            &prefixed,
+            &mut err,
+        );
+        state.messages = self.info.messages;
+        state.related = related.into();
+        state.accumulate = self.info.accumulate_errors;
 
         // this only handles disambiguation failure in construct
         if let Some(msg) = err {
@@ -219,15 +345,21 @@ impl<T> OptionParser<T> {
             let check_disambiguation = false;
 
             if check_disambiguation {
-                return Err(msg.render(&state, &self.inner.meta()));
+                return Err(msg.into_error(&state, &self.inner.meta()));
             }
         }
 
-        self.run_subparser(&mut state)
+        let res = self.run_subparser(&mut state)?;
+        if let Some(on_warning) = &self.on_warning {
+            for (ix, hint) in &state.warnings {
+                on_warning(render_deprecated(&state, *ix, hint));
+            }
+        }
+        Ok(res)
     }
 
     /// Run subparser, implementation detail
-    pub(crate) fn run_subparser(&self, args: &mut State) -> Result<T, ParseFailure> {
+    pub(crate) fn run_subparser(&self, args: &mut State) -> Result<T, ParseError> {
         // process should work like this:
         // - inner parser is evaluated, it returns Error
         // - if error is finalized (ParseFailure) - it is simply propagated outwards,
@@ -238,32 +370,28 @@ impl<T> OptionParser<T> {
         // - produce --help, --version
         // - Try to improve error message and finalize it otherwise
         //
-        // outer parser gets value in ParseFailure format
+        // outer parser gets value in ParseError format
 
         if self.info.help_if_no_args && args.is_empty() {
-            let buffer = render_help(
-                &args.path,
-                &self.info,
-                &self.inner.meta(),
-                &self.info.meta(),
-                true,
-            );
-            return Err(ParseFailure::Stdout(buffer, false));
+            return Err(self.help_failure(args, false));
         };
 
+        let names_used = args.names_used();
         let res = self.inner.eval(args);
-        if let Err(Error(Message::ParseFailure(failure))) = res {
+        if let Err(Error(Message::ParseError(failure))) = res {
             return Err(failure);
         }
         #[cfg(feature = "autocomplete")]
         if let Some(comp) = args.check_complete() {
-            return Err(ParseFailure::Completion(comp));
+            return Err(ParseError::completion(comp, &args.path));
         }
 
         let err = match res {
             Ok(ok) => {
                 if let Some((ix, _)) = args.items_iter().next() {
                     Message::Unconsumed(ix)
+                } else if let Some(msg) = args.check_relations(names_used) {
+                    msg
                 } else {
                     return Ok(ok);
                 }
@@ -273,31 +401,38 @@ impl<T> OptionParser<T> {
 
         // handle --help and --version messages
         if let Ok(extra) = self.info.eval(args) {
-            let mut detailed = false;
-            let buffer = match extra {
-                ExtraParams::Help(d) => {
-                    detailed = d;
-                    render_help(
-                        &args.path,
-                        &self.info,
-                        &self.inner.meta(),
-                        &self.info.meta(),
-                        true,
-                    )
-                }
+            return Err(match extra {
+                ExtraParams::Help(detailed) => self.help_failure(args, detailed),
                 ExtraParams::Version(v) => {
                     use crate::buffer::{Block, Token};
                     let mut buffer = Doc::default();
                     buffer.token(Token::BlockStart(Block::Block));
-                    buffer.text("Version: ");
-                    buffer.doc(&v);
+                    fill(&mut buffer, args.messages.version(), |buffer, _| {
+                        buffer.doc(&v)
+                    });
                     buffer.token(Token::BlockEnd(Block::Block));
-                    buffer
+                    ParseError::stdout(ErrorKind::Version, buffer, false, &args.path)
+                        .with_detail("version", json::text(&v, true).into())
                 }
-            };
-            return Err(ParseFailure::Stdout(buffer, detailed));
+            });
         }
-        Err(err.render(args, &self.inner.meta()))
+        Err(err.into_error(args, &self.inner.meta()))
+    }
+
+    /// `--help` output, also described as data for [`ParseError::to_json`]
+    fn help_failure(&self, args: &State, detailed: bool) -> ParseError {
+        let parser_meta = self.inner.meta();
+        let help_meta = self.info.help_meta(&*args.messages);
+        let buffer = render_help(
+            &args.path,
+            &self.info,
+            &parser_meta,
+            &help_meta,
+            true,
+            &*args.messages,
+        );
+        let help = json::help(&args.path, &self.info, &parser_meta, &help_meta);
+        ParseError::stdout(ErrorKind::Help, buffer, detailed, &args.path).with_detail("help", help)
     }
 
     /// Get first line of description if Available
@@ -701,28 +836,319 @@ impl<T> OptionParser<T> {
         self.info.max_width = width;
         self
     }
+
+    /// Expand `@path` items on the command line into the contents of response files
+    ///
+    /// Each line of a response file contains zero or more items separated by whitespace, items
+    /// can be quoted with single or double quotes and a backslash escapes the next character,
+    /// same as in a POSIX shell. Response files can refer to other response files, relative
+    /// paths are resolved against the directory of the file that refers to them, paths on the
+    /// command line - against the current directory. Items after `--` are not expanded.
+    ///
+    /// Errors inside response files are reported with a file name and a line number.
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// # let name = format!("bpaf_expand_response_files_doc_{}.txt", std::process::id());
+    /// # let path = std::env::temp_dir().join(name);
+    /// # std::fs::write(&path, "--name 'Bob Smith'\n-v").unwrap();
+    /// # let path = path.to_str().unwrap();
+    /// let name = long("name").argument::<String>("NAME");
+    /// let verbose = short('v').switch();
+    /// let parser = construct!(name, verbose)
+    ///     .to_options()
+    ///     .expand_response_files();
+    ///
+    /// let args = [format!("@{}", path)];
+    /// let r = parser.run_inner(&args[..]).unwrap();
+    /// assert_eq!(r, (String::from("Bob Smith"), true));
+    /// # std::fs::remove_file(path).unwrap();
+    /// ```
+    #[must_use]
+    pub fn expand_response_files(mut self) -> Self {
+        self.info.response_files = true;
+        self
+    }
+
+    /// Accept unique prefixes of long names in place of full names
+    ///
+    /// With this option enabled `--verb` is accepted as `--verbose` for as long as there's no
+    /// other long name starting with `--verb`, ambiguous prefixes produce an error that lists
+    /// all the candidates. Exact matches always take priority over prefixes. After a subcommand
+    /// prefixes are matched against names of that subcommand first and against names of the
+    /// parents if nothing matches.
+    ///
+    /// Names of hidden items don't participate in abbreviation.
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// let verbose = long("verbose").switch();
+    /// let version = long("verify").switch();
+    /// let parser = construct!(verbose, version)
+    ///     .to_options()
+    ///     .allow_abbreviations();
+    ///
+    /// let r = parser.run_inner(&["--verb"]).unwrap();
+    /// assert_eq!(r, (true, false));
+    ///
+    /// let r = parser.run_inner(&["--ver"]).unwrap_err().unwrap_stderr();
+    /// assert_eq!(r, "`--ver` is ambiguous, it can be `--verbose` or `--verify`");
+    /// ```
+    #[must_use]
+    pub fn allow_abbreviations(mut self) -> Self {
+        self.info.abbreviations = true;
+        self
+    }
+
+    /// Handle warnings produced while parsing
+    ///
+    /// Parsers produce warnings for [deprecated](NamedArg::deprecated) items, the handler is
+    /// called for each one once parsing succeeds. Without a handler
+    /// [`run_inner`](OptionParser::run_inner) discards warnings and [`run`](OptionParser::run)
+    /// prints them to stderr. Only the handler of the top level parser is used.
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// # use std::sync::mpsc::channel;
+    /// let colour = long("colour").deprecated("use --color instead").switch();
+    /// let color = long("color").switch();
+    /// let (tx, rx) = channel();
+    /// let parser = construct!(color, colour)
+    ///     .map(|(color, colour)| color || colour)
+    ///     .to_options()
+    ///     .on_warning(move |w| tx.send(w.monochrome(true)).unwrap());
+    ///
+    /// assert!(parser.run_inner(&["--colour"]).unwrap());
+    /// let w: Vec<_> = rx.try_iter().collect();
+    /// assert_eq!(w, ["`--colour` is deprecated, use --color instead"]);
+    /// ```
+    #[must_use]
+    pub fn on_warning<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(Doc) + 'static,
+    {
+        self.on_warning = Some(Box::new(handler));
+        self
+    }
+
+    /// Use a message catalog for built-in messages and help headings
+    ///
+    /// By default `bpaf` uses [`English`](crate::English), catalog set on the top level parser
+    /// is used for all the subcommands, errors, warnings and generated documentation. See
+    /// [`Messages`] for details.
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// struct Japanese;
+    /// impl Messages for Japanese {
+    ///     fn usage(&self) -> &str {
+    ///         "使い方"
+    ///     }
+    ///     fn conflict(&self) -> &str {
+    ///         "{0} と {1} は同時に使用できません"
+    ///     }
+    /// }
+    ///
+    /// let json = long("json").switch();
+    /// let yaml = long("yaml").switch();
+    /// let parser = construct!([json, yaml]).to_options().messages(&Japanese);
+    ///
+    /// let r = parser.run_inner(&["--json", "--yaml"]).unwrap_err().unwrap_stderr();
+    /// assert_eq!(r, "`--yaml` と `--json` は同時に使用できません");
+    /// ```
+    #[must_use]
+    pub fn messages(mut self, messages: &'static dyn Messages) -> Self {
+        self.info.messages = Catalog(messages);
+        self
+    }
+
+    /// Report all the problems found on a command line instead of the first one
+    ///
+    /// By default parsing stops at the first failure. With this option fields combined with
+    /// [`construct!`](crate::construct!) are all evaluated and failures such as missing items
+    /// or values that fail to parse or validate are reported together. Failures are still
+    /// reported one at a time in alternatives such as `construct!([a, b])`. Option set on the
+    /// top level parser is used for all the subcommands, individual failures are available with
+    /// [`ParseError::others`].
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// let name = long("name").argument::<String>("NAME");
+    /// let port = long("port").argument::<u16>("PORT");
+    /// let user = long("user").argument::<String>("USER");
+    /// let parser = construct!(name, port, user).to_options().accumulate_errors();
+    ///
+    /// let r = parser.run_inner(&["--port", "http"]).unwrap_err().unwrap_stderr();
+    /// let expected = "\
+    /// found 3 problems:
+    ///   expected `--name=NAME`, pass `--help` for usage information
+    ///   couldn't parse `http`: invalid digit found in string
+    ///   expected `--user=USER`, pass `--help` for usage information";
+    /// assert_eq!(r, expected);
+    /// ```
+    #[must_use]
+    pub fn accumulate_errors(mut self) -> Self {
+        self.info.accumulate_errors = true;
+        self
+    }
+
+    /// Print the command line back with failing items underlined
+    ///
+    /// Failures that point to command line items get the shell quoted command line attached
+    /// with the items underlined and labelled, similar to what `rustc` does. Diagnostic fits in
+    /// [`max_width`](OptionParser::max_width): if the command line is too long, arguments far
+    /// from the failing items are replaced with `...`.
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// let json = long("json").switch();
+    /// let yaml = long("yaml").switch();
+    /// let format = construct!([json, yaml]);
+    /// let port = long("port").argument::<u16>("PORT");
+    /// let parser = construct!(format, port).to_options().caret_diagnostics();
+    ///
+    /// let r = parser
+    ///     .run_inner(&["--port", "http"])
+    ///     .unwrap_err()
+    ///     .unwrap_stderr();
+    /// let expected = "\
+    /// couldn't parse `http`: invalid digit found in string
+    ///   |
+    ///   | --port http
+    ///   |        ^^^^ invalid digit found in string";
+    /// assert_eq!(r, expected);
+    ///
+    /// let r = parser
+    ///     .run_inner(&["--json", "--port", "80", "--yaml"])
+    ///     .unwrap_err()
+    ///     .unwrap_stderr();
+    /// let expected = "\
+    /// `--yaml` cannot be used at the same time as `--json`
+    ///   |
+    ///   | --json --port 80 --yaml
+    ///   | ------           ^^^^^^
+    ///   | |
+    ///   | conflicts with this";
+    /// assert_eq!(r, expected);
+    /// ```
+    #[must_use]
+    pub fn caret_diagnostics(mut self) -> Self {
+        self.info.caret_diagnostics = true;
+        self
+    }
+
+    /// Make [`run`](OptionParser::run) print failures, help and version as JSON
+    ///
+    /// Useful for tools that drive the program and need data instead of text: help lists all
+    /// the items with their names, metavars, help, environment variables and defaults,
+    /// failures come with their location on the command line. See [`ParseError::to_json`] for
+    /// details. Help and version go to `stdout`, everything else to `stderr`, completion output
+    /// stays unchanged. Setting `BPAF_OUTPUT` environment variable to `json` enables this for
+    /// any parser.
+    ///
+    /// [`run_inner`](OptionParser::run_inner) is not affected, use
+    /// [`run_structured`](OptionParser::run_structured) and [`ParseError::to_json`] to get the
+    /// same output.
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// let port = long("port")
+    ///     .help("Port to listen on")
+    ///     .env("PORT")
+    ///     .argument::<u16>("PORT")
+    ///     .fallback(8080)
+    ///     .display_fallback();
+    /// let parser = port.to_options().json_output();
+    ///
+    /// let err = parser.run_structured(&["--help"]).unwrap_err();
+    /// let json = err.to_json();
+    /// assert!(json.starts_with(r#"{"kind":"help","#));
+    /// assert!(json.contains(r#"{"kind":"argument","short":null,"long":"--port","aliases":[],"metavar":"PORT","help":"Port to listen on","env":"PORT","possible_values":[],"default":"8080"}"#));
+    /// ```
+    #[must_use]
+    pub fn json_output(mut self) -> Self {
+        self.info.json_output = true;
+        self
+    }
+
+    /// Set exit codes [`run`](OptionParser::run) uses for each kind of failure
+    ///
+    /// By default help, version and completion exit with `0` and everything else with `1`, see
+    /// [`ErrorKind::exit_code`]. [`ErrorKind::sysexits`] follows BSD `sysexits.h` and can be
+    /// used as is or as a fallback for kinds you don't care about. With
+    /// [`accumulate_errors`](OptionParser::accumulate_errors) the code comes from the first
+    /// failure.
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// let token = env("APP_TOKEN").argument::<String>("TOKEN");
+    /// let port = long("port").argument::<u16>("PORT");
+    /// let parser = construct!(token, port)
+    ///     .to_options()
+    ///     .exit_codes(|kind| match kind {
+    ///         ErrorKind::MissingEnv => 3,
+    ///         kind => kind.sysexits(),
+    ///     });
+    ///
+    /// assert_eq!(parser.exit_code(ErrorKind::MissingEnv), 3);
+    /// assert_eq!(parser.exit_code(ErrorKind::UnknownName), 64);
+    /// assert_eq!(parser.exit_code(ErrorKind::Help), 0);
+    ///
+    /// let err = parser.run_structured(&["--port", "http"]).unwrap_err();
+    /// assert_eq!(parser.exit_code(err.kind()), 3);
+    /// ```
+    #[must_use]
+    pub fn exit_codes(mut self, policy: fn(ErrorKind) -> i32) -> Self {
+        self.info.exit_codes = policy;
+        self
+    }
+
+    /// Exit code [`run`](OptionParser::run) uses for a failure of this kind
+    ///
+    /// See [`exit_codes`](OptionParser::exit_codes).
+    #[must_use]
+    pub fn exit_code(&self, kind: ErrorKind) -> i32 {
+        (self.info.exit_codes)(kind)
+    }
 }
 
 impl Info {
     #[inline(never)]
-    fn mk_help_parser(&self) -> impl Parser<()> {
-        self.help_arg.clone().req_flag(())
+    fn mk_help_parser(&self, messages: &dyn Messages) -> impl Parser<()> {
+        let mut arg = self.help_arg.clone();
+        if arg.help.is_none() {
+            arg.help = Some(messages.help_flag().into());
+        }
+        arg.req_flag(())
     }
     #[inline(never)]
-    fn mk_version_parser(&self) -> impl Parser<()> {
-        self.version_arg.clone().req_flag(())
+    fn mk_version_parser(&self, messages: &dyn Messages) -> impl Parser<()> {
+        let mut arg = self.version_arg.clone();
+        if arg.help.is_none() {
+            arg.help = Some(messages.version_flag().into());
+        }
+        arg.req_flag(())
+    }
+
+    /// Meta for `--help` and `--version` with descriptions from the catalog
+    pub(crate) fn help_meta(&self, messages: &dyn Messages) -> Meta {
+        let help = self.mk_help_parser(messages).meta();
+        match &self.version {
+            Some(_) => Meta::And(vec![help, self.mk_version_parser(messages).meta()]),
+            None => help,
+        }
     }
 }
 
 impl Parser<ExtraParams> for Info {
     fn eval(&self, args: &mut State) -> Result<ExtraParams, Error> {
-        let help = self.mk_help_parser();
+        let help = self.mk_help_parser(&*args.messages);
         if help.eval(args).is_ok() {
             return Ok(ExtraParams::Help(help.eval(args).is_ok()));
         }
 
         if let Some(version) = &self.version {
-            if self.mk_version_parser().eval(args).is_ok() {
+            if self.mk_version_parser(&*args.messages).eval(args).is_ok() {
                 return Ok(ExtraParams::Version(version.clone()));
             }
         }
@@ -732,11 +1158,7 @@ impl Parser<ExtraParams> for Info {
     }
 
     fn meta(&self) -> Meta {
-        let help = self.mk_help_parser().meta();
-        match &self.version {
-            Some(_) => Meta::And(vec![help, self.mk_version_parser().meta()]),
-            None => help,
-        }
+        self.help_meta(&*self.messages)
     }
 }
 
@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use crate::{info::Info, meta_help::Metavar, parsers::NamedArg, Doc, Meta};
 
 #[doc(hidden)]
@@ -11,13 +13,20 @@ pub enum Item {
     },
     /// Positional item, consumed from the the front of the arguments
     /// <FILE>
-    Positional { metavar: Metavar, help: Option<Doc> },
+    Positional {
+        metavar: Metavar,
+        help: Option<Doc>,
+        /// see [`possible_values`](crate::parsers::ParsePositional::possible_values)
+        possible: Vec<Cow<'static, str>>,
+    },
     Command {
-        name: &'static str,
+        name: Cow<'static, str>,
         short: Option<char>,
         help: Option<Doc>,
         meta: Box<Meta>,
         info: Box<Info>,
+        /// see [`visible_alias`](crate::parsers::ParseCommand::visible_alias)
+        aliases: Vec<Cow<'static, str>>,
     },
     /// short or long name, consumed anywhere
     /// -f
@@ -26,8 +35,15 @@ pub enum Item {
         name: ShortLong,
         /// used for disambiguation
         shorts: Vec<char>,
-        env: Option<&'static str>,
+        /// used for disambiguation and abbreviations
+        longs: Vec<Cow<'static, str>>,
+        env: Option<Cow<'static, str>>,
         help: Option<Doc>,
+        /// rendered as `--[no-]name`, see [`negatable_switch`](crate::NamedArg::negatable_switch)
+        negatable: bool,
+        relations: Relations,
+        /// see [`visible_alias`](NamedArg::visible_alias)
+        aliases: Vec<ShortLong>,
     },
     /// Short or long name followed by a value, consumed anywhere
     /// -f <VAL>
@@ -36,9 +52,18 @@ pub enum Item {
         name: ShortLong,
         /// used for disambiguation
         shorts: Vec<char>,
+        /// used for disambiguation and abbreviations
+        longs: Vec<Cow<'static, str>>,
         metavar: Metavar,
-        env: Option<&'static str>,
+        /// rendered as `METAVAR,...`, see [`delimited`](crate::parsers::ParseArgument::delimited)
+        delimiter: Option<char>,
+        env: Option<Cow<'static, str>>,
         help: Option<Doc>,
+        relations: Relations,
+        /// see [`possible_values`](crate::parsers::ParseArgument::possible_values)
+        possible: Vec<Cow<'static, str>>,
+        /// see [`visible_alias`](NamedArg::visible_alias)
+        aliases: Vec<ShortLong>,
     },
 }
 
@@ -50,6 +75,16 @@ impl Item {
             Item::Flag { .. } | Item::Argument { .. } => false,
         }
     }
+    /// Name of the item as it is spelled on a command line: `--name`, `-n`, `FILE` or `command`
+    pub(crate) fn name(&self) -> String {
+        match self {
+            Item::Any { metavar, .. } => metavar.monochrome(false),
+            Item::Positional { metavar, .. } => metavar.0.to_string(),
+            Item::Command { name, .. } => name.to_string(),
+            Item::Flag { name, .. } | Item::Argument { name, .. } => name.spelled(),
+        }
+    }
+
     /// Normalize name inside [`ShortLong`] into either short or long
     pub(crate) fn normalize(&mut self, short: bool) {
         match self {
@@ -59,25 +94,77 @@ impl Item {
     }
 }
 
+/// Prefix used to spell a long name on a command line, see [`prefix`](NamedArg::prefix)
+///
+/// Names with prefixes other than [`DoubleDash`](NamePrefix::DoubleDash) are recognized only if
+/// they are declared by some parser: `/tmp` is still a positional item unless there's a parser
+/// for `/tmp` name.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NamePrefix {
+    /// `--name`, the usual way
+    DoubleDash,
+    /// `-name`, as used by `find` or `xorg`
+    SingleDash,
+    /// `+name`, as used by `set +e` or `xorg`
+    Plus,
+    /// `/name`, as used by some Windows tools
+    Slash,
+}
+
+impl NamePrefix {
+    pub(crate) fn as_str(self) -> &'static str {
+        match self {
+            NamePrefix::DoubleDash => "--",
+            NamePrefix::SingleDash => "-",
+            NamePrefix::Plus => "+",
+            NamePrefix::Slash => "/",
+        }
+    }
+}
+
+/// Relations with other named items, see [`conflicts_with`](NamedArg::conflicts_with) and
+/// [`requires`](NamedArg::requires)
 #[doc(hidden)]
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug, Default)]
+pub struct Relations {
+    /// names as they are spelled on a command line: `--yaml`, `-y`
+    pub(crate) conflicts: Vec<Cow<'static, str>>,
+    pub(crate) requires: Vec<Cow<'static, str>>,
+}
+
+impl Relations {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.conflicts.is_empty() && self.requires.is_empty()
+    }
+}
+
+#[doc(hidden)]
+#[derive(Clone, Debug)]
 pub enum ShortLong {
     Short(char),
-    Long(&'static str),
-    Both(char, &'static str),
+    Long(NamePrefix, Cow<'static, str>),
+    Both(char, NamePrefix, Cow<'static, str>),
 }
 
 impl ShortLong {
-    pub(crate) fn as_long(&self) -> Option<&'static str> {
+    /// Name as it is spelled on a command line, long name for [`Both`](ShortLong::Both)
+    pub(crate) fn spelled(&self) -> String {
+        match self {
+            ShortLong::Short(s) => format!("-{}", s),
+            ShortLong::Long(p, l) | ShortLong::Both(_, p, l) => format!("{}{}", p.as_str(), l),
+        }
+    }
+
+    pub(crate) fn as_long(&self) -> Option<(NamePrefix, &str)> {
         match self {
-            ShortLong::Long(l) | ShortLong::Both(_, l) => Some(l),
+            ShortLong::Long(p, l) | ShortLong::Both(_, p, l) => Some((*p, l)),
             ShortLong::Short(_) => None,
         }
     }
     pub(crate) fn as_short(&self) -> Option<char> {
         match self {
-            ShortLong::Short(s) | ShortLong::Both(s, _) => Some(*s),
-            ShortLong::Long(_) => None,
+            ShortLong::Short(s) | ShortLong::Both(s, _, _) => Some(*s),
+            ShortLong::Long(_, _) => None,
         }
     }
 }
@@ -88,13 +175,13 @@ impl PartialEq<&str> for ShortLong {
             let mut tmp = [0u8; 4];
             s.strip_prefix('-') == Some(c.encode_utf8(&mut tmp))
         }
-        fn long_eq(l: &str, s: &str) -> bool {
-            Some(l) == s.strip_prefix("--")
+        fn long_eq(p: NamePrefix, l: &str, s: &str) -> bool {
+            Some(l) == s.strip_prefix(p.as_str())
         }
         match self {
             ShortLong::Short(s) => short_eq(*s, other),
-            ShortLong::Long(l) => long_eq(l, other),
-            ShortLong::Both(s, l) => short_eq(*s, other) || long_eq(l, other),
+            ShortLong::Long(p, l) => long_eq(*p, l, other),
+            ShortLong::Both(s, p, l) => short_eq(*s, other) || long_eq(*p, l, other),
         }
     }
 }
@@ -104,12 +191,12 @@ impl ShortLong {
     /// leaves both Short and Long untouched
     pub(crate) fn normalize(&mut self, short: bool) {
         match self {
-            ShortLong::Short(_) | ShortLong::Long(_) => {}
-            ShortLong::Both(s, l) => {
+            ShortLong::Short(_) | ShortLong::Long(_, _) => {}
+            ShortLong::Both(s, p, l) => {
                 if short {
                     *self = Self::Short(*s);
                 } else {
-                    *self = Self::Long(l);
+                    *self = Self::Long(*p, std::mem::take(l));
                 }
             }
         }
@@ -120,11 +207,19 @@ impl TryFrom<&NamedArg> for ShortLong {
     type Error = ();
 
     fn try_from(named: &NamedArg) -> Result<Self, Self::Error> {
-        match (named.short.is_empty(), named.long.is_empty()) {
-            (true, true) => Err(()),
-            (true, false) => Ok(Self::Long(named.long[0])),
-            (false, true) => Ok(Self::Short(named.short[0])),
-            (false, false) => Ok(Self::Both(named.short[0], named.long[0])),
+        // prefer main names over aliases, but use aliases if there are no main names
+        let (short, long) = match (named.short.first(), named.long.first()) {
+            (None, None) => (
+                named.short_aliases.first().map(|(s, _)| s),
+                named.long_aliases.first().map(|(l, _)| l),
+            ),
+            names => names,
+        };
+        match (short, long) {
+            (None, None) => Err(()),
+            (None, Some(long)) => Ok(Self::Long(named.prefix, long.clone())),
+            (Some(short), None) => Ok(Self::Short(*short)),
+            (Some(short), Some(long)) => Ok(Self::Both(*short, named.prefix, long.clone())),
         }
     }
 }
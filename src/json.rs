@@ -0,0 +1,239 @@
+//! JSON output for failures and help, see [`json_output`](crate::OptionParser::json_output)
+//!
+//! Output is small and has a fixed shape so values are built by hand instead of pulling in
+//! `serde`.
+
+use std::fmt::{self, Write};
+
+use crate::{
+    buffer::{Color, Doc},
+    info::Info,
+    item::{Item, ShortLong},
+    meta::Note,
+    Meta,
+};
+
+/// Environment variable that enables JSON output for [`run`](crate::OptionParser::run) when set
+/// to `json`
+pub(crate) const ENV_VAR: &str = "BPAF_OUTPUT";
+
+/// Check if JSON output is requested with [`ENV_VAR`]
+pub(crate) fn requested_by_env() -> bool {
+    std::env::var_os(ENV_VAR).map_or(false, |v| v == "json")
+}
+
+/// Just enough of JSON to describe failures and help
+#[derive(Debug, Clone)]
+pub(crate) enum Value {
+    Null,
+    Number(usize),
+    String(String),
+    Array(Vec<Value>),
+    Object(Vec<(&'static str, Value)>),
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_owned())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl From<usize> for Value {
+    fn from(value: usize) -> Self {
+        Value::Number(value)
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Value::Null, Into::into)
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(value: Vec<T>) -> Self {
+        Value::Array(value.into_iter().map(Into::into).collect())
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => f.write_str("null"),
+            Value::Number(n) => write!(f, "{}", n),
+            Value::String(s) => write_string(f, s),
+            Value::Array(xs) => {
+                f.write_char('[')?;
+                for (ix, x) in xs.iter().enumerate() {
+                    if ix > 0 {
+                        f.write_char(',')?;
+                    }
+                    x.fmt(f)?;
+                }
+                f.write_char(']')
+            }
+            Value::Object(fields) => {
+                f.write_char('{')?;
+                for (ix, (name, value)) in fields.iter().enumerate() {
+                    if ix > 0 {
+                        f.write_char(',')?;
+                    }
+                    write_string(f, name)?;
+                    f.write_char(':')?;
+                    value.fmt(f)?;
+                }
+                f.write_char('}')
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if u32::from(c) < 0x20 => write!(f, "\\u{:04x}", u32::from(c))?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+/// Plain text of a document, lines are not wrapped
+pub(crate) fn text(doc: &Doc, full: bool) -> String {
+    doc.render_console(full, Color::Monochrome, usize::MAX / 2)
+}
+
+/// Parser as described by `--help`: usage, description and all the items
+pub(crate) fn help(path: &[String], info: &Info, parser_meta: &Meta, help_meta: &Meta) -> Value {
+    let usage = if let Some(usage) = &info.usage {
+        text(usage, true)
+    } else {
+        let mut doc = Doc::default();
+        doc.write_path(path);
+        doc.write_meta(parser_meta, true);
+        text(&doc, true)
+    };
+    let mut items = Vec::new();
+    collect_items(parser_meta, None, &mut items);
+    collect_items(help_meta, None, &mut items);
+    let doc = |doc: &Option<Doc>| Value::from(doc.as_ref().map(|d| text(d, true)));
+    Value::Object(vec![
+        ("usage", usage.into()),
+        ("description", doc(&info.descr)),
+        ("header", doc(&info.header)),
+        ("footer", doc(&info.footer)),
+        ("items", Value::Array(items)),
+    ])
+}
+
+fn collect_items(meta: &Meta, default: Option<&str>, items: &mut Vec<Value>) {
+    match meta {
+        Meta::And(xs) | Meta::Or(xs) => {
+            for x in xs {
+                collect_items(x, default, items);
+            }
+        }
+        Meta::Optional(x)
+        | Meta::Required(x)
+        | Meta::Adjacent(x)
+        | Meta::Many(x)
+        | Meta::Bounded(x, _, _)
+        | Meta::Subsection(x, _)
+        | Meta::Suffix(x, _)
+        | Meta::Strict(x)
+        | Meta::CustomUsage(x, _) => collect_items(x, default, items),
+        Meta::Note(x, Note::Default(value)) => collect_items(x, Some(value), items),
+        Meta::Note(x, Note::Range(..)) => collect_items(x, default, items),
+        Meta::Item(item) => items.push(item_value(item, default)),
+        Meta::Skip | Meta::Hidden(_) => {}
+    }
+}
+
+fn item_value(item: &Item, default: Option<&str>) -> Value {
+    let doc = |doc: &Option<Doc>| Value::from(doc.as_ref().map(|d| text(d, true)));
+    let strings = |xs: &[std::borrow::Cow<'static, str>]| {
+        Value::from(xs.iter().map(|x| x.to_string()).collect::<Vec<_>>())
+    };
+    let mut fields = Vec::new();
+    match item {
+        Item::Flag {
+            name,
+            env,
+            help,
+            aliases,
+            ..
+        } => {
+            fields.push(("kind", "flag".into()));
+            push_names(&mut fields, name, aliases);
+            fields.push(("help", doc(help)));
+            fields.push(("env", env.as_deref().into()));
+        }
+        Item::Argument {
+            name,
+            metavar,
+            env,
+            help,
+            possible,
+            aliases,
+            ..
+        } => {
+            fields.push(("kind", "argument".into()));
+            push_names(&mut fields, name, aliases);
+            fields.push(("metavar", metavar.0.as_ref().into()));
+            fields.push(("help", doc(help)));
+            fields.push(("env", env.as_deref().into()));
+            fields.push(("possible_values", strings(possible)));
+        }
+        Item::Positional {
+            metavar,
+            help,
+            possible,
+        } => {
+            fields.push(("kind", "positional".into()));
+            fields.push(("metavar", metavar.0.as_ref().into()));
+            fields.push(("help", doc(help)));
+            fields.push(("possible_values", strings(possible)));
+        }
+        Item::Command {
+            name,
+            short,
+            help,
+            aliases,
+            ..
+        } => {
+            fields.push(("kind", "command".into()));
+            fields.push(("name", name.as_ref().into()));
+            fields.push(("short", short.map(String::from).into()));
+            fields.push(("aliases", strings(aliases)));
+            fields.push(("help", doc(help)));
+        }
+        Item::Any { metavar, help, .. } => {
+            fields.push(("kind", "any".into()));
+            fields.push(("metavar", text(metavar, true).into()));
+            fields.push(("help", doc(help)));
+        }
+    }
+    fields.push(("default", default.into()));
+    Value::Object(fields)
+}
+
+fn push_names(fields: &mut Vec<(&'static str, Value)>, name: &ShortLong, aliases: &[ShortLong]) {
+    let short = name.as_short().map(|s| format!("-{}", s));
+    let long = name.as_long().map(|(p, l)| format!("{}{}", p.as_str(), l));
+    let aliases = aliases.iter().map(ShortLong::spelled).collect::<Vec<_>>();
+    fields.push(("short", short.into()));
+    fields.push(("long", long.into()));
+    fields.push(("aliases", aliases.into()));
+}
@@ -46,6 +46,8 @@
 //!   [`help`](NamedArg::help).
 //! - [`NamedArg::switch`] - simple switch that returns `true` if it's present on a command
 //!   line and `false` otherwise.
+//! - [`NamedArg::negatable_switch`] - a variant of `switch` that also accepts `--no-name` to
+//!   turn it off again.
 //! - [`NamedArg::flag`] - a variant of `switch` that lets you return one of two custom
 //!   values, for example `Color::On` and `Color::Off`.
 //! - [`NamedArg::req_flag`] - a variant of `switch` that only only succeeds when it's name
@@ -133,6 +135,8 @@
 //! - [`ParseFailure`] contains the parse outcome, you can consume it either by hands or using one
 //!   of [`exit_code`](ParseFailure::exit_code), [`unwrap_stdout`](ParseFailure::unwrap_stdout) and
 //!   [`unwrap_stderr`](ParseFailure::unwrap_stderr)
+//! - [`run_structured`](OptionParser::run_structured) produces a [`ParseError`] instead, use it
+//!   to tell different kinds of failures apart
 //!
 //! ## Cargo features
 //!
@@ -175,15 +179,19 @@ mod complete_gen;
 mod complete_run;
 #[cfg(feature = "autocomplete")]
 mod complete_shell;
+mod diagnostic;
 pub mod doc;
 mod error;
 mod from_os_str;
 mod info;
 mod item;
+mod json;
+mod messages;
 mod meta;
 mod meta_help;
 mod meta_youmean;
 pub mod params;
+mod shell_words;
 mod structs;
 #[cfg(test)]
 mod tests;
@@ -198,19 +206,25 @@ pub mod parsers {
     pub use crate::complete_shell::ParseCompShell;
     #[doc(inline)]
     pub use crate::params::{
-        NamedArg, ParseAny, ParseArgument, ParseCommand, ParseFlag, ParsePositional,
+        IntoName, NamedArg, ParseAny, ParseArgument, ParseCommand, ParseDelimited, ParseFlag,
+        ParseKeyValue, ParseKeyValueCollect, ParsePositional,
     };
     #[doc(inline)]
     pub use crate::structs::{
-        ParseCollect, ParseCon, ParseCount, ParseFallback, ParseFallbackWith, ParseLast, ParseMany,
-        ParseOptional, ParseSome,
+        ParseAndThen, ParseCollect, ParseCon, ParseContext, ParseCount, ParseFallback,
+        ParseFallbackWith, ParseLast, ParseMany, ParseOptional, ParseRange, ParseSome,
+        ParseWithSource,
     };
 }
 
 // -------------------------------------------------------------------
 
 #[doc(inline)]
-pub use crate::{args::Args, buffer::Doc, error::ParseFailure, info::OptionParser};
+pub use crate::{
+    args::Args, buffer::Doc, error::ErrorKind, error::ParseError, error::ParseFailure,
+    info::OptionParser, item::NamePrefix, messages::English, messages::Messages,
+    params::DuplicateKeys, shell_words::CommandLineError, structs::Spanned, structs::ValueSource,
+};
 
 #[doc(hidden)]
 // used by construct macro, not part of public API
@@ -220,13 +234,15 @@ use std::{marker::PhantomData, str::FromStr};
 
 use crate::{
     buffer::{MetaInfo, Style},
-    item::Item,
+    item::{Item, Relations},
+    meta_help::Metavar,
     params::build_positional,
-    parsers::{NamedArg, ParseAny, ParseCommand, ParsePositional},
+    parsers::{IntoName, NamedArg, ParseAny, ParseCommand, ParsePositional},
     structs::{
-        ParseCollect, ParseCount, ParseFail, ParseFallback, ParseFallbackWith, ParseGroupHelp,
-        ParseGuard, ParseHide, ParseLast, ParseMany, ParseMap, ParseOptional, ParseOrElse,
-        ParsePure, ParsePureWith, ParseSome, ParseUsage, ParseWith, ParseWithGroupHelp,
+        Bounds, ParseAndThen, ParseCollect, ParseContext, ParseCount, ParseFail, ParseFallback,
+        ParseFallbackWith, ParseGroupHelp, ParseGuard, ParseHide, ParseLast, ParseMany, ParseMap,
+        ParseOptional, ParseOrElse, ParsePure, ParsePureWith, ParseRange, ParseSome, ParseSpanned,
+        ParseUsage, ParseWith, ParseWithGroupHelp, ParseWithSource,
     },
 };
 
@@ -457,8 +473,12 @@ macro_rules! construct {
                 $front = Ok($front?);
             }
             $(let $fields = $fields.eval(args);)*
-            let $front = $front?;
-            $(let $fields = $fields?;)*
+            let mut failure = ::std::option::Option::None;
+            let $front = $crate::Error::collect($front, &mut failure, args);
+            $(let $fields = $crate::Error::collect($fields, &mut failure, args);)*
+
+            let $front = $crate::Error::finish($front, &mut failure)?;
+            $(let $fields = $crate::Error::finish($fields, &mut failure)?;)*
 
             args.current = None;
             ::std::result::Result::Ok::<_, $crate::Error>
@@ -628,6 +648,7 @@ pub trait Parser<T> {
         ParseMany {
             inner: self,
             catch: false,
+            bounds: Bounds::default(),
         }
     }
     // }}}
@@ -655,6 +676,7 @@ pub trait Parser<T> {
         ParseCollect {
             inner: self,
             catch: false,
+            bounds: Bounds::default(),
             ctx: PhantomData,
         }
     }
@@ -689,6 +711,7 @@ pub trait Parser<T> {
             inner: self,
             message,
             catch: false,
+            bounds: Bounds::default(),
         }
     }
     // }}}
@@ -816,6 +839,65 @@ pub trait Parser<T> {
     }
     // }}}
 
+    // {{{ and_then
+    /// Use a parsed value to create a parser for the rest of the input
+    ///
+    /// Once `self` succeeds its value is passed to `next` and the parser `next` returns runs on
+    /// the remaining input. This allows names, possible values or dynamic completion of later
+    /// items to depend on an earlier one, for example a target can be completed only from the
+    /// package picked with `--package`. Since bpaf doesn't care about the order of named items
+    /// dependency holds regardless of where the items are on a command line.
+    ///
+    /// There's no parsed value when rendering help or usage, bpaf passes `representative` to
+    /// `next` once instead and help and usage describe the parser it returns. This parser is
+    /// also the only place bpaf looks for names when splitting the command line, so pick a value
+    /// that makes `next` use every name it can - usually the one that restricts the least, such
+    /// as `None` for an optional item. `representative` is never returned as a parsed value.
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// let package = long("package").argument::<String>("PKG").optional();
+    /// let parser = package
+    ///     // without a package any binary is allowed
+    ///     .and_then(None, |package| {
+    ///         let bins = match package.as_deref() {
+    ///             Some("client") => vec!["cli", "gui"],
+    ///             Some("server") => vec!["srv", "admin"],
+    ///             _ => vec!["cli", "gui", "srv", "admin"],
+    ///         };
+    ///         long("bin")
+    ///             .argument::<String>("BIN")
+    ///             .possible_values(bins)
+    ///             .map(move |bin| (package.clone(), bin))
+    ///     })
+    ///     .to_options();
+    ///
+    /// let r = parser.run_inner(&["--bin", "admin", "--package", "server"]).unwrap();
+    /// assert_eq!(r, (Some("server".to_owned()), "admin".to_owned()));
+    ///
+    /// let r = parser
+    ///     .run_inner(&["--package", "client", "--bin", "admin"])
+    ///     .unwrap_err()
+    ///     .unwrap_stderr();
+    /// assert_eq!(r, "`admin` is not a valid value, expected one of `cli`, `gui`");
+    /// ```
+    #[must_use]
+    fn and_then<F, Q, R>(self, representative: T, next: F) -> ParseAndThen<T, Self, F>
+    where
+        Self: Sized + Parser<T>,
+        F: Fn(T) -> Q,
+        Q: Parser<R>,
+    {
+        let help = next(representative).meta();
+        ParseAndThen {
+            inner: self,
+            help,
+            inner_res: PhantomData,
+            next_fn: next,
+        }
+    }
+    // }}}
+
     // {{{ guard
     /// Validate or fail with a message
     ///
@@ -841,6 +923,136 @@ pub trait Parser<T> {
     }
     // }}}
 
+    // {{{ range
+    /// Validate that a value is within a range
+    ///
+    /// A more specific version of [`guard`](Parser::guard) for numbers and other ordered values:
+    /// error message states both the bound and the value, help message shows the range next to
+    /// the item.
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// let parser = long("jobs")
+    ///     .help("Number of parallel jobs")
+    ///     .argument::<u32>("N")
+    ///     .range(1..=64)
+    ///     .to_options();
+    ///
+    /// let r = parser.run_inner(&["--jobs", "100"]).unwrap_err().unwrap_stderr();
+    /// assert_eq!(r, "`100` is out of range, expected at most `64`");
+    ///
+    /// let r = parser.run_inner(&["--help"]).unwrap_err().unwrap_stdout();
+    /// let expected = "\
+    /// Usage: --jobs=N
+    ///
+    /// Available options:
+    ///         --jobs=N  Number of parallel jobs [1..=64]
+    ///     -h, --help    Prints help information
+    /// ";
+    /// assert_eq!(r, expected);
+    /// ```
+    ///
+    /// # Derive usage
+    /// Derive variant takes a range expression: `#[bpaf(argument("N"), range(1..=64))]`
+    #[must_use]
+    fn range<R>(self, range: R) -> ParseRange<Self, R>
+    where
+        Self: Sized + Parser<T>,
+        T: PartialOrd + std::fmt::Display,
+        R: std::ops::RangeBounds<T>,
+    {
+        ParseRange { inner: self, range }
+    }
+    // }}}
+
+    // {{{ spanned
+    /// Remember where on the command line the value comes from
+    ///
+    /// Wraps the value into [`Spanned`] that contains indices of command line arguments consumed
+    /// by the inner parser and the original text of the item the value was parsed from. Use
+    /// [`Spanned::error`] to report problems found by validation performed after parsing the same
+    /// way `bpaf` reports its own errors.
+    ///
+    /// Indices start from the first argument after the program name, items from response files
+    /// point to the `@path` argument. Values that don't come from a command line, such as
+    /// [`fallback`](Parser::fallback) or environment variables, have no indices.
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// let verbose = short('v').switch();
+    /// let port = long("port").argument::<u16>("PORT").spanned();
+    /// let parser = construct!(verbose, port).to_options();
+    ///
+    /// let (_verbose, port) = parser.run_inner(&["-v", "--port", "80"]).unwrap();
+    /// assert_eq!(port.value, 80);
+    /// assert_eq!(port.indices(), &[1, 2]);
+    ///
+    /// // port 80 turns out to be in use
+    /// let err = port.error("port is already in use").unwrap_stderr();
+    /// assert_eq!(err, "`80`: port is already in use");
+    /// ```
+    #[must_use]
+    fn spanned(self) -> ParseSpanned<Self>
+    where
+        Self: Sized + Parser<T>,
+    {
+        ParseSpanned { inner: self }
+    }
+    // }}}
+
+    // {{{ with_source
+    /// Remember if the value comes from the command line, environment or a fallback
+    ///
+    /// Produces the value together with [`ValueSource`], useful for audit logging or to let
+    /// settings from a configuration file override defaults but not explicitly passed values.
+    /// Values produced from the command line are [`Argv`](ValueSource::Argv), environment
+    /// variables given with [`env`](NamedArg::env) are [`Env`](ValueSource::Env), values from
+    /// [`fallback`](Parser::fallback), [`fallback_with`](Parser::fallback_with) and values
+    /// parsers produce for absent items, such as `false` for a switch, are
+    /// [`Fallback`](ValueSource::Fallback). [`pure`] and [`pure_with`] produce
+    /// [`Pure`](ValueSource::Pure).
+    ///
+    /// Meant for parsers that produce a single value: if a parser combines several values from
+    /// different sources, only one of them is reported.
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// let jobs = long("jobs")
+    ///     .env("BPAF_DOC_WITH_SOURCE_JOBS")
+    ///     .argument::<u32>("N")
+    ///     .fallback(4)
+    ///     .with_source();
+    /// let parser = jobs.to_options();
+    ///
+    /// let r = parser.run_inner(&["--jobs", "8"]).unwrap();
+    /// assert_eq!(r, (8, ValueSource::Argv));
+    ///
+    /// let r = parser.run_inner(&[]).unwrap();
+    /// assert_eq!(r, (4, ValueSource::Fallback));
+    /// ```
+    ///
+    /// # Derive usage
+    /// Field type should be a tuple of the value and [`ValueSource`], the rest of the annotations
+    /// work as if the type was just the value:
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// #[derive(Debug, Clone, Bpaf)]
+    /// #[bpaf(options)]
+    /// struct Options {
+    ///     #[bpaf(argument("N"), fallback(4), with_source)]
+    ///     jobs: (u32, ValueSource),
+    /// }
+    /// ```
+    #[must_use]
+    fn with_source(self) -> ParseWithSource<Self>
+    where
+        Self: Sized + Parser<T>,
+    {
+        ParseWithSource { inner: self }
+    }
+    // }}}
+
     // combine
     // {{{ fallback
     /// Use this value as default if the value isn't present on a command line
@@ -863,7 +1075,7 @@ pub trait Parser<T> {
         ParseFallback {
             inner: self,
             value,
-            value_str: String::new(),
+            value_str: None,
         }
     }
     // }}}
@@ -891,7 +1103,7 @@ pub trait Parser<T> {
             inner: self,
             inner_res: PhantomData,
             fallback,
-            value_str: String::new(),
+            value_str: None,
             err: PhantomData,
         }
     }
@@ -1065,6 +1277,42 @@ pub trait Parser<T> {
         ParseWithGroupHelp { inner: self, f }
     }
 
+    // {{{ context
+    /// Describe what the parser is doing for error messages
+    ///
+    /// If the inner parser fails - error message is prefixed with the context, similar to
+    /// `anyhow::Context`. Contexts nest, outer contexts go first, followed by a path to the
+    /// command the failure happened in. Errors about missing items and other failures that
+    /// can be handled by [`optional`](Parser::optional), [`fallback`](Parser::fallback) and
+    /// similar are left untouched.
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// let port = long("port").argument::<u16>("PORT");
+    /// let host = long("host").argument::<String>("HOST");
+    /// let db = construct!(host, port).context("while reading database options");
+    /// let parser = db.to_options().command("serve").to_options();
+    ///
+    /// let r = parser
+    ///     .run_inner(&["serve", "--host", "db", "--port", "http"])
+    ///     .unwrap_err()
+    ///     .unwrap_stderr();
+    /// let expected = "`serve`: while reading database options: \
+    /// couldn't parse `http`: invalid digit found in string";
+    /// assert_eq!(r, expected);
+    /// ```
+    #[must_use]
+    fn context(self, context: &'static str) -> ParseContext<Self>
+    where
+        Self: Sized + Parser<T>,
+    {
+        ParseContext {
+            inner: self,
+            context,
+        }
+    }
+    // }}}
+
     // {{{ comp
     /// Dynamic shell completion
     ///
@@ -1185,6 +1433,7 @@ pub trait Parser<T> {
         OptionParser {
             info: info::Info::default(),
             inner: Box::new(self),
+            on_warning: None,
         }
     }
     // }}}
@@ -1304,7 +1553,12 @@ pub fn short(short: char) -> NamedArg {
         short: vec![short],
         env: Vec::new(),
         long: Vec::new(),
+        prefix: NamePrefix::DoubleDash,
         help: None,
+        relations: Relations::default(),
+        deprecated: None,
+        short_aliases: Vec::new(),
+        long_aliases: Vec::new(),
     }
 }
 
@@ -1316,12 +1570,20 @@ pub fn short(short: char) -> NamedArg {
 ///
 #[cfg_attr(not(doctest), doc = include_str!("docs2/short_long_env.md"))]
 #[must_use]
-pub fn long(long: &'static str) -> NamedArg {
+pub fn long<M>(long: M) -> NamedArg
+where
+    M: IntoName,
+{
     NamedArg {
         short: Vec::new(),
-        long: vec![long],
+        long: vec![long.into_name()],
+        prefix: NamePrefix::DoubleDash,
         env: Vec::new(),
         help: None,
+        relations: Relations::default(),
+        deprecated: None,
+        short_aliases: Vec::new(),
+        long_aliases: Vec::new(),
     }
 }
 
@@ -1345,12 +1607,20 @@ pub fn long(long: &'static str) -> NamedArg {
 ///
 #[cfg_attr(not(doctest), doc = include_str!("docs2/short_long_env.md"))]
 #[must_use]
-pub fn env(variable: &'static str) -> NamedArg {
+pub fn env<M>(variable: M) -> NamedArg
+where
+    M: IntoName,
+{
     NamedArg {
         short: Vec::new(),
         long: Vec::new(),
+        prefix: NamePrefix::DoubleDash,
         help: None,
-        env: vec![variable],
+        env: vec![variable.into_name()],
+        relations: Relations::default(),
+        deprecated: None,
+        short_aliases: Vec::new(),
+        long_aliases: Vec::new(),
     }
 }
 
@@ -1396,10 +1666,12 @@ pub fn env(variable: &'static str) -> NamedArg {
 /// By default `bpaf` accepts positional items with or without `--` where values permit, you can
 /// further restrict the parser to accept positional items only on the right side of `--` using
 /// [`strict`](ParsePositional::strict).
+///
+/// To use a metavariable computed at runtime - see [`metavar`](ParsePositional::metavar).
 #[cfg_attr(not(doctest), doc = include_str!("docs2/positional.md"))]
 #[must_use]
 pub fn positional<T>(metavar: &'static str) -> ParsePositional<T> {
-    build_positional(metavar)
+    build_positional(Metavar(std::borrow::Cow::Borrowed(metavar)))
 }
 
 #[doc(hidden)]
@@ -1409,11 +1681,13 @@ where
     T: 'static,
 {
     ParseCommand {
-        longs: vec![name],
+        longs: vec![std::borrow::Cow::Borrowed(name)],
         shorts: Vec::new(),
         help: subparser.short_descr().map(Into::into),
         subparser,
         adjacent: false,
+        deprecated: None,
+        visible: Vec::new(),
     }
 }
 
@@ -1464,7 +1738,7 @@ where
         metavar: [(metavar, Style::Metavar)][..].into(),
         help: None,
         check: Box::new(move |os: std::ffi::OsString| {
-            match crate::from_os_str::parse_os_str::<I>(os) {
+            match crate::from_os_str::parse_os_str::<I>(os, &crate::messages::English) {
                 Ok(v) => check(v),
                 Err(_) => None,
             }
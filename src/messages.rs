@@ -0,0 +1,581 @@
+//! Text `bpaf` uses in help messages, documentation and errors
+//!
+//! Set a catalog with [`OptionParser::messages`](crate::OptionParser::messages), English is used
+//! by default.
+
+use crate::Doc;
+
+/// Catalog of built-in messages and help headings
+///
+/// Every method has a default implementation that returns English text, so a catalog only needs
+/// to override what it translates. A catalog set on the top level parser applies to all the
+/// subcommands as well as to [`render_markdown`](crate::OptionParser::render_markdown),
+/// [`render_html`](crate::OptionParser::render_html) and
+/// [`render_manpage`](crate::OptionParser::render_manpage).
+///
+/// Some of the methods return templates: `{0}`, `{1}`, ... get replaced with parts of the message
+/// described in the method documentation, translation can use them in any order or skip them.
+/// Variants for unnamed items use the same placeholders as the named ones, but without the name.
+///
+/// Catalogs are shared with [`ParseError`](crate::ParseError) so they must be [`Sync`].
+///
+/// ```rust
+/// # use bpaf::*;
+/// struct German;
+/// impl Messages for German {
+///     fn usage(&self) -> &str {
+///         "Verwendung"
+///     }
+///     fn available_options(&self) -> &str {
+///         "Verfügbare Optionen:"
+///     }
+///     fn help_flag(&self) -> &str {
+///         "Zeigt die Hilfe an"
+///     }
+///     fn unexpected(&self) -> &str {
+///         "{0} wird in diesem Kontext nicht erwartet"
+///     }
+/// }
+///
+/// let parser = short('v').switch().to_options().messages(&German);
+///
+/// let r = parser.run_inner(&["--help"]).unwrap_err().unwrap_stdout();
+/// assert!(r.starts_with("Verwendung: "));
+/// assert!(r.contains("Zeigt die Hilfe an"));
+///
+/// let r = parser.run_inner(&["-x"]).unwrap_err().unwrap_stderr();
+/// assert_eq!(r, "`-x` wird in diesem Kontext nicht erwartet");
+/// ```
+pub trait Messages: Sync {
+    // {{{ help
+    /// Usage line header: `Usage`
+    fn usage(&self) -> &str {
+        "Usage"
+    }
+
+    /// Positional items section header: `Available positional items:`
+    fn available_positional(&self) -> &str {
+        "Available positional items:"
+    }
+
+    /// Named items section header: `Available options:`
+    fn available_options(&self) -> &str {
+        "Available options:"
+    }
+
+    /// Subcommands section header: `Available commands:`
+    fn available_commands(&self) -> &str {
+        "Available commands:"
+    }
+
+    /// `--version` output: `Version: {0}`, where `{0}` is the version
+    fn version(&self) -> &str {
+        "Version: {0}"
+    }
+
+    /// Help for `--help` unless set with [`help_parser`](crate::OptionParser::help_parser)
+    fn help_flag(&self) -> &str {
+        "Prints help information"
+    }
+
+    /// Help for `--version` unless set with
+    /// [`version_parser`](crate::OptionParser::version_parser)
+    fn version_flag(&self) -> &str {
+        "Prints version information"
+    }
+
+    /// Label for a list of aliases after the help: `aliases`
+    fn note_aliases(&self) -> &str {
+        "aliases"
+    }
+
+    /// Label for a list of possible values after the help: `possible values`
+    fn note_possible_values(&self) -> &str {
+        "possible values"
+    }
+
+    /// Label for a list of conflicting items after the help: `conflicts with`
+    fn note_conflicts(&self) -> &str {
+        "conflicts with"
+    }
+
+    /// Label for a list of required items after the help: `requires`
+    fn note_requires(&self) -> &str {
+        "requires"
+    }
+
+    /// Label for a [`fallback`](crate::Parser::fallback) value shown with
+    /// [`display_fallback`](crate::parsers::ParseFallback::display_fallback): `default`
+    fn note_default(&self) -> &str {
+        "default"
+    }
+
+    /// Environment variable in generated documentation: `Uses environment variable {0}`
+    fn env_var(&self) -> &str {
+        "Uses environment variable {0}"
+    }
+
+    /// Environment variable for a flag is set: `set`
+    fn env_set(&self) -> &str {
+        "set"
+    }
+
+    /// Environment variable for a flag is not set: `not set`
+    fn env_not_set(&self) -> &str {
+        "not set"
+    }
+
+    /// Environment variable for an argument is not set: `N/A`
+    fn env_no_value(&self) -> &str {
+        "N/A"
+    }
+
+    /// Header for the list of commands in html and markdown documentation: `Command summary`
+    fn command_summary(&self) -> &str {
+        "Command summary"
+    }
+
+    /// Manpage section with the application name: `NAME`
+    fn manpage_name(&self) -> &str {
+        "NAME"
+    }
+
+    /// Manpage section with the usage: `SYNOPSIS`
+    fn manpage_synopsis(&self) -> &str {
+        "SYNOPSIS"
+    }
+    // }}}
+
+    // {{{ errors and warnings
+    /// Prefix for errors printed by [`run`](crate::OptionParser::run): `Error: `
+    fn error_prefix(&self) -> &str {
+        "Error: "
+    }
+
+    /// Prefix for warnings printed by [`run`](crate::OptionParser::run): `Warning: `
+    fn warning_prefix(&self) -> &str {
+        "Warning: "
+    }
+
+    /// Header for failures reported together with
+    /// [`accumulate_errors`](crate::OptionParser::accumulate_errors): `found {0} problems:`
+    fn several_errors(&self) -> &str {
+        "found {0} problems:"
+    }
+
+    /// Label under the item a conflicting one is used with in
+    /// [`caret_diagnostics`](crate::OptionParser::caret_diagnostics): `conflicts with this`
+    fn label_conflict(&self) -> &str {
+        "conflicts with this"
+    }
+
+    /// Label under the first use of a repeated item in
+    /// [`caret_diagnostics`](crate::OptionParser::caret_diagnostics): `first used here`
+    fn label_first_use(&self) -> &str {
+        "first used here"
+    }
+
+    /// Deprecated item is used: `{0} is deprecated, {1}`, where `{1}` is the migration hint
+    fn deprecated(&self) -> &str {
+        "{0} is deprecated, {1}"
+    }
+
+    /// Item is not consumed by any parser: `{0} is not expected in this context`
+    fn unexpected(&self) -> &str {
+        "{0} is not expected in this context"
+    }
+
+    /// Required environment variable is missing: `environment variable {0} is not set`
+    fn env_missing(&self) -> &str {
+        "environment variable {0} is not set"
+    }
+
+    /// Strictly positional item comes before `--`: `expected {0} to be on the right side of {1}`
+    fn strictly_positional(&self) -> &str {
+        "expected {0} to be on the right side of {1}"
+    }
+
+    /// Non strictly positional item comes after `--`:
+    /// `expected {0} to be on the left side of {1}`
+    fn non_strictly_positional(&self) -> &str {
+        "expected {0} to be on the left side of {1}"
+    }
+
+    /// Value can't be parsed: `couldn't parse {0}: {1}`, where `{1}` is the parser's message
+    fn parse_failed(&self) -> &str {
+        "couldn't parse {0}: {1}"
+    }
+
+    /// Same as [`parse_failed`](Messages::parse_failed) for an unnamed value: `couldn't parse: {1}`
+    fn parse_failed_unnamed(&self) -> &str {
+        "couldn't parse: {1}"
+    }
+
+    /// Value is not valid UTF-8: `{0} is not a valid utf8`
+    fn not_utf8(&self) -> &str {
+        "{0} is not a valid utf8"
+    }
+
+    /// [`key_value`](crate::parsers::NamedArg::key_value) value has no `=`: `expected {0}={1}`,
+    /// where `{0}` and `{1}` are metavars of the key and the value
+    fn key_value_expected(&self) -> &str {
+        "expected {0}={1}"
+    }
+
+    /// [`key_value`](crate::parsers::NamedArg::key_value) key can't be parsed:
+    /// ``invalid key `{0}`: {1}``, where `{1}` is the parser's message
+    fn key_value_invalid_key(&self) -> &str {
+        "invalid key `{0}`: {1}"
+    }
+
+    /// [`key_value`](crate::parsers::NamedArg::key_value) value can't be parsed:
+    /// ``invalid value `{0}`: {1}``, where `{1}` is the parser's message
+    fn key_value_invalid_value(&self) -> &str {
+        "invalid value `{0}`: {1}"
+    }
+
+    /// Element of a delimited list can't be parsed: `couldn't parse element {0} ({1}) of {2}: {3}`
+    ///
+    /// `{0}` is the element number starting from 1, `{1}` is the element, `{2}` is the whole value
+    /// and `{3}` is the parser's message
+    fn parse_element_failed(&self) -> &str {
+        "couldn't parse element {0} ({1}) of {2}: {3}"
+    }
+
+    /// Same as [`parse_element_failed`](Messages::parse_element_failed) for an unnamed value:
+    /// `couldn't parse element {0} ({1}): {3}`
+    fn parse_element_failed_unnamed(&self) -> &str {
+        "couldn't parse element {0} ({1}): {3}"
+    }
+
+    /// [`guard`](crate::Parser::guard) rejects a value: `{0}: {1}`, where `{1}` is the guard's
+    /// message
+    fn check_failed(&self) -> &str {
+        "{0}: {1}"
+    }
+
+    /// Same as [`check_failed`](Messages::check_failed) for an unnamed value: `check failed: {1}`
+    fn check_failed_unnamed(&self) -> &str {
+        "check failed: {1}"
+    }
+
+    /// Argument is missing its value: `{0} requires an argument {1}`
+    fn missing_value(&self) -> &str {
+        "{0} requires an argument {1}"
+    }
+
+    /// Argument is followed by a flag instead of a value:
+    /// `{0} requires an argument {1}, got a flag {2}, try {3} to use it as an argument`
+    fn missing_value_got_flag(&self) -> &str {
+        "{0} requires an argument {1}, got a flag {2}, try {3} to use it as an argument"
+    }
+
+    /// Short name is both a flag and an argument: `{0} supports {1} as both an option and an
+    /// option-argument, try to split {2} into individual options ({3}) or use {4} syntax to
+    /// disambiguate`, where `{0}` is the application name
+    fn ambiguous_short(&self) -> &str {
+        "{0} supports {1} as both an option and an option-argument, try to split {2} into individual options ({3}) or use {4} syntax to disambiguate"
+    }
+
+    /// Same as [`ambiguous_short`](Messages::ambiguous_short) when the application name is not
+    /// known: `app supports {1} as ...`
+    fn ambiguous_short_unnamed(&self) -> &str {
+        "app supports {1} as both an option and an option-argument, try to split {2} into individual options ({3}) or use {4} syntax to disambiguate"
+    }
+
+    /// [Abbreviation](crate::OptionParser::allow_abbreviations) matches several names:
+    /// `{0} is ambiguous, it can be {1}`
+    fn ambiguous_prefix(&self) -> &str {
+        "{0} is ambiguous, it can be {1}"
+    }
+
+    /// Separator between items of a list: `, `
+    fn list_separator(&self) -> &str {
+        ", "
+    }
+
+    /// Separator before the last of several alternatives: ` or `
+    fn list_or(&self) -> &str {
+        " or "
+    }
+
+    /// Unknown flag: `no such flag: {0}, did you mean {1}?`
+    fn no_such_flag(&self) -> &str {
+        "no such flag: {0}, did you mean {1}?"
+    }
+
+    /// Unknown value of an argument: `no such argument value: {0}, did you mean {1}?`
+    fn no_such_argument_value(&self) -> &str {
+        "no such argument value: {0}, did you mean {1}?"
+    }
+
+    /// Unknown command or positional item: `no such command or positional: {0}, did you mean {1}?`
+    fn no_such_command(&self) -> &str {
+        "no such command or positional: {0}, did you mean {1}?"
+    }
+
+    /// Long name is used with a single dash: `no such flag: {0} (with one dash), did you mean {1}?`
+    fn single_dash(&self) -> &str {
+        "no such flag: {0} (with one dash), did you mean {1}?"
+    }
+
+    /// Short name is used with two dashes:
+    /// `no such flag: {0} (with two dashes), did you mean {1}?`
+    fn double_dash(&self) -> &str {
+        "no such flag: {0} (with two dashes), did you mean {1}?"
+    }
+
+    /// Flag belongs to a subcommand:
+    /// `flag {0} is not valid in this context, did you mean to pass it to command {1}?`
+    fn flag_of_command(&self) -> &str {
+        "flag {0} is not valid in this context, did you mean to pass it to command {1}?"
+    }
+
+    /// Command belongs to a subcommand:
+    /// `subcommand {0} is not valid in this context, did you mean to pass it to command {1}?`
+    fn command_of_command(&self) -> &str {
+        "subcommand {0} is not valid in this context, did you mean to pass it to command {1}?"
+    }
+
+    /// Required item is missing, something else is present:
+    /// `expected {0}, got {1}. Pass {2} for usage information`, where `{2}` is `--help`
+    fn expected_got(&self) -> &str {
+        "expected {0}, got {1}. Pass {2} for usage information"
+    }
+
+    /// Required item is missing: `expected {0}, pass {2} for usage information`
+    fn expected(&self) -> &str {
+        "expected {0}, pass {2} for usage information"
+    }
+
+    /// Nothing is expected, used in [`expected_got`](Messages::expected_got): `no arguments`
+    fn no_arguments(&self) -> &str {
+        "no arguments"
+    }
+
+    /// One of two items is expected: `{0} or {1}`
+    fn one_of_two(&self) -> &str {
+        "{0} or {1}"
+    }
+
+    /// One of many items is expected, only first two are listed: `{0}, {1}, or more`
+    fn one_of_many(&self) -> &str {
+        "{0}, {1}, or more"
+    }
+
+    /// Two items can't be used together: `{0} cannot be used at the same time as {1}`
+    fn conflict(&self) -> &str {
+        "{0} cannot be used at the same time as {1}"
+    }
+
+    /// Value is below the [`range`](crate::Parser::range):
+    /// `{0} is out of range, expected at least {1}`
+    fn at_least(&self) -> &str {
+        "{0} is out of range, expected at least {1}"
+    }
+
+    /// Value is below the [`range`](crate::Parser::range):
+    /// `{0} is out of range, expected greater than {1}`
+    fn greater_than(&self) -> &str {
+        "{0} is out of range, expected greater than {1}"
+    }
+
+    /// Value is above the [`range`](crate::Parser::range):
+    /// `{0} is out of range, expected at most {1}`
+    fn at_most(&self) -> &str {
+        "{0} is out of range, expected at most {1}"
+    }
+
+    /// Value is above the [`range`](crate::Parser::range):
+    /// `{0} is out of range, expected less than {1}`
+    fn less_than(&self) -> &str {
+        "{0} is out of range, expected less than {1}"
+    }
+
+    /// Value is not one of [`possible_values`](crate::parsers::ParseArgument::possible_values):
+    /// `{0} is not a valid value, expected one of {1}`
+    fn not_possible(&self) -> &str {
+        "{0} is not a valid value, expected one of {1}"
+    }
+
+    /// Value is close to one of the possible values: `{0} is not a valid value, did you mean {1}?`
+    fn not_possible_suggest(&self) -> &str {
+        "{0} is not a valid value, did you mean {1}?"
+    }
+
+    /// Item must be present exactly `{1}`: `expected {0} exactly {1}`
+    fn count_exactly(&self) -> &str {
+        "expected {0} exactly {1}"
+    }
+
+    /// Item must be present at most `{1}`: `expected {0} at most {1}`
+    fn count_at_most(&self) -> &str {
+        "expected {0} at most {1}"
+    }
+
+    /// Item must be present at least `{1}`: `expected {0} at least {1}`
+    fn count_at_least(&self) -> &str {
+        "expected {0} at least {1}"
+    }
+
+    /// Count of one for [`count_exactly`](Messages::count_exactly) and others: `once`
+    fn once(&self) -> &str {
+        "once"
+    }
+
+    /// Count of several for [`count_exactly`](Messages::count_exactly) and others: `{0} times`
+    fn times(&self) -> &str {
+        "{0} times"
+    }
+
+    /// Item is present too few times: `{0}, got {1}`, where `{0}` is the expected count
+    fn too_few(&self) -> &str {
+        "{0}, got {1}"
+    }
+
+    /// Item is present too many times: `{0}, {1} is one too many`, where `{0}` is the expected
+    /// count
+    fn too_many(&self) -> &str {
+        "{0}, {1} is one too many"
+    }
+
+    /// [`requires`](crate::parsers::NamedArg::requires) is not satisfied:
+    /// `{0} requires {1} to be present`
+    fn requires(&self) -> &str {
+        "{0} requires {1} to be present"
+    }
+
+    /// Item is used more than once: `argument {0} cannot be used multiple times in this context`
+    fn only_once(&self) -> &str {
+        "argument {0} cannot be used multiple times in this context"
+    }
+
+    /// [`key_value`](crate::parsers::NamedArg::key_value) key is set twice:
+    /// `key {0} is set more than once: {1} and {2}`
+    fn duplicate_key(&self) -> &str {
+        "key {0} is set more than once: {1} and {2}"
+    }
+
+    /// [Response file](crate::OptionParser::expand_response_files) can't be read:
+    /// `couldn't read response file {0}: {1}`
+    fn response_file_read(&self) -> &str {
+        "couldn't read response file {0}: {1}"
+    }
+
+    /// [Response file](crate::OptionParser::expand_response_files) includes itself:
+    /// `response file {0} includes itself`
+    fn response_file_cycle(&self) -> &str {
+        "response file {0} includes itself"
+    }
+
+    /// Response file includes itself through other response files:
+    /// `response file {0} includes itself through {1}`, {1} lists the files in between
+    fn response_file_cycle_via(&self) -> &str {
+        "response file {0} includes itself through {1}"
+    }
+
+    /// Failure inside of a response file: `{0}:{1}: {2}`, where `{0}` is the file, `{1}` is the
+    /// line and `{2}` is the message
+    fn response_file_location(&self) -> &str {
+        "{0}:{1}: {2}"
+    }
+
+    /// Command line passed to [`from_command_line`](crate::Args::from_command_line) can't be
+    /// split into words: `{0} at character {1}`, where `{0}` is the problem
+    fn command_line_error(&self) -> &str {
+        "{0} at character {1}"
+    }
+
+    /// Quoted string in a response file is not closed: `unterminated quote`
+    fn unterminated_quote(&self) -> &str {
+        "unterminated quote"
+    }
+
+    /// Response file line ends with a backslash:
+    /// `backslash at the end of the line escapes nothing`
+    fn dangling_escape(&self) -> &str {
+        "backslash at the end of the line escapes nothing"
+    }
+
+    /// Required item is hidden with [`hide`](crate::Parser::hide):
+    /// `parser requires an extra flag, argument or parameter, but its name is hidden by the author`
+    fn hidden_item(&self) -> &str {
+        "parser requires an extra flag, argument or parameter, but its name is hidden by the author"
+    }
+    // }}}
+}
+
+/// Default message catalog
+#[derive(Debug, Clone, Copy, Default)]
+pub struct English;
+
+impl Messages for English {}
+
+/// Message catalog in use, see [`OptionParser::messages`](crate::OptionParser::messages)
+#[derive(Clone, Copy)]
+pub(crate) struct Catalog(pub(crate) &'static dyn Messages);
+
+impl Default for Catalog {
+    fn default() -> Self {
+        Self(&English)
+    }
+}
+
+impl std::fmt::Debug for Catalog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Catalog")
+    }
+}
+
+impl std::ops::Deref for Catalog {
+    type Target = dyn Messages;
+
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+
+/// Append a template to `doc`, placeholders `{0}`, `{1}`, ... are written by `arg`
+pub(crate) fn fill(doc: &mut Doc, template: &str, mut arg: impl FnMut(&mut Doc, usize)) {
+    split_template(template, |piece| match piece {
+        Ok(text) => doc.text(text),
+        Err(n) => arg(doc, n),
+    });
+}
+
+/// Same as [`fill`] for messages that are kept as plain text, `args` replace placeholders
+pub(crate) fn fill_text(template: &str, args: &[&str]) -> String {
+    let mut res = String::new();
+    split_template(template, |piece| match piece {
+        Ok(text) => res.push_str(text),
+        Err(n) => res.push_str(args.get(n).copied().unwrap_or_default()),
+    });
+    res
+}
+
+/// Split a template into text, passed as `Ok`, and placeholder numbers, passed as `Err`
+fn split_template<'a>(template: &'a str, mut piece: impl FnMut(Result<&'a str, usize>)) {
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let (prefix, tail) = rest.split_at(start);
+        let placeholder = tail[1..]
+            .find('}')
+            .and_then(|end| Some((tail[1..=end].parse::<usize>().ok()?, end + 2)));
+        let text = match placeholder {
+            Some((n, len)) => {
+                if !prefix.is_empty() {
+                    piece(Ok(prefix));
+                }
+                piece(Err(n));
+                rest = &tail[len..];
+                continue;
+            }
+            None => &rest[..=start],
+        };
+        piece(Ok(text));
+        rest = &tail[1..];
+    }
+    if !rest.is_empty() {
+        piece(Ok(rest));
+    }
+}
@@ -1,4 +1,10 @@
-use crate::{buffer::Doc, item::Item};
+use std::{borrow::Cow, ops::Bound};
+
+use crate::{
+    args::LongNames,
+    buffer::Doc,
+    item::{Item, NamePrefix},
+};
 
 #[doc(hidden)]
 #[derive(Clone, Debug)]
@@ -17,14 +23,21 @@ pub enum Meta {
     Item(Box<Item>),
     /// Accepts multiple arguments
     Many(Box<Meta>),
+    /// Accepts multiple arguments, at least and at most this many
+    Bounded(Box<Meta>, usize, Option<usize>),
     /// Arguments form a subsection with buffer being it's header
     ///
     /// whole set of arguments go into the same section as the first one
     Subsection(Box<Meta>, Box<Doc>),
     /// Buffer is rendered after
     Suffix(Box<Meta>, Box<Doc>),
+    /// Note about the item, text is rendered with the message catalog
+    Note(Box<Meta>, Note),
     /// This item is not rendered in the help message
     Skip,
+    /// Item is accepted on a command line but is not rendered in the help message, names
+    /// inside are still used to split the command line
+    Hidden(Box<Meta>),
     /// TODO make it Option<Box<Doc>>
     CustomUsage(Box<Meta>, Box<Doc>),
     /// this meta must be prefixed with -- in unsage group
@@ -38,6 +51,17 @@ impl Default for Meta {
     }
 }
 
+/// Notes about items with text rendered at help time, see [`Meta::Note`]
+#[doc(hidden)]
+#[derive(Clone, Debug)]
+pub enum Note {
+    /// Value used when the item is absent, see
+    /// [`display_fallback`](crate::parsers::ParseFallback::display_fallback)
+    Default(String),
+    /// Bounds a value must be within, see [`range`](crate::Parser::range)
+    Range(Bound<String>, Bound<String>),
+}
+
 // Meta::Strict should bubble up to one of 3 places:
 // - top level
 // - one of "and" elements
@@ -122,11 +146,13 @@ impl Meta {
                 Meta::Optional(m)
                 | Meta::Required(m)
                 | Meta::Many(m)
+                | Meta::Bounded(m, _, _)
                 | Meta::CustomUsage(m, _)
                 | Meta::Subsection(m, _)
                 | Meta::Strict(m)
-                | Meta::Suffix(m, _) => go(m, is_pos, v),
-                Meta::Skip => {}
+                | Meta::Suffix(m, _)
+                | Meta::Note(m, _) => go(m, is_pos, v),
+                Meta::Skip | Meta::Hidden(_) => {}
             }
         }
         let mut is_pos = false;
@@ -158,14 +184,16 @@ impl Meta {
         match meta {
             Meta::And(xs) => xs.first().and_then(Self::first_item),
             Meta::Item(item) => Some(item),
-            Meta::Skip | Meta::Or(_) => None,
+            Meta::Skip | Meta::Hidden(_) | Meta::Or(_) => None,
             Meta::Optional(x)
             | Meta::Strict(x)
             | Meta::Required(x)
             | Meta::Adjacent(x)
             | Meta::Many(x)
+            | Meta::Bounded(x, _, _)
             | Meta::Subsection(x, _)
             | Meta::Suffix(x, _)
+            | Meta::Note(x, _)
             | Meta::CustomUsage(x, _) => Self::first_item(x),
         }
     }
@@ -260,13 +288,13 @@ impl Meta {
                     *self = std::mem::take(m);
                 }
             }
-            Meta::Many(m) => {
+            Meta::Many(m) | Meta::Bounded(m, _, _) => {
                 m.normalize(for_usage, norm);
                 if matches!(**m, Meta::Skip) {
                     *self = Meta::Skip;
                 }
             }
-            Meta::Adjacent(m) | Meta::Subsection(m, _) | Meta::Suffix(m, _) => {
+            Meta::Adjacent(m) | Meta::Subsection(m, _) | Meta::Suffix(m, _) | Meta::Note(m, _) => {
                 m.normalize(for_usage, norm);
                 *self = std::mem::take(m);
             }
@@ -274,6 +302,7 @@ impl Meta {
             Meta::Skip => {
                 // nothing to do with items and skip just bubbles upwards
             }
+            Meta::Hidden(_) => *self = Meta::Skip,
             Meta::CustomUsage(m, u) => {
                 m.normalize(for_usage, norm);
                 // strip CustomUsage if we are not in usage so writer can simply render it
@@ -342,10 +371,157 @@ impl Meta {
             | Meta::Adjacent(m)
             | Meta::Subsection(m, _)
             | Meta::Suffix(m, _)
-            | Meta::Many(m) => {
+            | Meta::Note(m, _)
+            | Meta::Many(m)
+            | Meta::Bounded(m, _, _)
+            | Meta::Hidden(m) => {
                 m.collect_shorts(flags, args);
             }
             Meta::Skip | Meta::Strict(_) => {}
         }
     }
+
+    /// collect long names of this command level for abbreviations, subcommands get their own
+    /// tables
+    pub(crate) fn collect_long_names<'a>(&'a self, level: &mut LongNames<'a>) {
+        match self {
+            Meta::And(xs) | Meta::Or(xs) => {
+                for x in xs {
+                    x.collect_long_names(level);
+                }
+            }
+            Meta::Item(m) => match &**m {
+                Item::Any { .. } | Item::Positional { .. } => {}
+                Item::Command {
+                    name,
+                    short,
+                    meta,
+                    aliases,
+                    ..
+                } => {
+                    let mut names = std::iter::once(name)
+                        .chain(aliases)
+                        .map(|name| Cow::Borrowed(name.as_ref()))
+                        .collect::<Vec<_>>();
+                    names.extend(short.map(|s| Cow::Owned(s.to_string())));
+                    let mut inner = LongNames::default();
+                    meta.collect_long_names(&mut inner);
+                    level.commands.push((names, inner));
+                }
+                Item::Flag { name, longs, .. } => {
+                    if let Some((NamePrefix::DoubleDash, owner)) = name.as_long() {
+                        level
+                            .names
+                            .extend(longs.iter().map(|l| (l.as_ref(), owner)));
+                    }
+                }
+                Item::Argument { name, longs, .. } => {
+                    if let Some((NamePrefix::DoubleDash, owner)) = name.as_long() {
+                        level
+                            .names
+                            .extend(longs.iter().map(|l| (l.as_ref(), owner)));
+                        level.arguments.extend(longs.iter().map(AsRef::as_ref));
+                    }
+                }
+            },
+            Meta::CustomUsage(m, _)
+            | Meta::Required(m)
+            | Meta::Optional(m)
+            | Meta::Adjacent(m)
+            | Meta::Subsection(m, _)
+            | Meta::Suffix(m, _)
+            | Meta::Note(m, _)
+            | Meta::Many(m)
+            | Meta::Bounded(m, _, _) => {
+                m.collect_long_names(level);
+            }
+            // hidden names are not abbreviated but should still win over prefixes
+            Meta::Hidden(m) => {
+                let mut hidden = LongNames::default();
+                m.collect_long_names(&mut hidden);
+                level
+                    .exact
+                    .extend(hidden.names.into_iter().map(|(name, _)| name));
+                level.exact.append(&mut hidden.exact);
+                level.arguments.append(&mut hidden.arguments);
+                level.commands.append(&mut hidden.commands);
+            }
+            Meta::Skip | Meta::Strict(_) => {}
+        }
+    }
+
+    /// collect names mentioned in [`conflicts_with`](crate::NamedArg::conflicts_with) and
+    /// [`requires`](crate::NamedArg::requires) relations
+    pub(crate) fn collect_related<'a>(&'a self, related: &mut Vec<&'a str>) {
+        match self {
+            Meta::And(xs) | Meta::Or(xs) => {
+                for x in xs {
+                    x.collect_related(related);
+                }
+            }
+            Meta::Item(m) => match &**m {
+                Item::Any { .. } | Item::Positional { .. } => {}
+                Item::Command { meta, .. } => {
+                    meta.collect_related(related);
+                }
+                Item::Flag { relations, .. } | Item::Argument { relations, .. } => {
+                    related.extend(
+                        relations
+                            .conflicts
+                            .iter()
+                            .chain(&relations.requires)
+                            .map(AsRef::as_ref),
+                    );
+                }
+            },
+            Meta::CustomUsage(m, _)
+            | Meta::Required(m)
+            | Meta::Optional(m)
+            | Meta::Adjacent(m)
+            | Meta::Subsection(m, _)
+            | Meta::Suffix(m, _)
+            | Meta::Note(m, _)
+            | Meta::Many(m)
+            | Meta::Bounded(m, _, _)
+            | Meta::Hidden(m) => {
+                m.collect_related(related);
+            }
+            Meta::Skip | Meta::Strict(_) => {}
+        }
+    }
+
+    /// collect long names together with their prefixes for abbreviation and disambiguation
+    pub(crate) fn collect_longs<'a>(&'a self, longs: &mut Vec<(NamePrefix, &'a str)>) {
+        match self {
+            Meta::And(xs) | Meta::Or(xs) => {
+                for x in xs {
+                    x.collect_longs(longs);
+                }
+            }
+            Meta::Item(m) => match &**m {
+                Item::Any { .. } | Item::Positional { .. } => {}
+                Item::Command { meta, .. } => {
+                    meta.collect_longs(longs);
+                }
+                Item::Flag { name, longs: l, .. } | Item::Argument { name, longs: l, .. } => {
+                    if let Some((prefix, _)) = name.as_long() {
+                        longs.extend(l.iter().map(|l| (prefix, l.as_ref())));
+                    }
+                }
+            },
+            Meta::CustomUsage(m, _)
+            | Meta::Required(m)
+            | Meta::Optional(m)
+            | Meta::Adjacent(m)
+            | Meta::Subsection(m, _)
+            | Meta::Suffix(m, _)
+            | Meta::Note(m, _)
+            | Meta::Many(m)
+            | Meta::Bounded(m, _, _)
+            | Meta::Hidden(m) => {
+                m.collect_longs(longs);
+            }
+            Meta::Skip | Meta::Strict(_) => {}
+        }
+    }
 }
@@ -1,20 +1,33 @@
-use std::collections::BTreeSet;
+use std::{borrow::Cow, collections::BTreeSet, ops::Bound};
 
 use crate::{
     buffer::{Block, Doc, Style, Token},
     info::Info,
-    item::{Item, ShortLong},
+    item::{Item, Relations, ShortLong},
+    messages::{fill, Messages},
+    meta::Note,
     Meta,
 };
 
 #[doc(hidden)]
+#[derive(Debug, Clone)]
+pub struct Metavar(pub(crate) Cow<'static, str>);
+
+/// Bounds of a [`range`](crate::Parser::range) check
+type ValueRange<'a> = (&'a Bound<String>, &'a Bound<String>);
+
+/// Line rendered after a group of items
 #[derive(Debug, Clone, Copy)]
-pub struct Metavar(pub(crate) &'static str);
+pub(crate) enum Suffix<'a> {
+    Doc(&'a Doc),
+    /// fallback value: `[default: 4]`
+    Default(&'a str),
+}
 
 #[derive(Debug, Clone, Copy)]
 pub(crate) enum HelpItem<'a> {
     DecorSuffix {
-        help: &'a Doc,
+        help: Suffix<'a>,
         ty: HiTy,
     },
     GroupStart {
@@ -30,27 +43,38 @@ pub(crate) enum HelpItem<'a> {
         help: Option<&'a Doc>,
     },
     Positional {
-        metavar: Metavar,
+        metavar: &'a Metavar,
         help: Option<&'a Doc>,
+        possible: &'a [Cow<'static, str>],
+        range: Option<ValueRange<'a>>,
     },
     Command {
-        name: &'static str,
+        name: &'a str,
         short: Option<char>,
         help: Option<&'a Doc>,
         meta: &'a Meta,
         #[cfg(feature = "docgen")]
         info: &'a Info,
+        aliases: &'a [Cow<'static, str>],
     },
     Flag {
-        name: ShortLong,
-        env: Option<&'static str>,
+        name: &'a ShortLong,
+        env: Option<&'a str>,
         help: Option<&'a Doc>,
+        negatable: bool,
+        relations: &'a Relations,
+        aliases: &'a [ShortLong],
     },
     Argument {
-        name: ShortLong,
-        metavar: Metavar,
-        env: Option<&'static str>,
+        name: &'a ShortLong,
+        metavar: &'a Metavar,
+        delimiter: Option<char>,
+        env: Option<&'a str>,
         help: Option<&'a Doc>,
+        relations: &'a Relations,
+        possible: &'a [Cow<'static, str>],
+        range: Option<ValueRange<'a>>,
+        aliases: &'a [ShortLong],
     },
     AnywhereStart {
         inner: &'a Meta,
@@ -184,12 +208,14 @@ impl Meta {
             | Meta::Required(x)
             | Meta::Adjacent(x)
             | Meta::Many(x)
+            | Meta::Bounded(x, _, _)
             | Meta::Subsection(x, _)
             | Meta::Suffix(x, _)
+            | Meta::Note(x, _)
             | Meta::Strict(x)
             | Meta::CustomUsage(x, _) => x.peek_front_ty(),
             Meta::Item(i) => Some(HiTy::from(i.as_ref())),
-            Meta::Skip => None,
+            Meta::Skip | Meta::Hidden(_) => None,
         }
     }
 }
@@ -218,9 +244,11 @@ impl<'a> HelpItems<'a> {
                 | Meta::Required(x)
                 | Meta::Optional(x)
                 | Meta::Many(x)
+                | Meta::Bounded(x, _, _)
                 | Meta::Strict(x) => go(hi, x, no_ss),
                 Meta::Item(item) => {
-                    if matches!(item.as_ref(), Item::Positional { help: None, .. }) {
+                    if matches!(item.as_ref(), Item::Positional { help: None, possible, .. } if possible.is_empty())
+                    {
                         return;
                     }
                     hi.items.push(HelpItem::from(item.as_ref()));
@@ -239,10 +267,29 @@ impl<'a> HelpItems<'a> {
                 Meta::Suffix(m, help) => {
                     if let Some(ty) = m.peek_front_ty() {
                         go(hi, m, no_ss);
+                        let help = Suffix::Doc(help);
+                        hi.items.push(HelpItem::DecorSuffix { help, ty });
+                    }
+                }
+                Meta::Note(m, Note::Default(value)) => {
+                    if let Some(ty) = m.peek_front_ty() {
+                        go(hi, m, no_ss);
+                        let help = Suffix::Default(value);
                         hi.items.push(HelpItem::DecorSuffix { help, ty });
                     }
                 }
-                Meta::Skip => (),
+                Meta::Note(m, Note::Range(start, end)) => {
+                    let first = hi.items.len();
+                    go(hi, m, no_ss);
+                    for item in &mut hi.items[first..] {
+                        if let HelpItem::Positional { range, .. }
+                        | HelpItem::Argument { range, .. } = item
+                        {
+                            *range = Some((start, end));
+                        }
+                    }
+                }
+                Meta::Skip | Meta::Hidden(_) => (),
             }
         }
         go(self, meta, false);
@@ -280,9 +327,15 @@ impl<'a> From<&'a Item> for HelpItem<'a> {
     // {{{
     fn from(item: &'a Item) -> Self {
         match item {
-            Item::Positional { metavar, help } => Self::Positional {
-                metavar: *metavar,
+            Item::Positional {
+                metavar,
+                help,
+                possible,
+            } => Self::Positional {
+                metavar,
                 help: help.as_ref(),
+                possible,
+                range: None,
             },
             Item::Command {
                 name,
@@ -293,6 +346,7 @@ impl<'a> From<&'a Item> for HelpItem<'a> {
                 info,
                 #[cfg(not(feature = "docgen"))]
                     info: _,
+                aliases,
             } => Self::Command {
                 name,
                 short: *short,
@@ -300,28 +354,46 @@ impl<'a> From<&'a Item> for HelpItem<'a> {
                 meta,
                 #[cfg(feature = "docgen")]
                 info,
+                aliases,
             },
             Item::Flag {
                 name,
                 env,
                 help,
                 shorts: _,
+                longs: _,
+                negatable,
+                relations,
+                aliases,
             } => Self::Flag {
-                name: *name,
-                env: *env,
+                name,
+                env: env.as_deref(),
                 help: help.as_ref(),
+                negatable: *negatable,
+                relations,
+                aliases,
             },
             Item::Argument {
                 name,
                 metavar,
+                delimiter,
                 env,
                 help,
                 shorts: _,
+                longs: _,
+                relations,
+                possible,
+                aliases,
             } => Self::Argument {
-                name: *name,
-                metavar: *metavar,
-                env: *env,
+                name,
+                metavar,
+                delimiter: *delimiter,
+                env: env.as_deref(),
                 help: help.as_ref(),
+                relations,
+                possible,
+                range: None,
+                aliases,
             },
             Item::Any {
                 metavar,
@@ -338,23 +410,32 @@ impl<'a> From<&'a Item> for HelpItem<'a> {
 
 impl Doc {
     #[inline(never)]
-    pub(crate) fn metavar(&mut self, metavar: Metavar) {
+    pub(crate) fn metavar(&mut self, metavar: &Metavar) {
         if metavar
             .0
             .chars()
             .all(|c| c.is_uppercase() || c.is_ascii_digit() || c == '-' || c == '_')
         {
-            self.write_str(metavar.0, Style::Metavar);
+            self.write_str(&metavar.0, Style::Metavar);
         } else {
             self.write_char('<', Style::Metavar);
-            self.write_str(metavar.0, Style::Metavar);
+            self.write_str(&metavar.0, Style::Metavar);
             self.write_char('>', Style::Metavar);
         }
     }
+
+    /// Metavar for an element of a delimited list is followed by `,...`
+    pub(crate) fn delimited_metavar(&mut self, metavar: &Metavar, delimiter: Option<char>) {
+        self.metavar(metavar);
+        if let Some(d) = delimiter {
+            self.write_char(d, Style::Metavar);
+            self.write_str("...", Style::Metavar);
+        }
+    }
 }
 
 #[allow(clippy::too_many_lines)] // lines are _very_ boring
-fn write_help_item(buf: &mut Doc, item: &HelpItem, include_env: bool) {
+fn write_help_item(buf: &mut Doc, item: &HelpItem, include_env: bool, m: &dyn Messages) {
     match item {
         HelpItem::GroupStart { help, .. } => {
             buf.token(Token::BlockStart(Block::Block));
@@ -371,7 +452,12 @@ fn write_help_item(buf: &mut Doc, item: &HelpItem, include_env: bool) {
             buf.token(Token::BlockStart(Block::ItemTerm));
             buf.token(Token::BlockEnd(Block::ItemTerm));
             buf.token(Token::BlockStart(Block::ItemBody));
-            buf.doc(help);
+            match help {
+                Suffix::Doc(help) => buf.doc(help),
+                Suffix::Default(value) => {
+                    buf.text(&format!("[{}: {}]", m.note_default(), value));
+                }
+            }
             buf.token(Token::BlockEnd(Block::ItemBody));
         }
         HelpItem::Any {
@@ -388,15 +474,17 @@ fn write_help_item(buf: &mut Doc, item: &HelpItem, include_env: bool) {
                 buf.token(Token::BlockEnd(Block::ItemBody));
             }
         }
-        HelpItem::Positional { metavar, help } => {
+        HelpItem::Positional {
+            metavar,
+            help,
+            possible,
+            range,
+        } => {
             buf.token(Token::BlockStart(Block::ItemTerm));
-            buf.metavar(*metavar);
+            buf.metavar(metavar);
             buf.token(Token::BlockEnd(Block::ItemTerm));
-            if let Some(help) = help {
-                buf.token(Token::BlockStart(Block::ItemBody));
-                buf.doc(help);
-                buf.token(Token::BlockEnd(Block::ItemBody));
-            }
+            let notes = [(m.note_possible_values(), *possible)];
+            write_help_notes(buf, *help, &notes, *range);
         }
         HelpItem::Command {
             name,
@@ -405,6 +493,7 @@ fn write_help_item(buf: &mut Doc, item: &HelpItem, include_env: bool) {
             meta: _,
             #[cfg(feature = "docgen")]
                 info: _,
+            aliases,
         } => {
             buf.token(Token::BlockStart(Block::ItemTerm));
             buf.write_str(name, Style::Literal);
@@ -413,26 +502,34 @@ fn write_help_item(buf: &mut Doc, item: &HelpItem, include_env: bool) {
                 buf.write_char(*short, Style::Literal);
             }
             buf.token(Token::BlockEnd(Block::ItemTerm));
-            if let Some(help) = help {
-                buf.token(Token::BlockStart(Block::ItemBody));
-                buf.doc(help);
-                buf.token(Token::BlockEnd(Block::ItemBody));
-            }
+            write_help_notes(buf, *help, &[(m.note_aliases(), aliases)], None);
         }
-        HelpItem::Flag { name, env, help } => {
+        HelpItem::Flag {
+            name,
+            env,
+            help,
+            negatable,
+            relations,
+            aliases,
+        } => {
             buf.token(Token::BlockStart(Block::ItemTerm));
-            write_shortlong(buf, *name);
+            write_shortlong(buf, name, *negatable);
             buf.token(Token::BlockEnd(Block::ItemTerm));
-            if let Some(help) = help {
-                buf.token(Token::BlockStart(Block::ItemBody));
-                buf.doc(help);
-                buf.token(Token::BlockEnd(Block::ItemBody));
-            }
+            write_help_notes(
+                buf,
+                *help,
+                &[
+                    (m.note_aliases(), &spelled(aliases)),
+                    (m.note_conflicts(), &relations.conflicts),
+                    (m.note_requires(), &relations.requires),
+                ],
+                None,
+            );
             if let Some(env) = env {
                 let val = if std::env::var_os(env).is_some() {
-                    ": set"
+                    m.env_set()
                 } else {
-                    ": not set"
+                    m.env_not_set()
                 };
                 if help.is_some() {
                     buf.token(Token::BlockStart(Block::ItemTerm));
@@ -440,10 +537,9 @@ fn write_help_item(buf: &mut Doc, item: &HelpItem, include_env: bool) {
                 }
                 buf.token(Token::BlockStart(Block::ItemBody));
                 if include_env {
-                    buf.write_str(&format!("[env:{}{}]", env, val), Style::Text);
+                    buf.write_str(&format!("[env:{}: {}]", env, val), Style::Text);
                 } else {
-                    buf.text("Uses environment variable ");
-                    buf.literal(env);
+                    fill(buf, m.env_var(), |buf, _| buf.literal(env));
                 }
                 buf.token(Token::BlockEnd(Block::ItemBody));
             }
@@ -451,25 +547,35 @@ fn write_help_item(buf: &mut Doc, item: &HelpItem, include_env: bool) {
         HelpItem::Argument {
             name,
             metavar,
+            delimiter,
             env,
             help,
+            relations,
+            possible,
+            range,
+            aliases,
         } => {
             buf.token(Token::BlockStart(Block::ItemTerm));
-            write_shortlong(buf, *name);
+            write_shortlong(buf, name, false);
             buf.write_str("=", Style::Text);
-            buf.metavar(*metavar);
+            buf.delimited_metavar(metavar, *delimiter);
             buf.token(Token::BlockEnd(Block::ItemTerm));
-
-            if let Some(help) = help {
-                buf.token(Token::BlockStart(Block::ItemBody));
-                buf.doc(help);
-                buf.token(Token::BlockEnd(Block::ItemBody));
-            }
+            write_help_notes(
+                buf,
+                *help,
+                &[
+                    (m.note_aliases(), &spelled(aliases)),
+                    (m.note_possible_values(), possible),
+                    (m.note_conflicts(), &relations.conflicts),
+                    (m.note_requires(), &relations.requires),
+                ],
+                *range,
+            );
 
             if let Some(env) = env {
                 let val = match std::env::var_os(env) {
                     Some(s) => std::borrow::Cow::from(format!(" = {:?}", s.to_string_lossy())),
-                    None => std::borrow::Cow::Borrowed(": N/A"),
+                    None => std::borrow::Cow::from(format!(": {}", m.env_no_value())),
                 };
 
                 if help.is_some() {
@@ -481,8 +587,7 @@ fn write_help_item(buf: &mut Doc, item: &HelpItem, include_env: bool) {
                 if include_env {
                     buf.write_str(&format!("[env:{}{}]", env, val), Style::Text);
                 } else {
-                    buf.text("Uses environment variable ");
-                    buf.literal(env);
+                    fill(buf, m.env_var(), |buf, _| buf.literal(env));
                 }
 
                 buf.token(Token::BlockEnd(Block::ItemBody));
@@ -500,21 +605,84 @@ fn write_help_item(buf: &mut Doc, item: &HelpItem, include_env: bool) {
     }
 }
 
-fn write_shortlong(buf: &mut Doc, name: ShortLong) {
+fn spelled(names: &[ShortLong]) -> Vec<Cow<'static, str>> {
+    names.iter().map(|name| Cow::from(name.spelled())).collect()
+}
+
+/// Help message followed by notes about aliases, possible values, relations with other items
+/// and the range: `[aliases: --colour] [possible values: fast, slow] [conflicts with: --yaml]
+/// [requires: --output] [1..=64]`
+fn write_help_notes(
+    buf: &mut Doc,
+    help: Option<&Doc>,
+    notes: &[(&str, &[Cow<'static, str>])],
+    range: Option<ValueRange>,
+) {
+    let range = range.filter(|r| !matches!(r, (Bound::Unbounded, Bound::Unbounded)));
+    if help.is_none() && range.is_none() && notes.iter().all(|(_, names)| names.is_empty()) {
+        return;
+    }
+    buf.token(Token::BlockStart(Block::ItemBody));
+    let mut separate = false;
+    if let Some(help) = help {
+        buf.doc(help);
+        separate = true;
+    }
+    for (label, names) in notes {
+        if names.is_empty() {
+            continue;
+        }
+        if separate {
+            buf.text(" ");
+        }
+        separate = true;
+        buf.text(&format!("[{}: ", label));
+        for (ix, name) in names.iter().enumerate() {
+            if ix > 0 {
+                buf.text(", ");
+            }
+            buf.literal(name);
+        }
+        buf.text("]");
+    }
+    if let Some((start, end)) = range {
+        if separate {
+            buf.text(" ");
+        }
+        // bounds are spelled as a Rust range: `1..=64`, `1..`, `..64`, excluded start is `1<..`
+        let start = match start {
+            Bound::Included(v) => v.clone(),
+            Bound::Excluded(v) => format!("{}<", v),
+            Bound::Unbounded => String::new(),
+        };
+        let end = match end {
+            Bound::Included(v) => format!("={}", v),
+            Bound::Excluded(v) => v.clone(),
+            Bound::Unbounded => String::new(),
+        };
+        buf.text(&format!("[{}..{}]", start, end));
+    }
+    buf.token(Token::BlockEnd(Block::ItemBody));
+}
+
+/// `negatable` switches get `[no-]` in front of a long name
+fn write_shortlong(buf: &mut Doc, name: &ShortLong, negatable: bool) {
+    let no = if negatable { "[no-]" } else { "" };
     match name {
         ShortLong::Short(s) => {
             buf.write_char('-', Style::Literal);
-            buf.write_char(s, Style::Literal);
+            buf.write_char(*s, Style::Literal);
         }
-        ShortLong::Long(l) => {
-            buf.write_str("    --", Style::Literal);
+        ShortLong::Long(p, l) => {
+            buf.write_str(&format!("    {}{}", p.as_str(), no), Style::Literal);
             buf.write_str(l, Style::Literal);
         }
-        ShortLong::Both(s, l) => {
+        ShortLong::Both(s, p, l) => {
             buf.write_char('-', Style::Literal);
-            buf.write_char(s, Style::Literal);
+            buf.write_char(*s, Style::Literal);
             buf.write_str(", ", Style::Text);
-            buf.write_str("--", Style::Literal);
+            buf.write_str(p.as_str(), Style::Literal);
+            buf.write_str(no, Style::Literal);
             buf.write_str(l, Style::Literal);
         }
     }
@@ -527,6 +695,7 @@ pub(crate) fn render_help(
     parser_meta: &Meta,
     help_meta: &Meta,
     include_env: bool,
+    messages: &dyn Messages,
 ) -> Doc {
     parser_meta.positional_invariant_check(false);
     let mut buf = Doc::default();
@@ -541,7 +710,7 @@ pub(crate) fn render_help(
     if let Some(usage) = &info.usage {
         buf.doc(usage);
     } else {
-        buf.write_str("Usage", Style::Emphasis);
+        buf.write_str(messages.usage(), Style::Emphasis);
         buf.write_str(": ", Style::Text);
         buf.token(Token::BlockStart(Block::Mono));
         buf.write_path(path);
@@ -560,7 +729,7 @@ pub(crate) fn render_help(
     items.append_meta(parser_meta);
     items.append_meta(help_meta);
 
-    buf.write_help_item_groups(items, include_env);
+    buf.write_help_item_groups(items, include_env, messages);
 
     if let Some(footer) = &info.footer {
         buf.token(Token::BlockStart(Block::Block));
@@ -591,7 +760,7 @@ impl Dedup {
                 self.keep = self.items.insert(format!("{:?} {:?}", metavar, help));
                 self.keep
             }
-            HelpItem::Positional { metavar, help } => {
+            HelpItem::Positional { metavar, help, .. } => {
                 self.keep = self.items.insert(format!("{:?} {:?}", metavar.0, help));
                 self.keep
             }
@@ -620,27 +789,39 @@ impl Dedup {
 
 impl Doc {
     #[inline(never)]
-    pub(crate) fn write_help_item_groups(&mut self, mut items: HelpItems, include_env: bool) {
+    pub(crate) fn write_help_item_groups(
+        &mut self,
+        mut items: HelpItems,
+        include_env: bool,
+        m: &dyn Messages,
+    ) {
         while let Some(range) = items.find_group() {
             let mut dd = Dedup::default();
             for item in items.items.drain(range) {
                 if dd.check(&item) {
-                    write_help_item(self, &item, include_env);
+                    write_help_item(self, &item, include_env, m);
                 }
             }
         }
 
         for (ty, name) in [
-            (HiTy::Positional, "Available positional items:"),
-            (HiTy::Flag, "Available options:"),
-            (HiTy::Command, "Available commands:"),
+            (HiTy::Positional, m.available_positional()),
+            (HiTy::Flag, m.available_options()),
+            (HiTy::Command, m.available_commands()),
         ] {
-            self.write_help_items(&items, ty, name, include_env);
+            self.write_help_items(&items, ty, name, include_env, m);
         }
     }
 
     #[inline(never)]
-    fn write_help_items(&mut self, items: &HelpItems, ty: HiTy, name: &str, include_env: bool) {
+    fn write_help_items(
+        &mut self,
+        items: &HelpItems,
+        ty: HiTy,
+        name: &str,
+        include_env: bool,
+        m: &dyn Messages,
+    ) {
         let mut xs = items.items_of_ty(ty).peekable();
         if xs.peek().is_some() {
             self.token(Token::BlockStart(Block::Block));
@@ -651,7 +832,7 @@ impl Doc {
             let mut dd = Dedup::default();
             for item in xs {
                 if dd.check(item) {
-                    write_help_item(self, item, include_env);
+                    write_help_item(self, item, include_env, m);
                 }
             }
             self.token(Token::BlockEnd(Block::DefinitionList));
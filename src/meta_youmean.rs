@@ -1,25 +1,42 @@
+use std::borrow::Cow;
+
 use crate::{
-    item::ShortLong,
+    args::{split_os_argument, Arg},
+    item::{NamePrefix, ShortLong},
     meta_help::{HelpItem, HelpItems},
     Meta, State,
 };
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 pub(crate) enum Variant {
-    CommandLong(&'static str),
+    CommandLong(String),
     Flag(ShortLong),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) enum Suggestion {
     Variant(Variant),
     /// expected --foo, actual -foo
-    MissingDash(&'static str),
+    MissingDash(String),
     /// expected -f, actual --f
     ExtraDash(char),
     Nested(String, Variant),
 }
 
+impl Suggestion {
+    /// Suggested name as it should be spelled on a command line
+    pub(crate) fn name(&self) -> String {
+        match self {
+            Suggestion::Variant(Variant::CommandLong(name)) | Suggestion::Nested(name, _) => {
+                name.clone()
+            }
+            Suggestion::Variant(Variant::Flag(name)) => name.spelled(),
+            Suggestion::MissingDash(name) => format!("--{}", name),
+            Suggestion::ExtraDash(name) => format!("-{}", name),
+        }
+    }
+}
+
 /// Looks for potential typos
 #[inline(never)]
 pub(crate) fn suggest(args: &State, meta: &Meta) -> Option<(usize, Suggestion)> {
@@ -30,12 +47,20 @@ pub(crate) fn suggest(args: &State, meta: &Meta) -> Option<(usize, Suggestion)>
         return None;
     }
     // don't try to suggest fixes for typos in strictly positional items
-    if matches!(arg, crate::args::Arg::PosWord(_)) {
+    if matches!(arg, Arg::PosWord(_)) {
         return None;
     }
     // it also should be a printable name
     let actual = arg.to_string();
 
+    // abbreviated long name that was expanded to a known name, typo fixes would be confusing
+    let abbreviated = match arg {
+        Arg::Long(NamePrefix::DoubleDash, name, _, os) => {
+            matches!(split_os_argument(os, &[]), Some((_, typed, _)) if typed != *name)
+        }
+        _ => false,
+    };
+
     // all the help items one level deep
     let mut hi = HelpItems::default();
     hi.append_meta(meta);
@@ -47,7 +72,7 @@ pub(crate) fn suggest(args: &State, meta: &Meta) -> Option<(usize, Suggestion)>
     let mut best_match = None;
     let mut best_dist = usize::MAX;
     let mut improve = |dist, val| {
-        if best_dist > dist && dist > 0 && dist < 4 {
+        if !abbreviated && best_dist > dist && dist > 0 && dist < 4 {
             best_dist = dist;
             best_match = Some(val);
         }
@@ -57,12 +82,21 @@ pub(crate) fn suggest(args: &State, meta: &Meta) -> Option<(usize, Suggestion)>
 
     for item in &hi.items {
         match item {
-            HelpItem::Command { name, meta, .. } => {
+            HelpItem::Command {
+                name,
+                meta,
+                aliases,
+                ..
+            } => {
                 // command can result in 2 types of suggestions:
                 // - typo in a short or a long name
                 // - there is a nested command that matches perfectly - try using that
                 let distance = damerau_levenshtein(&actual, name);
-                improve(distance, Variant::CommandLong(name));
+                improve(distance, Variant::CommandLong(name.to_string()));
+                for alias in aliases.iter() {
+                    let distance = damerau_levenshtein(&actual, alias);
+                    improve(distance, Variant::CommandLong(alias.to_string()));
+                }
 
                 // scan nested items and look for exact matches only
                 nested.items.clear();
@@ -71,13 +105,13 @@ pub(crate) fn suggest(args: &State, meta: &Meta) -> Option<(usize, Suggestion)>
                     match item {
                         HelpItem::Command { name: nname, .. } => {
                             if *nname == actual {
-                                nest = Some((name, Variant::CommandLong(nname)));
+                                nest = Some((name, Variant::CommandLong(nname.to_string())));
                             }
                         }
                         HelpItem::Flag { name: nname, .. }
                         | HelpItem::Argument { name: nname, .. } => {
-                            if *nname == &actual {
-                                nest = Some((name, Variant::Flag(*nname)));
+                            if **nname == actual.as_str() {
+                                nest = Some((name, Variant::Flag((*nname).clone())));
                             }
                         }
                         HelpItem::DecorSuffix { .. }
@@ -90,10 +124,24 @@ pub(crate) fn suggest(args: &State, meta: &Meta) -> Option<(usize, Suggestion)>
                     }
                 }
             }
-            HelpItem::Flag { name, .. } | HelpItem::Argument { name, .. } => {
-                if let Some(long) = name.as_long() {
-                    let distance = damerau_levenshtein(&actual, &format!("--{}", long));
-                    improve(distance, Variant::Flag(*name));
+            HelpItem::Flag { name, aliases, .. } | HelpItem::Argument { name, aliases, .. } => {
+                if let Some((prefix, long)) = name.as_long() {
+                    let distance =
+                        damerau_levenshtein(&actual, &format!("{}{}", prefix.as_str(), long));
+                    improve(distance, Variant::Flag((*name).clone()));
+
+                    if let HelpItem::Flag {
+                        negatable: true, ..
+                    } = item
+                    {
+                        let long = format!("no-{}", long);
+                        let distance =
+                            damerau_levenshtein(&actual, &format!("{}{}", prefix.as_str(), long));
+                        improve(
+                            distance,
+                            Variant::Flag(ShortLong::Long(prefix, long.into())),
+                        );
+                    }
                 }
                 if let Some(short) = name.as_short() {
                     if let Some(act) = actual.strip_prefix("--") {
@@ -103,6 +151,10 @@ pub(crate) fn suggest(args: &State, meta: &Meta) -> Option<(usize, Suggestion)>
                         }
                     }
                 }
+                for alias in aliases.iter() {
+                    let distance = damerau_levenshtein(&actual, &alias.spelled());
+                    improve(distance, Variant::Flag(alias.clone()));
+                }
             }
             HelpItem::Positional { .. }
             | HelpItem::DecorSuffix { .. }
@@ -124,10 +176,10 @@ pub(crate) fn suggest(args: &State, meta: &Meta) -> Option<(usize, Suggestion)>
         let best_match = best_match?;
 
         // handle missing single dash typos separately
-        if let Variant::Flag(n) = best_match {
-            if let Some(long) = n.as_long() {
+        if let Variant::Flag(n) = &best_match {
+            if let Some((NamePrefix::DoubleDash, long)) = n.as_long() {
                 if actual.strip_prefix('-') == Some(long) {
-                    return Some((ix, Suggestion::MissingDash(long)));
+                    return Some((ix, Suggestion::MissingDash(long.to_owned())));
                 }
             }
         }
@@ -135,6 +187,19 @@ pub(crate) fn suggest(args: &State, meta: &Meta) -> Option<(usize, Suggestion)>
     }
 }
 
+/// Looks for a typo in a value that should be one of the possible values
+pub(crate) fn suggest_value<'a>(
+    actual: &str,
+    possible: &'a [Cow<'static, str>],
+) -> Option<&'a str> {
+    possible
+        .iter()
+        .map(|p| (damerau_levenshtein(actual, p), p))
+        .filter(|(dist, _)| *dist > 0 && *dist < 4)
+        .min_by_key(|(dist, _)| *dist)
+        .map(|(_, p)| p.as_ref())
+}
+
 /// Damerau-Levenshtein distance function
 ///
 /// returns `usize::MAX` if there's no common characters at all mostly to avoid
@@ -51,6 +51,11 @@
 #![cfg_attr(not(doctest), doc = include_str!("docs2/any_simple.md"))]
 #![cfg_attr(not(doctest), doc = include_str!("docs2/any_literal.md"))]
 //!
+//! ## Key value
+//!
+//! A special case of an argument that takes `KEY=VALUE` pairs, `-D name=Bob` or
+//! `--set level=3`. Created with [`NamedArg::key_value`], can collect pairs into a map.
+//!
 //! ## Command
 //!
 //! A command defines a starting point for an independent subparser. Name must be a valid utf8
@@ -59,20 +64,55 @@
 //!
 #![cfg_attr(not(doctest), doc = include_str!("docs2/command.md"))]
 //!
-use std::{ffi::OsString, marker::PhantomData, str::FromStr};
+use std::{borrow::Cow, ffi::OsString, marker::PhantomData, str::FromStr};
 
 use crate::{
     args::{Arg, State},
-    error::{Message, MissingItem},
+    error::{Current, Message, MissingItem},
     from_os_str::parse_os_str,
-    item::ShortLong,
+    item::{NamePrefix, Relations, ShortLong},
+    messages::fill_text,
     meta_help::Metavar,
-    Doc, Error, Item, Meta, OptionParser, Parser,
+    structs::parse_option,
+    Doc, Error, Item, Meta, OptionParser, Parser, ValueSource,
 };
 
 #[cfg(doc)]
 use crate::{any, command, env, long, positional, short};
 
+/// A name for a flag, an argument, a command or a metavariable
+///
+/// Names are usually string literals, but you can also use a `String` computed at runtime,
+/// for example when the command line is built from a configuration file.
+pub trait IntoName {
+    /// Convert into a name
+    fn into_name(self) -> Cow<'static, str>;
+}
+
+impl IntoName for &'static str {
+    fn into_name(self) -> Cow<'static, str> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl IntoName for &&'static str {
+    fn into_name(self) -> Cow<'static, str> {
+        Cow::Borrowed(*self)
+    }
+}
+
+impl IntoName for String {
+    fn into_name(self) -> Cow<'static, str> {
+        Cow::Owned(self)
+    }
+}
+
+impl IntoName for Cow<'static, str> {
+    fn into_name(self) -> Cow<'static, str> {
+        self
+    }
+}
+
 /// A named thing used to create [`flag`](NamedArg::flag), [`switch`](NamedArg::switch) or
 /// [`argument`](NamedArg::argument)
 ///
@@ -106,20 +146,116 @@ use crate::{any, command, env, long, positional, short};
 #[derive(Clone, Debug)]
 pub struct NamedArg {
     pub(crate) short: Vec<char>,
-    pub(crate) long: Vec<&'static str>,
-    pub(crate) env: Vec<&'static str>,
+    pub(crate) long: Vec<Cow<'static, str>>,
+    pub(crate) prefix: NamePrefix,
+    pub(crate) env: Vec<Cow<'static, str>>,
     pub(crate) help: Option<Doc>,
+    pub(crate) relations: Relations,
+    /// migration hint, see [`deprecated`](NamedArg::deprecated)
+    pub(crate) deprecated: Option<&'static str>,
+    /// names added with [`short_alias`](NamedArg::short_alias) and
+    /// [`visible_short_alias`](NamedArg::visible_short_alias), set to `true` for visible ones
+    pub(crate) short_aliases: Vec<(char, bool)>,
+    /// names added with [`alias`](NamedArg::alias) and [`visible_alias`](NamedArg::visible_alias),
+    /// set to `true` for visible ones, spelled with the prefix of the item
+    pub(crate) long_aliases: Vec<(Cow<'static, str>, bool)>,
 }
 
 impl NamedArg {
-    pub(crate) fn flag_item(&self) -> Option<Item> {
+    pub(crate) fn flag_item(&self, negatable: bool) -> Option<Item> {
+        let mut longs = self.all_longs();
+        if negatable {
+            longs.extend(self.negated().all_longs());
+        }
         Some(Item::Flag {
             name: ShortLong::try_from(self).ok()?,
             help: self.help.clone(),
-            env: self.env.first().copied(),
-            shorts: self.short.clone(),
+            env: self.env.first().cloned(),
+            shorts: self.all_shorts(),
+            longs,
+            negatable,
+            relations: self.relations.clone(),
+            aliases: self.visible_aliases(),
         })
     }
+
+    /// Visible aliases, long names use the prefix of the item
+    pub(crate) fn visible_aliases(&self) -> Vec<ShortLong> {
+        let shorts = self
+            .short_aliases
+            .iter()
+            .filter(|(_, visible)| *visible)
+            .map(|(short, _)| ShortLong::Short(*short));
+        let longs = self
+            .long_aliases
+            .iter()
+            .filter(|(_, visible)| *visible)
+            .map(|(long, _)| ShortLong::Long(self.prefix, long.clone()));
+        shorts.chain(longs).collect()
+    }
+
+    /// Short names followed by short aliases
+    pub(crate) fn all_shorts(&self) -> Vec<char> {
+        let aliases = self.short_aliases.iter().map(|(short, _)| *short);
+        self.short.iter().copied().chain(aliases).collect()
+    }
+
+    /// Long names followed by long aliases
+    pub(crate) fn all_longs(&self) -> Vec<Cow<'static, str>> {
+        let aliases = self.long_aliases.iter().map(|(long, _)| long.clone());
+        self.long.iter().cloned().chain(aliases).collect()
+    }
+
+    fn has_short(&self, short: char) -> bool {
+        self.short.contains(&short) || self.short_aliases.iter().any(|(s, _)| *s == short)
+    }
+
+    fn has_long(&self, long: &str) -> bool {
+        self.long.iter().any(|l| l == long) || self.long_aliases.iter().any(|(l, _)| l == long)
+    }
+
+    /// Value of the first environment variable that is set, marks the value as coming from it
+    fn env_value(&self, args: &mut State) -> Option<OsString> {
+        let val = self.env.iter().find_map(|e| std::env::var_os(e.as_ref()))?;
+        args.source = Some(ValueSource::Env);
+        Some(val)
+    }
+
+    /// Check if any of the names is spelled as `name`: `-v`, `--verbose`, `+x`, etc.
+    pub(crate) fn is_spelled(&self, name: &str) -> bool {
+        let mut chars = name.strip_prefix('-').unwrap_or_default().chars();
+        let short = match (chars.next(), chars.next()) {
+            (Some(s), None) => self.has_short(s),
+            _ => false,
+        };
+        short
+            || name
+                .strip_prefix(self.prefix.as_str())
+                .map_or(false, |l| self.has_long(l))
+    }
+
+    /// `--no-` counterpart of a negatable switch: the same long names with `no-` in front
+    pub(crate) fn negated(&self) -> NamedArg {
+        NamedArg {
+            short: Vec::new(),
+            long: self
+                .long
+                .iter()
+                .map(|l| Cow::Owned(format!("no-{}", l)))
+                .collect(),
+            prefix: self.prefix,
+            env: Vec::new(),
+            help: self.help.clone(),
+            relations: Relations::default(),
+            deprecated: self.deprecated,
+            short_aliases: Vec::new(),
+            long_aliases: self
+                .long_aliases
+                .iter()
+                .map(|(l, _)| (Cow::Owned(format!("no-{}", l)), false))
+                .collect(),
+        }
+    }
 }
 
 impl NamedArg {
@@ -134,10 +270,125 @@ impl NamedArg {
 
     /// Add a long name to a flag/switch/argument
     ///
+    /// Name can be either `&'static str` or a `String` computed at runtime.
+    ///
     #[cfg_attr(not(doctest), doc = include_str!("docs2/short_long_env.md"))]
     #[must_use]
-    pub fn long(mut self, long: &'static str) -> Self {
-        self.long.push(long);
+    pub fn long<M>(mut self, long: M) -> Self
+    where
+        M: IntoName,
+    {
+        self.long.push(long.into_name());
+        self
+    }
+
+    /// Add a hidden long alias to a flag/switch/argument
+    ///
+    /// Alias is accepted on a command line but not shown in the help message or offered by
+    /// completion. Aliases are never used as the main name of the item.
+    #[must_use]
+    pub fn alias<M>(mut self, long: M) -> Self
+    where
+        M: IntoName,
+    {
+        self.long_aliases.push((long.into_name(), false));
+        self
+    }
+
+    /// Add a long alias to a flag/switch/argument that is listed in the help message
+    ///
+    /// Visible aliases are shown next to the help message, offered by completion and considered
+    /// when suggesting a fix for a typo.
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// let parser = long("color")
+    ///     .help("Use colors")
+    ///     .visible_alias("colour")
+    ///     .switch()
+    ///     .to_options();
+    ///
+    /// assert!(parser.run_inner(&["--colour"]).unwrap());
+    ///
+    /// let r = parser.run_inner(&["--help"]).unwrap_err().unwrap_stdout();
+    /// let expected = "\
+    /// Usage: [--color]
+    ///
+    /// Available options:
+    ///         --color  Use colors [aliases: --colour]
+    ///     -h, --help   Prints help information
+    /// ";
+    /// assert_eq!(r, expected);
+    /// ```
+    #[must_use]
+    pub fn visible_alias<M>(mut self, long: M) -> Self
+    where
+        M: IntoName,
+    {
+        self.long_aliases.push((long.into_name(), true));
+        self
+    }
+
+    /// Add a hidden short alias to a flag/switch/argument
+    ///
+    /// See [`alias`](NamedArg::alias).
+    #[must_use]
+    pub fn short_alias(mut self, short: char) -> Self {
+        self.short_aliases.push((short, false));
+        self
+    }
+
+    /// Add a short alias to a flag/switch/argument that is listed in the help message
+    ///
+    /// See [`visible_alias`](NamedArg::visible_alias).
+    #[must_use]
+    pub fn visible_short_alias(mut self, short: char) -> Self {
+        self.short_aliases.push((short, true));
+        self
+    }
+
+    /// Spell long names with a single dash: `-name` instead of `--name`
+    ///
+    /// Some tools such as `find` or `xorg` use long names prefixed by a single dash. Such names
+    /// take priority over a group of short names when splitting the command line: if both
+    /// `-name` and short flags `-n`, `-a`, `-m` and `-e` are available - `-name` is parsed as a
+    /// long name. Names that are not declared this way are still parsed as a group of short
+    /// names.
+    ///
+    /// A shortcut for [`prefix(NamePrefix::SingleDash)`](NamedArg::prefix).
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// let name = long("name").single_dash().argument::<String>("PATTERN");
+    /// let r = name.to_options().run_inner(&["-name", "*.rs"]).unwrap();
+    /// assert_eq!(r, "*.rs");
+    /// ```
+    #[must_use]
+    pub fn single_dash(self) -> Self {
+        self.prefix(NamePrefix::SingleDash)
+    }
+
+    /// Spell long names with an alternative prefix: `+name` or `/name` instead of `--name`
+    ///
+    /// Prefix applies to all the long names of this item, short names still use a single dash.
+    /// Names with `+` and `/` prefixes are recognized only if some parser declares them, so
+    /// `/tmp` is still a positional item unless there's a `/tmp` name.
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// // set -e / set +e
+    /// let on = short('e').req_flag(true);
+    /// let off = long("e").prefix(NamePrefix::Plus).req_flag(false);
+    /// let parser = construct!([on, off]).to_options();
+    ///
+    /// let r = parser.run_inner(&["+e"]).unwrap();
+    /// assert_eq!(r, false);
+    /// let r = parser.run_inner(&["-e"]).unwrap();
+    /// assert_eq!(r, true);
+    /// ```
+    #[must_use]
+    pub fn prefix(mut self, prefix: NamePrefix) -> Self {
+        self.prefix = prefix;
         self
     }
 
@@ -156,8 +407,11 @@ impl NamedArg {
     /// ```
     #[cfg_attr(not(doctest), doc = include_str!("docs2/short_long_env.md"))]
     #[must_use]
-    pub fn env(mut self, variable: &'static str) -> Self {
-        self.env.push(variable);
+    pub fn env<M>(mut self, variable: M) -> Self
+    where
+        M: IntoName,
+    {
+        self.env.push(variable.into_name());
         self
     }
 
@@ -182,6 +436,92 @@ impl NamedArg {
         self
     }
 
+    /// Fail if this item is used together with another named item
+    ///
+    /// Name should be spelled the same way as on a command line: `"--yaml"` or `"-y"`. Only
+    /// names used on the command line are checked, values taken from environment variables or
+    /// fallbacks are not. Both items are mentioned in the error message and the relation is
+    /// shown in the help message. Debug builds panic if no item has such name.
+    ///
+    /// Unlike [`construct!`](crate::construct!) with alternatives this doesn't change the shape of
+    /// the parsers.
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// let json = long("json").conflicts_with("--yaml").switch();
+    /// let yaml = long("yaml").switch();
+    /// let parser = construct!(json, yaml).to_options();
+    ///
+    /// let r = parser.run_inner(&["--json", "--yaml"]).unwrap_err().unwrap_stderr();
+    /// assert_eq!(r, "`--yaml` cannot be used at the same time as `--json`");
+    /// ```
+    #[must_use]
+    pub fn conflicts_with<M>(mut self, name: M) -> Self
+    where
+        M: IntoName,
+    {
+        self.relations.conflicts.push(name.into_name());
+        self
+    }
+
+    /// Fail if this item is used without another named item
+    ///
+    /// Name should be spelled the same way as on a command line: `"--output"` or `"-o"`. Only
+    /// names used on the command line are checked, values taken from environment variables or
+    /// fallbacks are not. The relation is shown in the help message. Debug builds panic if no
+    /// item has such name.
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// let json = long("json").requires("--output").switch();
+    /// let output = long("output").argument::<String>("FILE").optional();
+    /// let parser = construct!(json, output).to_options();
+    ///
+    /// let r = parser.run_inner(&["--json"]).unwrap_err().unwrap_stderr();
+    /// assert_eq!(r, "`--json` requires `--output` to be present");
+    ///
+    /// let r = parser.run_inner(&["--json", "--output", "out.json"]).unwrap();
+    /// assert_eq!(r, (true, Some("out.json".to_owned())));
+    /// ```
+    #[must_use]
+    pub fn requires<M>(mut self, name: M) -> Self
+    where
+        M: IntoName,
+    {
+        self.relations.requires.push(name.into_name());
+        self
+    }
+
+    /// Keep accepting this item but warn users to migrate away from it
+    ///
+    /// Deprecated items are parsed as usual, but each use produces a warning that mentions the
+    /// name as it was spelled along with the migration hint. Warnings are passed to a handler set
+    /// with [`on_warning`](OptionParser::on_warning), [`run`](OptionParser::run) prints them to
+    /// stderr by default. Deprecated items are hidden from the help message and completion.
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// # use std::sync::mpsc::channel;
+    /// let jobs = long("jobs").argument::<u32>("N");
+    /// let threads = long("threads")
+    ///     .deprecated("use --jobs instead")
+    ///     .argument::<u32>("N");
+    /// let (tx, rx) = channel();
+    /// let parser = construct!([jobs, threads])
+    ///     .to_options()
+    ///     .on_warning(move |w| tx.send(w.monochrome(true)).unwrap());
+    ///
+    /// let r = parser.run_inner(&["--threads", "4"]).unwrap();
+    /// assert_eq!(r, 4);
+    /// let w = rx.try_recv().unwrap();
+    /// assert_eq!(w, "`--threads` is deprecated, use --jobs instead");
+    /// ```
+    #[must_use]
+    pub fn deprecated(mut self, hint: &'static str) -> Self {
+        self.deprecated = Some(hint);
+        self
+    }
+
     /// Simple boolean flag
     ///
     /// A special case of a [`flag`](NamedArg::flag) that gets decoded into a `bool`, mostly serves as a convenient
@@ -197,6 +537,35 @@ impl NamedArg {
         build_flag_parser(true, Some(false), self)
     }
 
+    /// Boolean flag that can be turned off with a `--no-` version of its name
+    ///
+    /// Similar to [`switch`](NamedArg::switch), but for every long name `--name` also accepts
+    /// `--no-name`. Both forms can be used multiple times, the last one wins and parser produces
+    /// `false` if neither is present. Short names and environment variables can only turn the
+    /// switch on. Help message renders such switch as `--[no-]name` and completion offers both
+    /// forms.
+    ///
+    /// In Derive API you can get this parser with `negatable_switch` annotation.
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// let parser = long("color").help("Use colors").negatable_switch().to_options();
+    ///
+    /// assert!(parser.run_inner(&["--color"]).unwrap());
+    /// assert!(!parser.run_inner(&["--color", "--no-color"]).unwrap());
+    /// assert!(parser.run_inner(&["--no-color", "--color"]).unwrap());
+    /// assert!(!parser.run_inner(&[]).unwrap());
+    ///
+    /// let help = parser.run_inner(&["--help"]).unwrap_err().unwrap_stdout();
+    /// assert!(help.contains("--[no-]color  Use colors"));
+    /// ```
+    #[must_use]
+    pub fn negatable_switch(self) -> ParseFlag<bool> {
+        let mut parser = build_flag_parser(true, Some(false), self);
+        parser.negatable = true;
+        parser
+    }
+
     /// Flag with custom present/absent values
     ///
     /// More generic version of [`switch`](NamedArg::switch) that can use arbitrary type instead of
@@ -245,21 +614,62 @@ impl NamedArg {
     ///
     #[cfg_attr(not(doctest), doc = include_str!("docs2/argument.md"))]
     ///
-    /// You can further restrict it using [`adjacent`](ParseArgument::adjacent)
+    /// You can further restrict it using [`adjacent`](ParseArgument::adjacent), to use a
+    /// metavariable computed at runtime - see [`metavar`](ParseArgument::metavar).
     #[must_use]
     pub fn argument<T>(self, metavar: &'static str) -> ParseArgument<T>
     where
         T: FromStr + 'static,
     {
-        build_argument(self, metavar)
+        build_argument(self, Metavar(Cow::Borrowed(metavar)))
+    }
+
+    /// Argument with a value in `KEY=VALUE` form
+    ///
+    /// Value is split at the first `=`, key and value are parsed with [`FromStr`] separately.
+    /// Parser produces a single pair, use [`collect`](ParseKeyValue::collect) to get a map
+    /// and to decide what to do with duplicate keys.
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// # use std::collections::BTreeMap;
+    /// let parser = short('D')
+    ///     .help("Define a variable")
+    ///     .key_value::<String, u32>("KEY", "VALUE")
+    ///     .collect::<BTreeMap<_, _>>()
+    ///     .to_options();
+    ///
+    /// let r = parser.run_inner(&["-D", "width=80", "-D", "height=25"]).unwrap();
+    /// assert_eq!(r["width"], 80);
+    /// assert_eq!(r["height"], 25);
+    /// ```
+    #[must_use]
+    pub fn key_value<K, V>(self, key: &'static str, value: &'static str) -> ParseKeyValue<K, V>
+    where
+        K: FromStr + 'static,
+        V: FromStr + 'static,
+    {
+        let metavar = Metavar(Cow::Owned(format!("{}={}", key, value)));
+        ParseKeyValue {
+            inner: build_argument(self, metavar),
+            key: Metavar(Cow::Borrowed(key)),
+            value: Metavar(Cow::Borrowed(value)),
+            #[cfg(feature = "autocomplete")]
+            complete_key: None,
+            #[cfg(feature = "autocomplete")]
+            complete_value: None,
+            ty: PhantomData,
+        }
     }
 
     /// `adjacent` requires for the argument to be present in the same word as the flag:
     /// `-f bar` - no, `-fbar` or `-f=bar` - yes.
     pub(crate) fn matches_arg(&self, arg: &Arg, adjacent: bool) -> bool {
         match arg {
-            Arg::Short(s, is_adj, _) => self.short.contains(s) && (!adjacent || *is_adj),
-            Arg::Long(l, is_adj, _) => self.long.contains(&l.as_str()) && (!adjacent || *is_adj),
+            Arg::Short(s, is_adj, _) => self.has_short(*s) && (!adjacent || *is_adj),
+            Arg::Long(p, l, is_adj, _) => {
+                *p == self.prefix && self.has_long(l) && (!adjacent || *is_adj)
+            }
             Arg::ArgWord(_) | Arg::Word(_) | Arg::PosWord(_) => false,
         }
     }
@@ -299,16 +709,19 @@ impl<T> OptionParser<T> {
     /// To represent multiple possible commands it is convenient to use enums
     #[cfg_attr(not(doctest), doc = include_str!("docs2/command_enum.md"))]
     #[must_use]
-    pub fn command(self, name: &'static str) -> ParseCommand<T>
+    pub fn command<M>(self, name: M) -> ParseCommand<T>
     where
         T: 'static,
+        M: IntoName,
     {
         ParseCommand {
-            longs: vec![name],
+            longs: vec![name.into_name()],
             shorts: Vec::new(),
             help: self.short_descr().map(Into::into),
             subparser: self,
             adjacent: false,
+            deprecated: None,
+            visible: Vec::new(),
         }
     }
 }
@@ -317,12 +730,16 @@ impl<T> OptionParser<T> {
 ///
 /// Created with [`command`], implements parser for the inner structure, gives access to [`help`](ParseCommand::help).
 pub struct ParseCommand<T> {
-    pub(crate) longs: Vec<&'static str>,
+    pub(crate) longs: Vec<Cow<'static, str>>,
     pub(crate) shorts: Vec<char>,
     // short help!
     pub(crate) help: Option<Doc>,
     pub(crate) subparser: OptionParser<T>,
     pub(crate) adjacent: bool,
+    /// migration hint, see [`deprecated`](ParseCommand::deprecated)
+    pub(crate) deprecated: Option<&'static str>,
+    /// aliases listed in the help message, see [`visible_alias`](ParseCommand::visible_alias)
+    pub(crate) visible: Vec<Cow<'static, str>>,
 }
 
 impl<P> ParseCommand<P> {
@@ -395,11 +812,63 @@ impl<P> ParseCommand<P> {
     /// Behavior is similar to [`long`](NamedArg::long), but since you had to specify the first long
     /// name when making the command - this one becomes a hidden alias.
     #[must_use]
-    pub fn long(mut self, long: &'static str) -> Self {
-        self.longs.push(long);
+    pub fn long<M>(mut self, long: M) -> Self
+    where
+        M: IntoName,
+    {
+        self.longs.push(long.into_name());
         self
     }
 
+    /// Add a hidden alias for a command
+    ///
+    /// Same as [`long`](ParseCommand::long).
+    #[must_use]
+    pub fn alias<M>(self, name: M) -> Self
+    where
+        M: IntoName,
+    {
+        self.long(name)
+    }
+
+    /// Add an alias for a command that is listed in the help message
+    ///
+    /// Visible aliases are shown next to the command description, offered by completion and
+    /// considered when suggesting a fix for a typo.
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// let parser = pure(())
+    ///     .to_options()
+    ///     .descr("Remove a file")
+    ///     .command("remove")
+    ///     .visible_alias("rm")
+    ///     .to_options();
+    ///
+    /// assert_eq!(parser.run_inner(&["rm"]).unwrap(), ());
+    ///
+    /// let r = parser.run_inner(&["--help"]).unwrap_err().unwrap_stdout();
+    /// let expected = "\
+    /// Usage: COMMAND ...
+    ///
+    /// Available options:
+    ///     -h, --help  Prints help information
+    ///
+    /// Available commands:
+    ///     remove      Remove a file [aliases: rm]
+    /// ";
+    /// assert_eq!(r, expected);
+    /// ```
+    #[must_use]
+    pub fn visible_alias<M>(mut self, name: M) -> Self
+    where
+        M: IntoName,
+    {
+        let name = name.into_name();
+        self.visible.push(name.clone());
+        self.long(name)
+    }
+
     /// Allow for the command to succeed even if there are non consumed items present
     ///
     /// Normally a subcommand parser should handle the rest of the unconsumed elements thus
@@ -422,6 +891,17 @@ impl<P> ParseCommand<P> {
         self.adjacent = true;
         self
     }
+
+    /// Keep accepting this command but warn users to migrate away from it
+    ///
+    /// Behavior is similar to [`deprecated`](NamedArg::deprecated): command is parsed as usual,
+    /// each use produces a warning and the command is hidden from the help message and
+    /// completion.
+    #[must_use]
+    pub fn deprecated(mut self, hint: &'static str) -> Self {
+        self.deprecated = Some(hint);
+        self
+    }
 }
 
 impl<T> Parser<T> for ParseCommand<T> {
@@ -440,10 +920,14 @@ impl<T> Parser<T> for ParseCommand<T> {
                 // in completion mode prefer to autocomplete the command name vs going inside the
                 // parser
                 args.clear_comps();
-                args.push_command(self.longs[0], self.shorts.first().copied(), &self.help);
+                self.push_completions(args);
                 return Err(Error(Message::Missing(Vec::new())));
             }
 
+            if let (Some(hint), Some(cur)) = (self.deprecated, args.current) {
+                args.warnings.push((cur, hint));
+            }
+
             if let Some(cur) = args.current {
                 args.set_scope(cur..args.scope().end);
             }
@@ -458,7 +942,7 @@ impl<T> Parser<T> for ParseCommand<T> {
                 match self
                     .subparser
                     .run_subparser(args)
-                    .map_err(Message::ParseFailure)
+                    .map_err(Message::ParseError)
                 {
                     Ok(ok) => {
                         args.set_scope(orig_args.scope());
@@ -480,11 +964,11 @@ impl<T> Parser<T> for ParseCommand<T> {
             } else {
                 self.subparser
                     .run_subparser(args)
-                    .map_err(|e| Error(Message::ParseFailure(e)))
+                    .map_err(|e| Error(Message::ParseError(e)))
             }
         } else {
             #[cfg(feature = "autocomplete")]
-            args.push_command(self.longs[0], self.shorts.first().copied(), &self.help);
+            self.push_completions(args);
 
             let missing = MissingItem {
                 item: self.item(),
@@ -496,18 +980,37 @@ impl<T> Parser<T> for ParseCommand<T> {
     }
 
     fn meta(&self) -> Meta {
-        Meta::from(self.item())
+        let meta = Meta::from(self.item());
+        if self.deprecated.is_some() {
+            Meta::Hidden(Box::new(meta))
+        } else {
+            meta
+        }
     }
 }
 
 impl<T> ParseCommand<T> {
     fn item(&self) -> Item {
         Item::Command {
-            name: self.longs[0],
+            name: self.longs[0].clone(),
             short: self.shorts.first().copied(),
             help: self.help.clone(),
             meta: Box::new(self.subparser.inner.meta()),
             info: Box::new(self.subparser.info.clone()),
+            aliases: self.visible.clone(),
+        }
+    }
+
+    /// Offer command name and its visible aliases as completion hints
+    #[cfg(feature = "autocomplete")]
+    fn push_completions(&self, args: &mut State) {
+        if self.deprecated.is_some() {
+            return;
+        }
+        let short = self.shorts.first().copied();
+        args.push_command(self.longs[0].clone(), short, &self.help);
+        for alias in &self.visible {
+            args.push_command(alias.clone(), None, &self.help);
         }
     }
 }
@@ -520,6 +1023,7 @@ where
         present,
         absent,
         named,
+        negatable: false,
     }
 }
 
@@ -529,24 +1033,35 @@ pub struct ParseFlag<T> {
     present: T,
     absent: Option<T>,
     named: NamedArg,
+    /// accepts `--no-` names as well, see [`NamedArg::negatable_switch`]
+    negatable: bool,
 }
 
 impl<T: Clone + 'static> Parser<T> for ParseFlag<T> {
     fn eval(&self, args: &mut State) -> Result<T, Error> {
-        if args.take_flag(&self.named) || self.named.env.iter().find_map(std::env::var_os).is_some()
-        {
-            #[cfg(feature = "autocomplete")]
-            if args.touching_last_remove() {
-                args.push_flag(&self.named);
+        // `--name` and `--no-name` can be repeated, the last one wins
+        let negated = self.negatable.then(|| self.named.negated());
+        let taken = match &negated {
+            Some(negated) => args.take_negatable_flag(&self.named, negated),
+            None => args.take_flag(&self.named).then(|| true),
+        };
+        let present = taken.or_else(|| self.named.env_value(args).map(|_| true));
+
+        #[cfg(feature = "autocomplete")]
+        if present.is_none() || args.touching_last_remove() {
+            args.push_flag(&self.named);
+            if let Some(negated) = &negated {
+                args.push_flag(negated);
             }
+        }
+
+        if present == Some(true) {
             Ok(self.present.clone())
         } else {
-            #[cfg(feature = "autocomplete")]
-            args.push_flag(&self.named);
             match &self.absent {
                 Some(ok) => Ok(ok.clone()),
                 None => {
-                    if let Some(item) = self.named.flag_item() {
+                    if let Some(item) = self.named.flag_item(self.negatable) {
                         let missing = MissingItem {
                             item,
                             position: args.scope().start,
@@ -554,7 +1069,7 @@ impl<T: Clone + 'static> Parser<T> for ParseFlag<T> {
                         };
                         Err(Error(Message::Missing(vec![missing])))
                     } else if let Some(name) = self.named.env.first() {
-                        Err(Error(Message::NoEnv(name)))
+                        Err(Error(Message::NoEnv(name.clone())))
                     } else {
                         todo!("no key!")
                     }
@@ -564,10 +1079,12 @@ impl<T: Clone + 'static> Parser<T> for ParseFlag<T> {
     }
 
     fn meta(&self) -> Meta {
-        if let Some(item) = self.named.flag_item() {
-            item.required(self.absent.is_none())
-        } else {
-            Meta::Skip
+        match self.named.flag_item(self.negatable) {
+            Some(item) if self.named.deprecated.is_some() => {
+                Meta::Hidden(Box::new(Meta::from(item)))
+            }
+            Some(item) => item.required(self.absent.is_none()),
+            None => Meta::Skip,
         }
     }
 }
@@ -600,12 +1117,14 @@ impl<T> ParseArgument<T> {
     }
 }
 
-fn build_argument<T>(named: NamedArg, metavar: &'static str) -> ParseArgument<T> {
+fn build_argument<T>(named: NamedArg, metavar: Metavar) -> ParseArgument<T> {
     ParseArgument {
         named,
         metavar,
         ty: PhantomData,
         adjacent: false,
+        delimiter: None,
+        possible: Vec::new(),
     }
 }
 
@@ -614,8 +1133,12 @@ fn build_argument<T>(named: NamedArg, metavar: &'static str) -> ParseArgument<T>
 pub struct ParseArgument<T> {
     ty: PhantomData<T>,
     named: NamedArg,
-    metavar: &'static str,
+    metavar: Metavar,
     adjacent: bool,
+    /// set by [`delimited`](ParseArgument::delimited)
+    delimiter: Option<char>,
+    /// set by [`possible_values`](ParseArgument::possible_values)
+    possible: Vec<Cow<'static, str>>,
 }
 
 impl<T> ParseArgument<T> {
@@ -634,35 +1157,150 @@ impl<T> ParseArgument<T> {
         self
     }
 
+    /// Replace metavariable with a custom value
+    ///
+    /// Unlike [`argument`](NamedArg::argument) this method accepts names computed at runtime
+    /// ```rust
+    /// # use bpaf::*;
+    /// let meta = String::from("USER");
+    /// let parser = long("user").argument::<String>("").metavar(meta);
+    /// ```
+    #[must_use]
+    pub fn metavar<M>(mut self, metavar: M) -> Self
+    where
+        M: IntoName,
+    {
+        self.metavar = Metavar(metavar.into_name());
+        self
+    }
+
+    /// Restrict the value to one of the listed values
+    ///
+    /// Values are checked before parsing them with [`FromStr`], help message lists them,
+    /// completion suggests them and mistyped values get a "did you mean" suggestion. For
+    /// [`delimited`](ParseArgument::delimited) lists every element is checked.
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// let parser = long("mode")
+    ///     .help("Processing mode")
+    ///     .argument::<String>("MODE")
+    ///     .possible_values(["fast", "slow"])
+    ///     .to_options();
+    ///
+    /// let r = parser.run_inner(&["--mode", "fast"]).unwrap();
+    /// assert_eq!(r, "fast");
+    ///
+    /// let r = parser.run_inner(&["--mode", "fsat"]).unwrap_err().unwrap_stderr();
+    /// assert_eq!(r, "`fsat` is not a valid value, did you mean `fast`?");
+    ///
+    /// let r = parser.run_inner(&["--help"]).unwrap_err().unwrap_stdout();
+    /// let expected = "\
+    /// Usage: --mode=MODE
+    ///
+    /// Available options:
+    ///         --mode=MODE  Processing mode [possible values: fast, slow]
+    ///     -h, --help       Prints help information
+    /// ";
+    /// assert_eq!(r, expected);
+    /// ```
+    #[must_use]
+    pub fn possible_values<I, M>(mut self, values: I) -> Self
+    where
+        I: IntoIterator<Item = M>,
+        M: IntoName,
+    {
+        self.possible = values.into_iter().map(IntoName::into_name).collect();
+        self
+    }
+
+    /// Split the value into a list of elements separated by a delimiter
+    ///
+    /// Each element is parsed with [`FromStr`] and parser produces a vector of them, failure to
+    /// parse any of the elements is reported together with its position in the list. One
+    /// occurrence of a name gives one vector, combine it with [`many`](Parser::many) to accept
+    /// several of them. Help message renders metavariable as `PORT,...`, completion completes the
+    /// element after the last delimiter.
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// let parser = long("ports")
+    ///     .argument::<u16>("PORT")
+    ///     .delimited(',')
+    ///     .many()
+    ///     .map(|xs| xs.concat())
+    ///     .to_options();
+    ///
+    /// let r = parser.run_inner(&["--ports", "80,443", "--ports", "8080"]).unwrap();
+    /// assert_eq!(r, [80, 443, 8080]);
+    ///
+    /// let r = parser.run_inner(&["--ports", "80,http"]).unwrap_err().unwrap_stderr();
+    /// assert_eq!(
+    ///     r,
+    ///     "couldn't parse element 2 (`http`) of `80,http`: invalid digit found in string"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn delimited(mut self, delimiter: char) -> ParseDelimited<T> {
+        self.delimiter = Some(delimiter);
+        ParseDelimited {
+            inner: self,
+            delimiter,
+        }
+    }
+
     fn item(&self) -> Option<Item> {
         Some(Item::Argument {
             name: ShortLong::try_from(&self.named).ok()?,
-            metavar: Metavar(self.metavar),
-            env: self.named.env.first().copied(),
+            metavar: self.metavar.clone(),
+            delimiter: self.delimiter,
+            env: self.named.env.first().cloned(),
             help: self.named.help.clone(),
-            shorts: self.named.short.clone(),
+            shorts: self.named.all_shorts(),
+            longs: self.named.all_longs(),
+            relations: self.named.relations.clone(),
+            possible: self.possible.clone(),
+            aliases: self.named.visible_aliases(),
         })
     }
 
     fn take_argument(&self, args: &mut State) -> Result<OsString, Error> {
-        match args.take_arg(&self.named, self.adjacent, Metavar(self.metavar)) {
+        match args.take_arg(&self.named, self.adjacent, &self.metavar) {
             Ok(Some(w)) => {
                 #[cfg(feature = "autocomplete")]
                 if args.touching_last_remove() {
-                    args.push_metavar(self.metavar, &self.named.help, true);
+                    // only the last element of a delimited list is being completed
+                    let s = w.to_str().unwrap_or_default();
+                    let prefix = match self.delimiter {
+                        Some(d) => s.rfind(d).map_or("", |ix| &s[..ix + d.len_utf8()]),
+                        None => "",
+                    };
+                    push_possible_values(
+                        args,
+                        &self.possible,
+                        &self.metavar,
+                        &self.named.help,
+                        true,
+                        prefix,
+                        &s[prefix.len()..],
+                    );
                 }
                 Ok(w)
             }
             Err(err) => {
                 #[cfg(feature = "autocomplete")]
-                args.push_argument(&self.named, self.metavar);
+                args.push_argument(&self.named, &self.metavar);
                 Err(err)
             }
             _ => {
                 #[cfg(feature = "autocomplete")]
-                args.push_argument(&self.named, self.metavar);
-                if let Some(val) = self.named.env.iter().find_map(std::env::var_os) {
+                args.push_argument(&self.named, &self.metavar);
+                if let Some(val) = self.named.env_value(args) {
                     args.current = None;
+                    args.current_name = ShortLong::try_from(&self.named)
+                        .map(|n| n.spelled())
+                        .ok()
+                        .or_else(|| self.named.env.first().map(ToString::to_string));
                     return Ok(val);
                 }
 
@@ -674,7 +1312,7 @@ impl<T> ParseArgument<T> {
                     };
                     Err(Error(Message::Missing(vec![missing])))
                 } else if let Some(name) = self.named.env.first() {
-                    Err(Error(Message::NoEnv(name)))
+                    Err(Error(Message::NoEnv(name.clone())))
                 } else {
                     unreachable!()
                 }
@@ -690,26 +1328,500 @@ where
 {
     fn eval(&self, args: &mut State) -> Result<T, Error> {
         let os = self.take_argument(args)?;
-        match parse_os_str::<T>(os) {
+        check_possible(&self.possible, &os, args)?;
+        match parse_os_str::<T>(os, &*args.messages) {
             Ok(ok) => Ok(ok),
-            Err(err) => Err(Error(Message::ParseFailed(args.current, err))),
+            Err(err) => Err(Error(Message::ParseFailed(args.current_item(), err))),
         }
     }
 
     fn meta(&self) -> Meta {
-        if let Some(item) = self.item() {
-            Meta::from(item)
-        } else {
-            Meta::Skip
+        match self.item() {
+            Some(item) if self.named.deprecated.is_some() => {
+                Meta::Hidden(Box::new(Meta::from(item)))
+            }
+            Some(item) => Meta::from(item),
+            None => Meta::Skip,
+        }
+    }
+}
+
+/// Parser for a list of values separated by a delimiter, created with
+/// [`ParseArgument::delimited`]
+pub struct ParseDelimited<T> {
+    inner: ParseArgument<T>,
+    delimiter: char,
+}
+
+impl<T> Parser<Vec<T>> for ParseDelimited<T>
+where
+    T: FromStr + 'static,
+    <T as std::str::FromStr>::Err: std::fmt::Display,
+{
+    fn eval(&self, args: &mut State) -> Result<Vec<T>, Error> {
+        let os = self.inner.take_argument(args)?;
+        let s = match os.to_str() {
+            Some(s) => s,
+            None => {
+                let msg = fill_text(args.messages.not_utf8(), &[&os.to_string_lossy()]);
+                return Err(Error(Message::ParseFailed(args.current_item(), msg)));
+            }
+        };
+        s.split(self.delimiter)
+            .enumerate()
+            .map(|(ix, elt)| {
+                let elt_os = OsString::from(elt);
+                check_possible(&self.inner.possible, &elt_os, args)?;
+                parse_os_str::<T>(elt_os, &*args.messages).map_err(|err| {
+                    Error(Message::ParseElementFailed(
+                        args.current_item(),
+                        ix,
+                        elt.to_owned(),
+                        err,
+                    ))
+                })
+            })
+            .collect()
+    }
+
+    fn meta(&self) -> Meta {
+        self.inner.meta()
+    }
+}
+
+#[cfg(feature = "autocomplete")]
+type KeyCompleter = Box<dyn Fn(&str) -> Vec<(String, Option<String>)>>;
+#[cfg(feature = "autocomplete")]
+type ValueCompleter = Box<dyn Fn(&str, &str) -> Vec<(String, Option<String>)>>;
+
+/// Parser for `KEY=VALUE` pairs, created with [`NamedArg::key_value`]
+pub struct ParseKeyValue<K, V> {
+    inner: ParseArgument<OsString>,
+    key: Metavar,
+    value: Metavar,
+    #[cfg(feature = "autocomplete")]
+    complete_key: Option<KeyCompleter>,
+    #[cfg(feature = "autocomplete")]
+    complete_value: Option<ValueCompleter>,
+    ty: PhantomData<(K, V)>,
+}
+
+impl<K, V> ParseKeyValue<K, V> {
+    /// Add a help message to a `key_value` parser
+    ///
+    /// See [`NamedArg::help`]
+    #[must_use]
+    pub fn help<M>(mut self, help: M) -> Self
+    where
+        M: Into<Doc>,
+    {
+        self.inner.named.help = Some(help.into());
+        self
+    }
+
+    /// Replace metavariables for the key and the value with custom values
+    ///
+    /// Unlike [`key_value`](NamedArg::key_value) this method accepts names computed at runtime
+    /// ```rust
+    /// # use bpaf::*;
+    /// let (key, value) = (String::from("NAME"), String::from("VAL"));
+    /// let parser = short('D').key_value::<String, u32>("", "").metavar(key, value);
+    /// ```
+    #[must_use]
+    pub fn metavar<A, B>(mut self, key: A, value: B) -> Self
+    where
+        A: IntoName,
+        B: IntoName,
+    {
+        self.key = Metavar(key.into_name());
+        self.value = Metavar(value.into_name());
+        self.inner.metavar = Metavar(Cow::Owned(format!("{}={}", self.key.0, self.value.0)));
+        self
+    }
+
+    /// Collect all the pairs into a map or any other collection
+    ///
+    /// By default the last value wins when a key is used more than once, you can change it with
+    /// [`duplicates`](ParseKeyValueCollect::duplicates).
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// # use std::collections::HashMap;
+    /// let parser = long("set")
+    ///     .key_value::<String, String>("KEY", "VALUE")
+    ///     .collect::<HashMap<_, _>>()
+    ///     .to_options();
+    ///
+    /// let r = parser.run_inner(&["--set", "name=Bob", "--set=name=Bill"]).unwrap();
+    /// assert_eq!(r["name"], "Bill");
+    /// ```
+    #[must_use]
+    pub fn collect<M>(self) -> ParseKeyValueCollect<K, V, M>
+    where
+        M: FromIterator<(K, V)>,
+    {
+        ParseKeyValueCollect {
+            inner: self,
+            duplicates: DuplicateKeys::LastWins,
+            ty: PhantomData,
+        }
+    }
+
+    /// Complete keys with a custom function
+    ///
+    /// Function gets the key user typed so far and returns suggestions for the key with optional
+    /// descriptions, `=` is added after the key automatically.
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// let parser = long("set")
+    ///     .key_value::<String, String>("KEY", "VALUE")
+    ///     .complete_key(|k| {
+    ///         ["name", "level"]
+    ///             .iter()
+    ///             .filter(|n| n.starts_with(k))
+    ///             .map(|n| (*n, None))
+    ///             .collect()
+    ///     })
+    ///     .to_options();
+    ///
+    /// let r = parser
+    ///     .run_inner(Args::from(&["--set", "na"]).set_comp(0))
+    ///     .unwrap_err()
+    ///     .unwrap_stdout();
+    /// assert_eq!(r, "name=");
+    /// ```
+    #[cfg(feature = "autocomplete")]
+    #[must_use]
+    pub fn complete_key<M, F>(mut self, op: F) -> Self
+    where
+        M: Into<String>,
+        F: Fn(&str) -> Vec<(M, Option<M>)> + 'static,
+    {
+        self.complete_key = Some(Box::new(move |key| {
+            op(key)
+                .into_iter()
+                .map(|(k, help)| (k.into(), help.map(Into::into)))
+                .collect()
+        }));
+        self
+    }
+
+    /// Complete values with a custom function
+    ///
+    /// Function gets the key and the value user typed so far and returns suggestions for the
+    /// value with optional descriptions.
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// let parser = long("set")
+    ///     .key_value::<String, String>("KEY", "VALUE")
+    ///     .complete_value(|k, v| match k {
+    ///         "level" => vec![("high", None), ("low", None)],
+    ///         _ => Vec::new(),
+    ///     })
+    ///     .to_options();
+    ///
+    /// let r = parser
+    ///     .run_inner(Args::from(&["--set", "level="]).set_comp(0))
+    ///     .unwrap_err()
+    ///     .unwrap_stdout();
+    /// assert_eq!(r, "\tVALUE\t\t\nlevel=high\tlevel=high\t\t\nlevel=low\tlevel=low\t\t\n\n");
+    /// ```
+    #[cfg(feature = "autocomplete")]
+    #[must_use]
+    pub fn complete_value<M, F>(mut self, op: F) -> Self
+    where
+        M: Into<String>,
+        F: Fn(&str, &str) -> Vec<(M, Option<M>)> + 'static,
+    {
+        self.complete_value = Some(Box::new(move |key, value| {
+            op(key, value)
+                .into_iter()
+                .map(|(v, help)| (v.into(), help.map(Into::into)))
+                .collect()
+        }));
+        self
+    }
+
+    /// Key and value are completed separately, depending on presence of `=`
+    #[cfg(feature = "autocomplete")]
+    fn push_completions(&self, args: &mut State, os: &OsString) {
+        let s = match os.to_str() {
+            Some(s) => s,
+            None => return,
+        };
+        let help = &self.inner.named.help;
+        let (prefix, meta, suggestions) = match s.split_once('=') {
+            None => {
+                let suggestions = self.complete_key.as_ref().map(|f| {
+                    f(s).into_iter()
+                        .map(|(k, help)| (format!("{}=", k), help))
+                        .collect()
+                });
+                (String::new(), &self.key, suggestions)
+            }
+            Some((k, v)) => {
+                let prefix = format!("{}=", k);
+                let suggestions = self.complete_value.as_ref().map(|f| {
+                    f(k, v)
+                        .into_iter()
+                        .map(|(v, help)| (format!("{}{}", prefix, v), help))
+                        .collect()
+                });
+                (prefix, &self.value, suggestions)
+            }
+        };
+        let suggestions: Vec<(String, Option<String>)> = suggestions.unwrap_or_default();
+        // same as with `complete` - strip metavar when there's a single good suggestion
+        if suggestions.len() != 1 {
+            args.push_element_metavar(meta, help, true, prefix);
+        }
+        let depth = args.depth();
+        if let Some(comp) = args.comp_mut() {
+            for (body, help) in suggestions {
+                comp.push_value(body, help, None, depth, true);
+            }
+        }
+    }
+}
+
+impl<K, V> ParseKeyValue<K, V>
+where
+    K: FromStr + 'static,
+    <K as std::str::FromStr>::Err: std::fmt::Display,
+    V: FromStr + 'static,
+    <V as std::str::FromStr>::Err: std::fmt::Display,
+{
+    /// Parse a pair, keep the original `KEY=VALUE` text for error messages
+    fn take_pair(&self, args: &mut State) -> Result<(K, V, String), Error> {
+        #[cfg(feature = "autocomplete")]
+        let mut comps = Vec::new();
+        #[cfg(feature = "autocomplete")]
+        args.swap_comps_with(&mut comps);
+
+        let os = self.inner.take_argument(args);
+
+        // argument completes the whole value, key_value replaces that with completions for
+        // either key or value
+        #[cfg(feature = "autocomplete")]
+        {
+            args.swap_comps_with(&mut comps);
+            match &os {
+                Ok(os) if args.touching_last_remove() => self.push_completions(args, os),
+                _ => {
+                    if let Some(comp) = args.comp_mut() {
+                        comp.extend_comps(comps);
+                    }
+                }
+            }
+        }
+
+        let os = os?;
+        let m = args.messages;
+        let fail = |template, parts: &[&str]| {
+            Error(Message::ParseFailed(
+                args.current_item(),
+                fill_text(template, parts),
+            ))
+        };
+        let s = match os.to_str() {
+            Some(s) => s,
+            None => return Err(fail(m.not_utf8(), &[&os.to_string_lossy()])),
+        };
+        let (k, v) = match s.split_once('=') {
+            Some(kv) => kv,
+            None => return Err(fail(m.key_value_expected(), &[&self.key.0, &self.value.0])),
+        };
+        let key = parse_os_str::<K>(OsString::from(k), &*m)
+            .map_err(|err| fail(m.key_value_invalid_key(), &[k, &err]))?;
+        let value = parse_os_str::<V>(OsString::from(v), &*m)
+            .map_err(|err| fail(m.key_value_invalid_value(), &[v, &err]))?;
+        Ok((key, value, s.to_owned()))
+    }
+}
+
+impl<K, V> Parser<(K, V)> for ParseKeyValue<K, V>
+where
+    K: FromStr + 'static,
+    <K as std::str::FromStr>::Err: std::fmt::Display,
+    V: FromStr + 'static,
+    <V as std::str::FromStr>::Err: std::fmt::Display,
+{
+    fn eval(&self, args: &mut State) -> Result<(K, V), Error> {
+        let (key, value, _) = self.take_pair(args)?;
+        Ok((key, value))
+    }
+
+    fn meta(&self) -> Meta {
+        self.inner.meta()
+    }
+}
+
+/// [`ParseKeyValue`] that also returns the original text of the pair
+struct ParsePairText<'a, K, V>(&'a ParseKeyValue<K, V>);
+
+impl<K, V> Parser<(K, V, String)> for ParsePairText<'_, K, V>
+where
+    K: FromStr + 'static,
+    <K as std::str::FromStr>::Err: std::fmt::Display,
+    V: FromStr + 'static,
+    <V as std::str::FromStr>::Err: std::fmt::Display,
+{
+    fn eval(&self, args: &mut State) -> Result<(K, V, String), Error> {
+        self.0.take_pair(args)
+    }
+
+    fn meta(&self) -> Meta {
+        self.0.meta()
+    }
+}
+
+/// What to do with a key specified more than once, see
+/// [`ParseKeyValueCollect::duplicates`]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum DuplicateKeys {
+    /// Fail with an error that mentions both pairs
+    Reject,
+    /// Keep only the last value for each key, this is the default
+    LastWins,
+    /// Pass all the pairs to the collection as is, use it with collections that can store
+    /// several values for a key, such as `Vec<(K, V)>`
+    KeepAll,
+}
+
+/// Parser that collects `KEY=VALUE` pairs into a collection, created with
+/// [`ParseKeyValue::collect`]
+pub struct ParseKeyValueCollect<K, V, M> {
+    inner: ParseKeyValue<K, V>,
+    duplicates: DuplicateKeys,
+    ty: PhantomData<M>,
+}
+
+impl<K, V, M> ParseKeyValueCollect<K, V, M> {
+    /// Change what happens when a key is specified more than once
+    ///
+    /// Keys are compared with [`PartialEq`] against every pair collected so far, so detecting
+    /// duplicates takes time quadratic in the number of pairs.
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// # use std::collections::HashMap;
+    /// let parser = long("set")
+    ///     .key_value::<String, String>("KEY", "VALUE")
+    ///     .collect::<HashMap<_, _>>()
+    ///     .duplicates(DuplicateKeys::Reject)
+    ///     .to_options();
+    ///
+    /// let r = parser
+    ///     .run_inner(&["--set", "name=Bob", "--set", "name=Bill"])
+    ///     .unwrap_err()
+    ///     .unwrap_stderr();
+    /// assert_eq!(r, "key `name` is set more than once: `name=Bob` and `name=Bill`");
+    /// ```
+    #[must_use]
+    pub fn duplicates(mut self, duplicates: DuplicateKeys) -> Self {
+        self.duplicates = duplicates;
+        self
+    }
+}
+
+impl<K, V, M> Parser<M> for ParseKeyValueCollect<K, V, M>
+where
+    K: FromStr + PartialEq + 'static,
+    <K as std::str::FromStr>::Err: std::fmt::Display,
+    V: FromStr + 'static,
+    <V as std::str::FromStr>::Err: std::fmt::Display,
+    M: FromIterator<(K, V)>,
+{
+    fn eval(&self, args: &mut State) -> Result<M, Error> {
+        let parser = ParsePairText(&self.inner);
+        // pairs together with the original text and argv index of the value, if any
+        let mut pairs: Vec<(K, V, (Current, String))> = Vec::new();
+        let mut len = usize::MAX;
+        while let Some((key, value, text)) = parse_option(&parser, &mut len, args, false)? {
+            let prev = pairs.iter().position(|(k, _, _)| *k == key);
+            let origin = (args.current_item(), text);
+            match (self.duplicates, prev) {
+                (DuplicateKeys::Reject, Some(prev)) => {
+                    let (Current(first, _), text) = pairs.swap_remove(prev).2;
+                    let first = (first, text);
+                    return Err(Error(Message::DuplicateKey(first, origin)));
+                }
+                (DuplicateKeys::LastWins, Some(prev)) => {
+                    pairs.remove(prev);
+                }
+                _ => {}
+            }
+            pairs.push((key, value, origin));
+        }
+        Ok(pairs.into_iter().map(|(k, v, _)| (k, v)).collect())
+    }
+
+    fn meta(&self) -> Meta {
+        Meta::Many(Box::new(Meta::Optional(Box::new(self.inner.meta()))))
+    }
+}
+
+/// Check that the value is one of the possible values, if they are restricted
+fn check_possible(
+    possible: &[Cow<'static, str>],
+    os: &OsString,
+    args: &State,
+) -> Result<(), Error> {
+    if possible.is_empty() || possible.iter().any(|p| os == p.as_ref()) {
+        Ok(())
+    } else {
+        let value = os.to_string_lossy().into_owned();
+        Err(Error(Message::NotPossible(
+            args.current_item(),
+            value,
+            possible.to_vec(),
+        )))
+    }
+}
+
+/// Complete a value or an element of a delimited list, `prefix` contains elements before it
+///
+/// Without restrictions on possible values this completes just the metavariable
+#[cfg(feature = "autocomplete")]
+fn push_possible_values(
+    args: &mut State,
+    possible: &[Cow<'static, str>],
+    metavar: &Metavar,
+    help: &Option<Doc>,
+    is_argument: bool,
+    prefix: &str,
+    typed: &str,
+) {
+    let matching = possible
+        .iter()
+        .filter(|p| p.starts_with(typed))
+        .collect::<Vec<_>>();
+    // same as with `complete` - strip metavar when there's a single good suggestion
+    if matching.len() != 1 {
+        args.push_element_metavar(metavar, help, is_argument, prefix.to_owned());
+    }
+    let depth = args.depth();
+    if let Some(comp) = args.comp_mut() {
+        for value in matching {
+            comp.push_value(
+                format!("{}{}", prefix, value),
+                None,
+                None,
+                depth,
+                is_argument,
+            );
         }
     }
 }
 
-pub(crate) fn build_positional<T>(metavar: &'static str) -> ParsePositional<T> {
+pub(crate) fn build_positional<T>(metavar: Metavar) -> ParsePositional<T> {
     ParsePositional {
         metavar,
         help: None,
         position: Position::Unrestricted,
+        possible: Vec::new(),
         ty: PhantomData,
     }
 }
@@ -720,9 +1832,10 @@ pub(crate) fn build_positional<T>(metavar: &'static str) -> ParsePositional<T> {
 /// [`strict`](Self::strict), or [`non_strict`](Self::non_strict) on this struct.
 #[derive(Clone)]
 pub struct ParsePositional<T> {
-    metavar: &'static str,
+    metavar: Metavar,
     help: Option<Doc>,
     position: Position,
+    possible: Vec<Cow<'static, str>>,
     ty: PhantomData<T>,
 }
 
@@ -755,6 +1868,51 @@ impl<T> ParsePositional<T> {
         self
     }
 
+    /// Replace metavariable with a custom value
+    ///
+    /// Unlike [`positional`] this method accepts names computed at runtime
+    /// ```rust
+    /// # use bpaf::*;
+    /// let meta = String::from("FILE");
+    /// let parser = positional::<String>("").metavar(meta);
+    /// ```
+    #[must_use]
+    pub fn metavar<M>(mut self, metavar: M) -> Self
+    where
+        M: IntoName,
+    {
+        self.metavar = Metavar(metavar.into_name());
+        self
+    }
+
+    /// Restrict the value to one of the listed values
+    ///
+    /// Values are checked before parsing them with [`FromStr`], help message lists them,
+    /// completion suggests them and mistyped values get a "did you mean" suggestion.
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// let parser = positional::<String>("SHELL")
+    ///     .possible_values(["bash", "zsh", "fish"])
+    ///     .to_options();
+    ///
+    /// let r = parser.run_inner(&["zhs"]).unwrap_err().unwrap_stderr();
+    /// assert_eq!(r, "`zhs` is not a valid value, did you mean `zsh`?");
+    ///
+    /// let r = parser.run_inner(&["powershell"]).unwrap_err().unwrap_stderr();
+    /// let expected = "`powershell` is not a valid value, expected one of `bash`, `zsh`, `fish`";
+    /// assert_eq!(r, expected);
+    /// ```
+    #[must_use]
+    pub fn possible_values<I, M>(mut self, values: I) -> Self
+    where
+        I: IntoIterator<Item = M>,
+        M: IntoName,
+    {
+        self.possible = values.into_iter().map(IntoName::into_name).collect();
+        self
+    }
+
     /// Changes positional parser to be a "strict" positional
     ///
     /// Usually positional items can appear anywhere on a command line:
@@ -797,8 +1955,9 @@ impl<T> ParsePositional<T> {
     #[inline(always)]
     fn meta(&self) -> Meta {
         let meta = Meta::from(Item::Positional {
-            metavar: Metavar(self.metavar),
+            metavar: self.metavar.clone(),
             help: self.help.clone(),
+            possible: self.possible.clone(),
         });
         match self.position {
             Position::Strict => Meta::Strict(Box::new(meta)),
@@ -809,9 +1968,10 @@ impl<T> ParsePositional<T> {
 
 fn parse_pos_word(
     args: &mut State,
-    metavar: Metavar,
+    metavar: &Metavar,
     help: &Option<Doc>,
     position: Position,
+    possible: &[Cow<'static, str>],
 ) -> Result<OsString, Error> {
     match args.take_positional_word(metavar) {
         Ok((ix, is_strict, word)) => {
@@ -820,12 +1980,12 @@ fn parse_pos_word(
                     if !is_strict {
                         #[cfg(feature = "autocomplete")]
                         args.push_pos_sep();
-                        return Err(Error(Message::StrictPos(ix, metavar)));
+                        return Err(Error(Message::StrictPos(ix, metavar.clone())));
                     }
                 }
                 Position::NonStrict => {
                     if is_strict {
-                        return Err(Error(Message::NonStrictPos(ix, metavar)));
+                        return Err(Error(Message::NonStrictPos(ix, metavar.clone())));
                     }
                 }
                 Position::Unrestricted => {}
@@ -833,7 +1993,8 @@ fn parse_pos_word(
 
             #[cfg(feature = "autocomplete")]
             if args.touching_last_remove() && !args.check_no_pos_ahead() {
-                args.push_metavar(metavar.0, help, false);
+                let typed = word.to_str().unwrap_or_default();
+                push_possible_values(args, possible, metavar, help, false, "", typed);
                 args.set_no_pos_ahead();
             }
             Ok(word)
@@ -841,7 +2002,7 @@ fn parse_pos_word(
         Err(err) => {
             #[cfg(feature = "autocomplete")]
             if !args.check_no_pos_ahead() {
-                args.push_metavar(metavar.0, help, false);
+                args.push_metavar(metavar, help, false);
                 args.set_no_pos_ahead();
             }
             Err(err)
@@ -855,10 +2016,17 @@ where
     <T as std::str::FromStr>::Err: std::fmt::Display,
 {
     fn eval(&self, args: &mut State) -> Result<T, Error> {
-        let os = parse_pos_word(args, Metavar(self.metavar), &self.help, self.position)?;
-        match parse_os_str::<T>(os) {
+        let os = parse_pos_word(
+            args,
+            &self.metavar,
+            &self.help,
+            self.position,
+            &self.possible,
+        )?;
+        check_possible(&self.possible, &os, args)?;
+        match parse_os_str::<T>(os, &*args.messages) {
             Ok(ok) => Ok(ok),
-            Err(err) => Err(Error(Message::ParseFailed(args.current, err))),
+            Err(err) => Err(Error(Message::ParseFailed(args.current_item(), err))),
         }
     }
 
@@ -920,7 +2088,7 @@ impl<T> Parser<T> for ParseAny<T> {
     fn eval(&self, args: &mut State) -> Result<T, Error> {
         for (ix, x) in args.items_iter() {
             let (os, next) = match x {
-                Arg::Short(_, next, os) | Arg::Long(_, next, os) => (os, *next),
+                Arg::Short(_, next, os) | Arg::Long(_, _, next, os) => (os, *next),
                 Arg::ArgWord(os) | Arg::Word(os) | Arg::PosWord(os) => (os, false),
             };
             if let Some(i) = (self.check)(os.clone()) {
@@ -0,0 +1,214 @@
+//! Splitting a string into words the same way POSIX shell does it
+//!
+//! Used by response files and [`Args::from_command_line`](crate::Args::from_command_line).
+//! Only quoting is supported: no comments, variables or globs.
+
+use std::borrow::Cow;
+
+use crate::messages::{fill_text, English, Messages};
+
+/// Problem with quoting
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub(crate) enum WordsError {
+    /// Quoted string is not closed
+    UnterminatedQuote,
+    /// Input ends with a backslash
+    DanglingEscape,
+}
+
+impl WordsError {
+    pub(crate) fn text(self, m: &dyn Messages) -> &str {
+        match self {
+            WordsError::UnterminatedQuote => m.unterminated_quote(),
+            WordsError::DanglingEscape => m.dangling_escape(),
+        }
+    }
+}
+
+/// Failure to split a command line into words, created by
+/// [`Args::from_command_line`](crate::Args::from_command_line)
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct CommandLineError {
+    pub(crate) offset: usize,
+    pub(crate) error: WordsError,
+}
+
+impl CommandLineError {
+    /// Offset of the offending quote or backslash, in characters
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Describe the problem using a message catalog, [`Display`](std::fmt::Display) uses English
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// struct German;
+    /// impl Messages for German {
+    ///     fn command_line_error(&self) -> &str {
+    ///         "{0} an Position {1}"
+    ///     }
+    ///     fn unterminated_quote(&self) -> &str {
+    ///         "nicht geschlossenes Anführungszeichen"
+    ///     }
+    /// }
+    /// let err = Args::from_command_line("--name 'Bob").err().unwrap();
+    /// assert_eq!(err.to_string(), "unterminated quote at character 7");
+    /// assert_eq!(
+    ///     err.message(&German),
+    ///     "nicht geschlossenes Anführungszeichen an Position 7"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn message(&self, messages: &dyn Messages) -> String {
+        let offset = self.offset.to_string();
+        fill_text(
+            messages.command_line_error(),
+            &[self.error.text(messages), &offset],
+        )
+    }
+}
+
+impl std::fmt::Display for CommandLineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message(&English))
+    }
+}
+
+impl std::error::Error for CommandLineError {}
+
+/// Split input into words
+///
+/// Words are separated by whitespace, single quotes preserve everything inside literally,
+/// double quotes and a backslash outside of quotes allow to escape the next character, a
+/// backslash followed by a newline is removed.
+///
+/// In `partial` mode input is something user is still typing: unterminated quotes and
+/// backslashes are accepted and trailing whitespace starts a new empty word.
+pub(crate) fn split(input: &str, partial: bool) -> Result<Vec<String>, CommandLineError> {
+    let mut words = Vec::new();
+    // current word, `None` if there's nothing to push, even an empty quoted string
+    let mut word: Option<String> = None;
+    let mut chars = input.chars().enumerate();
+
+    let fail = |offset, error| {
+        if partial {
+            Ok(())
+        } else {
+            Err(CommandLineError { offset, error })
+        }
+    };
+
+    while let Some((ix, c)) = chars.next() {
+        match c {
+            '\'' => {
+                let w = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some((_, '\'')) => break,
+                        Some((_, c)) => w.push(c),
+                        None => {
+                            fail(ix, WordsError::UnterminatedQuote)?;
+                            break;
+                        }
+                    }
+                }
+            }
+            '"' => {
+                let w = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, '\n')) => {}
+                            Some((_, c @ ('"' | '\\' | '$' | '`'))) => w.push(c),
+                            Some((_, c)) => {
+                                w.push('\\');
+                                w.push(c);
+                            }
+                            None => {
+                                fail(ix, WordsError::UnterminatedQuote)?;
+                                break;
+                            }
+                        },
+                        Some((_, c)) => w.push(c),
+                        None => {
+                            fail(ix, WordsError::UnterminatedQuote)?;
+                            break;
+                        }
+                    }
+                }
+            }
+            '\\' => match chars.next() {
+                Some((_, '\n')) => {}
+                Some((_, c)) => word.get_or_insert_with(String::new).push(c),
+                None => fail(ix, WordsError::DanglingEscape)?,
+            },
+            c if c.is_whitespace() => words.extend(word.take()),
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+
+    // a word user is about to start typing
+    if partial && word.is_none() {
+        word = Some(String::new());
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// Quote a word so [`split`] reads it back unchanged, words that need no quoting are kept as is
+pub(crate) fn quote(word: &str) -> Cow<'_, str> {
+    let plain = |c: char| c.is_alphanumeric() || "-_./=:@,+%^".contains(c);
+    if !word.is_empty() && word.chars().all(plain) {
+        return Cow::Borrowed(word);
+    }
+    Cow::Owned(format!("'{}'", word.replace('\'', "'\\''")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backslash_newline() {
+        let words = split("a\\\nb \"c\\\nd\" 'e\\\nf'", false).unwrap();
+        assert_eq!(words, ["ab", "cd", "e\\\nf"]);
+    }
+
+    #[test]
+    fn error_offsets() {
+        let err = split("--name 'Bob", false).unwrap_err();
+        assert_eq!(err.error, WordsError::UnterminatedQuote);
+        assert_eq!(err.offset, 7);
+
+        let err = split("口水鸡 \"Bob", false).unwrap_err();
+        assert_eq!(err.error, WordsError::UnterminatedQuote);
+        assert_eq!(err.offset, 4);
+
+        let err = split("--name Bob\\", false).unwrap_err();
+        assert_eq!(err.error, WordsError::DanglingEscape);
+        assert_eq!(err.offset, 10);
+    }
+
+    #[test]
+    fn partial_input() {
+        assert_eq!(split("", true).unwrap(), [""]);
+        assert_eq!(split("--name", true).unwrap(), ["--name"]);
+        assert_eq!(split("--name ", true).unwrap(), ["--name", ""]);
+        assert_eq!(split("--file 'My Doc", true).unwrap(), ["--file", "My Doc"]);
+        assert_eq!(split("--file My\\", true).unwrap(), ["--file", "My"]);
+    }
+
+    #[test]
+    fn quote_round_trip() {
+        assert_eq!(quote("--name=Bob"), "--name=Bob");
+        assert_eq!(quote(""), "''");
+        assert_eq!(quote("My Doc"), "'My Doc'");
+        assert_eq!(quote("it's"), "'it'\\''s'");
+        for word in ["", "a b", "it's", "\\", "\"x\"", "$HOME"] {
+            assert_eq!(split(&quote(word), false).unwrap(), [word]);
+        }
+    }
+}
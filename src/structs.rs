@@ -1,11 +1,17 @@
 //! Structures that implement different methods on [`Parser`] trait
 use crate::{
     args::State,
-    buffer::MetaInfo,
-    error::{Message, MissingItem},
-    Doc, Error, Meta, Parser,
+    buffer::{Block, MetaInfo, Token},
+    error::{Limit, Message, MissingItem},
+    messages::{fill, Catalog},
+    meta::Note,
+    Doc, Error, Meta, ParseFailure, Parser,
+};
+use std::{
+    ffi::{OsStr, OsString},
+    marker::PhantomData,
+    ops::{Bound, RangeBounds},
 };
-use std::marker::PhantomData;
 
 /// Parser that substitutes missing value with a function results but not parser
 /// failure, created with [`fallback_with`](Parser::fallback_with).
@@ -13,7 +19,8 @@ pub struct ParseFallbackWith<T, P, F, E> {
     pub(crate) inner: P,
     pub(crate) inner_res: PhantomData<T>,
     pub(crate) fallback: F,
-    pub(crate) value_str: String,
+    /// fallback value rendered for the help message
+    pub(crate) value_str: Option<String>,
     pub(crate) err: PhantomData<E>,
 }
 
@@ -34,6 +41,7 @@ where
                 #[cfg(feature = "autocomplete")]
                 args.swap_comps(&mut clone);
                 if e.can_catch() {
+                    args.source = Some(ValueSource::Fallback);
                     match (self.fallback)() {
                         Ok(ok) => Ok(ok),
                         Err(e) => Err(Error(Message::PureFailed(e.to_string()))),
@@ -47,15 +55,38 @@ where
 
     fn meta(&self) -> Meta {
         let m = Meta::Optional(Box::new(self.inner.meta()));
-        if self.value_str.is_empty() {
-            m
-        } else {
-            let buf = Doc::from(self.value_str.as_str());
-            Meta::Suffix(Box::new(m), Box::new(buf))
+        match &self.value_str {
+            Some(value) => Meta::Note(Box::new(m), Note::Default(value.clone())),
+            None => m,
         }
     }
 }
 
+/// Parser that describes what it does in error messages, created with [`context`](Parser::context).
+pub struct ParseContext<P> {
+    pub(crate) inner: P,
+    pub(crate) context: &'static str,
+}
+
+impl<T, P> Parser<T> for ParseContext<P>
+where
+    P: Parser<T>,
+{
+    fn eval(&self, args: &mut State) -> Result<T, Error> {
+        match self.inner.eval(args) {
+            Err(Error(err)) if !(err.can_catch() || matches!(err, Message::ParseError(_))) => {
+                let path = args.path.clone();
+                Err(Error(Message::Context(Box::new(err), self.context, path)))
+            }
+            res => res,
+        }
+    }
+
+    fn meta(&self) -> Meta {
+        self.inner.meta()
+    }
+}
+
 /// Parser with attached message to several fields, created with [`group_help`](Parser::group_help).
 pub struct ParseGroupHelp<P> {
     pub(crate) inner: P,
@@ -119,6 +150,7 @@ pub struct ParseSome<P> {
     pub(crate) inner: P,
     pub(crate) message: &'static str,
     pub(crate) catch: bool,
+    pub(crate) bounds: Bounds,
 }
 
 impl<P> ParseSome<P> {
@@ -136,6 +168,33 @@ impl<P> ParseSome<P> {
         self.catch = true;
         self
     }
+
+    #[must_use]
+    /// Require at least `min` items, no items at all still fail with the `some` message
+    ///
+    /// See [`ParseMany::at_least`]
+    pub fn at_least(mut self, min: usize) -> Self {
+        self.bounds.min = min;
+        self
+    }
+
+    #[must_use]
+    /// Accept at most `max` items
+    ///
+    /// See [`ParseMany::at_most`]
+    pub fn at_most(mut self, max: usize) -> Self {
+        self.bounds.max = Some(max);
+        self
+    }
+
+    #[must_use]
+    /// Require exactly `count` items
+    ///
+    /// See [`ParseMany::exactly`]
+    pub fn exactly(mut self, count: usize) -> Self {
+        self.bounds = Bounds::exactly(count);
+        self
+    }
 }
 
 impl<T, P> Parser<Vec<T>> for ParseSome<P>
@@ -143,22 +202,32 @@ where
     P: Parser<T>,
 {
     fn eval(&self, args: &mut State) -> Result<Vec<T>, Error> {
-        let mut res = Vec::new();
-        let mut len = usize::MAX;
-
-        while let Some(val) = parse_option(&self.inner, &mut len, args, self.catch)? {
-            res.push(val);
-        }
+        let any = Bounds {
+            min: 0,
+            ..self.bounds
+        };
+        let res: Vec<T> = any.eval(&self.inner, args, self.catch)?;
 
         if res.is_empty() {
             Err(Error(Message::ParseSome(self.message)))
+        } else if res.len() < self.bounds.min {
+            let meta = Box::new(self.inner.meta());
+            Err(Error(Message::TooFew(meta, self.bounds, res.len())))
         } else {
             Ok(res)
         }
     }
 
     fn meta(&self) -> Meta {
-        Meta::Many(Box::new(Meta::Required(Box::new(self.inner.meta()))))
+        if self.bounds.is_set() {
+            let bounds = Bounds {
+                min: self.bounds.min.max(1),
+                ..self.bounds
+            };
+            bounds.meta(self.inner.meta(), true)
+        } else {
+            Meta::Many(Box::new(Meta::Required(Box::new(self.inner.meta()))))
+        }
     }
 }
 
@@ -168,6 +237,7 @@ where
 pub struct ParseCollect<P, C, T> {
     pub(crate) inner: P,
     pub(crate) catch: bool,
+    pub(crate) bounds: Bounds,
     pub(crate) ctx: PhantomData<(C, T)>,
 }
 
@@ -186,6 +256,33 @@ impl<T, C, P> ParseCollect<P, C, T> {
         self.catch = true;
         self
     }
+
+    #[must_use]
+    /// Require at least `min` items
+    ///
+    /// See [`ParseMany::at_least`]
+    pub fn at_least(mut self, min: usize) -> Self {
+        self.bounds.min = min;
+        self
+    }
+
+    #[must_use]
+    /// Accept at most `max` items
+    ///
+    /// See [`ParseMany::at_most`]
+    pub fn at_most(mut self, max: usize) -> Self {
+        self.bounds.max = Some(max);
+        self
+    }
+
+    #[must_use]
+    /// Require exactly `count` items
+    ///
+    /// See [`ParseMany::exactly`]
+    pub fn exactly(mut self, count: usize) -> Self {
+        self.bounds = Bounds::exactly(count);
+        self
+    }
 }
 
 impl<T, C, P> Parser<C> for ParseCollect<P, C, T>
@@ -194,13 +291,15 @@ where
     C: FromIterator<T>,
 {
     fn eval(&self, args: &mut State) -> Result<C, Error> {
-        let mut len = usize::MAX;
-        std::iter::from_fn(|| parse_option(&self.inner, &mut len, args, self.catch).transpose())
-            .collect::<Result<C, Error>>()
+        self.bounds.eval(&self.inner, args, self.catch)
     }
 
     fn meta(&self) -> Meta {
-        Meta::Many(Box::new(Meta::Required(Box::new(self.inner.meta()))))
+        if self.bounds.is_set() {
+            self.bounds.meta(self.inner.meta(), true)
+        } else {
+            Meta::Many(Box::new(Meta::Required(Box::new(self.inner.meta()))))
+        }
     }
 }
 
@@ -226,7 +325,7 @@ where
 
         #[cfg(feature = "autocomplete")]
         args.swap_comps_with(&mut comps);
-        if let Err(Error(Message::Missing(_))) = res {
+        if matches!(&res, Err(Error(err)) if err.is_missing()) {
             Err(Error(Message::Missing(Vec::new())))
         } else {
             res
@@ -234,7 +333,7 @@ where
     }
 
     fn meta(&self) -> Meta {
-        Meta::Skip
+        Meta::Hidden(Box::new(self.inner.meta()))
     }
 }
 
@@ -464,7 +563,10 @@ where
         let t = self.inner.eval(args)?;
         match (self.parse_fn)(t) {
             Ok(r) => Ok(r),
-            Err(e) => Err(Error(Message::ParseFailed(args.current, e.to_string()))),
+            Err(e) => Err(Error(Message::ParseFailed(
+                args.current_item(),
+                e.to_string(),
+            ))),
         }
     }
 
@@ -478,7 +580,8 @@ where
 pub struct ParseFallback<P, T> {
     pub(crate) inner: P,
     pub(crate) value: T,
-    pub(crate) value_str: String,
+    /// fallback value rendered for the help message
+    pub(crate) value_str: Option<String>,
 }
 
 impl<P, T> Parser<T> for ParseFallback<P, T>
@@ -497,6 +600,7 @@ where
                 #[cfg(feature = "autocomplete")]
                 args.swap_comps(&mut clone);
                 if e.can_catch() {
+                    args.source = Some(ValueSource::Fallback);
                     Ok(self.value.clone())
                 } else {
                     Err(Error(e))
@@ -507,11 +611,9 @@ where
 
     fn meta(&self) -> Meta {
         let m = Meta::Optional(Box::new(self.inner.meta()));
-        if self.value_str.is_empty() {
-            m
-        } else {
-            let buf = Doc::from(self.value_str.as_str());
-            Meta::Suffix(Box::new(m), Box::new(buf))
+        match &self.value_str {
+            Some(value) => Meta::Note(Box::new(m), Note::Default(value.clone())),
+            None => m,
         }
     }
 }
@@ -523,7 +625,7 @@ impl<P, T: std::fmt::Display> ParseFallback<P, T> {
     #[cfg_attr(not(doctest), doc = include_str!("docs2/dis_fallback.md"))]
     #[must_use]
     pub fn display_fallback(mut self) -> Self {
-        self.value_str = format!("[default: {}]", self.value);
+        self.value_str = Some(self.value.to_string());
         self
     }
 }
@@ -535,7 +637,7 @@ impl<P, T: std::fmt::Debug> ParseFallback<P, T> {
     #[cfg_attr(not(doctest), doc = include_str!("docs2/deb_fallback_with.md"))]
     #[must_use]
     pub fn debug_fallback(mut self) -> Self {
-        self.value_str = format!("[default: {:?}]", self.value);
+        self.value_str = Some(format!("{:?}", self.value));
         self
     }
 }
@@ -553,7 +655,7 @@ where
     #[must_use]
     pub fn display_fallback(mut self) -> Self {
         if let Ok(val) = (self.fallback)() {
-            self.value_str = format!("[default: {}]", val);
+            self.value_str = Some(val.to_string());
         }
         self
     }
@@ -572,7 +674,7 @@ where
     #[must_use]
     pub fn debug_fallback(mut self) -> Self {
         if let Ok(val) = (self.fallback)() {
-            self.value_str = format!("[default: {:?}]", val);
+            self.value_str = Some(format!("{:?}", val));
         }
         self
     }
@@ -595,7 +697,10 @@ where
         if (self.check)(&t) {
             Ok(t)
         } else {
-            Err(Error(Message::GuardFailed(args.current, self.message)))
+            Err(Error(Message::GuardFailed(
+                args.current_item(),
+                self.message,
+            )))
         }
     }
 
@@ -604,6 +709,47 @@ where
     }
 }
 
+/// Parser fails if the value is outside of a range, created with [`range`](Parser::range).
+pub struct ParseRange<P, R> {
+    pub(crate) inner: P,
+    pub(crate) range: R,
+}
+
+impl<T, P, R> Parser<T> for ParseRange<P, R>
+where
+    P: Parser<T>,
+    T: PartialOrd + std::fmt::Display,
+    R: RangeBounds<T>,
+{
+    fn eval(&self, args: &mut State) -> Result<T, Error> {
+        let t = self.inner.eval(args)?;
+        let (expected, bound) = match (self.range.start_bound(), self.range.end_bound()) {
+            (Bound::Included(start), _) if t < *start => (Limit::AtLeast, start),
+            (Bound::Excluded(start), _) if t <= *start => (Limit::GreaterThan, start),
+            (_, Bound::Included(end)) if t > *end => (Limit::AtMost, end),
+            (_, Bound::Excluded(end)) if t >= *end => (Limit::LessThan, end),
+            _ => return Ok(t),
+        };
+        Err(Error(Message::OutOfRange(
+            args.current_item(),
+            t.to_string(),
+            expected,
+            bound.to_string(),
+        )))
+    }
+
+    fn meta(&self) -> Meta {
+        let bound = |bound: Bound<&T>| match bound {
+            Bound::Included(x) => Bound::Included(x.to_string()),
+            Bound::Excluded(x) => Bound::Excluded(x.to_string()),
+            Bound::Unbounded => Bound::Unbounded,
+        };
+        let start = bound(self.range.start_bound());
+        let end = bound(self.range.end_bound());
+        Meta::Note(Box::new(self.inner.meta()), Note::Range(start, end))
+    }
+}
+
 /// Apply inner parser as many times as it succeeds while consuming something and return this
 /// number
 pub struct ParseCount<P, T> {
@@ -715,6 +861,7 @@ impl<P> ParseOptional<P> {
 pub struct ParseMany<P> {
     pub(crate) inner: P,
     pub(crate) catch: bool,
+    pub(crate) bounds: Bounds,
 }
 
 impl<P> ParseMany<P> {
@@ -732,10 +879,122 @@ impl<P> ParseMany<P> {
         self.catch = true;
         self
     }
+
+    #[must_use]
+    /// Require at least `min` items
+    ///
+    /// Usage shows the bounds after the item: `<FILE>{2,}`
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// let parser = positional::<String>("FILE").many().at_least(2).to_options();
+    ///
+    /// let r = parser.run_inner(&["a.txt"]).unwrap_err().unwrap_stderr();
+    /// assert_eq!(r, "expected `FILE` at least 2 times, got 1");
+    /// ```
+    pub fn at_least(mut self, min: usize) -> Self {
+        self.bounds.min = min;
+        self
+    }
+
+    #[must_use]
+    /// Accept at most `max` items
+    ///
+    /// Unlike leaving extra items unconsumed, the error message points at the first extra one.
+    /// Usage shows the bounds after the item: `<FILE>{0,2}`
+    ///
+    /// ```rust
+    /// # use bpaf::*;
+    /// let parser = short('v').req_flag(()).many().at_most(2).to_options();
+    ///
+    /// let r = parser.run_inner(&["-vvv"]).unwrap_err().unwrap_stderr();
+    /// assert_eq!(r, "expected `-v` at most 2 times, `-v` is one too many");
+    /// ```
+    pub fn at_most(mut self, max: usize) -> Self {
+        self.bounds.max = Some(max);
+        self
+    }
+
+    #[must_use]
+    /// Require exactly `count` items
+    ///
+    /// A shortcut for `.at_least(count).at_most(count)`, usage shows it as `<FILE>{2}`
+    pub fn exactly(mut self, count: usize) -> Self {
+        self.bounds = Bounds::exactly(count);
+        self
+    }
+}
+
+/// Limits on a number of items set by `at_least`, `at_most` and `exactly`
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Bounds {
+    pub(crate) min: usize,
+    pub(crate) max: Option<usize>,
+}
+
+impl Bounds {
+    fn exactly(count: usize) -> Self {
+        Bounds {
+            min: count,
+            max: Some(count),
+        }
+    }
+
+    fn is_set(self) -> bool {
+        self.min > 0 || self.max.is_some()
+    }
+
+    /// `required` is the shape of the parser without bounds, `min` above zero makes it required
+    fn meta(self, inner: Meta, required: bool) -> Meta {
+        let inner = if required || self.min > 0 {
+            Meta::Required(Box::new(inner))
+        } else {
+            Meta::Optional(Box::new(inner))
+        };
+        Meta::Bounded(Box::new(inner), self.min, self.max)
+    }
+
+    /// Run parser for as long as it consumes something, but no more than `max` times
+    fn eval<P, T, C>(self, parser: &P, args: &mut State, catch: bool) -> Result<C, Error>
+    where
+        P: Parser<T>,
+        C: FromIterator<T>,
+    {
+        let mut len = usize::MAX;
+        let mut count = 0;
+        let res = std::iter::from_fn(|| {
+            if self.max == Some(count) {
+                return None;
+            }
+            let res = parse_option(parser, &mut len, args, catch).transpose();
+            count += usize::from(res.is_some());
+            res
+        })
+        .collect::<Result<C, Error>>()?;
+
+        if self.max == Some(count) {
+            // there's no more space, but if the next item is present it's better to point at it
+            let before = args.clone();
+            if parse_option(parser, &mut len, args, catch)?.is_some() {
+                if let (_, Some(extra)) = args.pick_winner(&before) {
+                    let meta = Box::new(parser.meta());
+                    return Err(Error(Message::TooMany(meta, self, extra)));
+                }
+            }
+            *args = before;
+        }
+
+        if count < self.min {
+            let meta = Box::new(parser.meta());
+            Err(Error(Message::TooFew(meta, self, count)))
+        } else {
+            Ok(res)
+        }
+    }
 }
 
 /// try to parse
-fn parse_option<P, T>(
+pub(crate) fn parse_option<P, T>(
     parser: &P,
     len: &mut usize,
     args: &mut State,
@@ -766,7 +1025,7 @@ where
             // When parser returns Ok(None) we should return the original arguments so if there's
             // anything left unconsumed - this won't be lost.
 
-            let missing = matches!(err, Message::Missing(_));
+            let missing = err.is_missing();
 
             if catch || (missing && orig_args.len() == args.len()) || (!missing && err.can_catch())
             {
@@ -788,13 +1047,15 @@ where
     P: Parser<T>,
 {
     fn eval(&self, args: &mut State) -> Result<Vec<T>, Error> {
-        let mut len = usize::MAX;
-        std::iter::from_fn(|| parse_option(&self.inner, &mut len, args, self.catch).transpose())
-            .collect::<Result<Vec<T>, Error>>()
+        self.bounds.eval(&self.inner, args, self.catch)
     }
 
     fn meta(&self) -> Meta {
-        Meta::Many(Box::new(Meta::Optional(Box::new(self.inner.meta()))))
+        if self.bounds.is_set() {
+            self.bounds.meta(self.inner.meta(), false)
+        } else {
+            Meta::Many(Box::new(Meta::Optional(Box::new(self.inner.meta()))))
+        }
     }
 }
 
@@ -804,6 +1065,8 @@ pub struct ParsePure<T>(pub(crate) T);
 impl<T: Clone + 'static> Parser<T> for ParsePure<T> {
     fn eval(&self, args: &mut State) -> Result<T, Error> {
         args.current = None;
+        args.current_name = None;
+        args.source = Some(ValueSource::Pure);
         Ok(self.0.clone())
     }
 
@@ -819,7 +1082,8 @@ where
 impl<T: Clone + 'static, F: Fn() -> Result<T, E>, E: ToString> Parser<T>
     for ParsePureWith<T, F, E>
 {
-    fn eval(&self, _args: &mut State) -> Result<T, Error> {
+    fn eval(&self, args: &mut State) -> Result<T, Error> {
+        args.source = Some(ValueSource::Pure);
         match (self.0)() {
             Ok(ok) => Ok(ok),
             Err(e) => Err(Error(Message::PureFailed(e.to_string()))),
@@ -839,6 +1103,7 @@ pub struct ParseFail<T> {
 impl<T> Parser<T> for ParseFail<T> {
     fn eval(&self, args: &mut State) -> Result<T, Error> {
         args.current = None;
+        args.current_name = None;
         Err(Error(Message::ParseFail(self.field1)))
     }
 
@@ -869,6 +1134,172 @@ where
     }
 }
 
+/// Parser that builds the next parser from a parsed value, created with
+/// [`and_then`](Parser::and_then).
+pub struct ParseAndThen<T, P, F> {
+    pub(crate) inner: P,
+    /// describes the parser `next_fn` creates for a representative value
+    pub(crate) help: Meta,
+    pub(crate) inner_res: PhantomData<T>,
+    pub(crate) next_fn: F,
+}
+
+impl<T, P, F, Q, R> Parser<R> for ParseAndThen<T, P, F>
+where
+    P: Parser<T>,
+    F: Fn(T) -> Q,
+    Q: Parser<R>,
+{
+    fn eval(&self, args: &mut State) -> Result<R, Error> {
+        let t = self.inner.eval(args)?;
+        (self.next_fn)(t).eval(args)
+    }
+
+    fn meta(&self) -> Meta {
+        Meta::And(vec![self.inner.meta(), self.help.clone()])
+    }
+}
+
+/// Parser that remembers where the value comes from, created with [`spanned`](Parser::spanned)
+pub struct ParseSpanned<P> {
+    pub(crate) inner: P,
+}
+
+impl<P, T> Parser<Spanned<T>> for ParseSpanned<P>
+where
+    P: Parser<T>,
+{
+    fn eval(&self, args: &mut State) -> Result<Spanned<T>, Error> {
+        let before = args.clone();
+        let value = self.inner.eval(args)?;
+        let (indices, os) = args.span_since(&before);
+        Ok(Spanned {
+            value,
+            indices,
+            os,
+            messages: args.messages,
+        })
+    }
+
+    fn meta(&self) -> Meta {
+        self.inner.meta()
+    }
+}
+
+/// A value together with its location on the command line, created with
+/// [`spanned`](Parser::spanned)
+#[derive(Debug, Clone)]
+pub struct Spanned<T> {
+    /// Parsed value
+    pub value: T,
+    pub(crate) indices: Vec<usize>,
+    pub(crate) os: Option<OsString>,
+    /// catalog of the parser, used by [`error`](Spanned::error)
+    pub(crate) messages: Catalog,
+}
+
+impl<T: PartialEq> PartialEq for Spanned<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value && self.indices == other.indices && self.os == other.os
+    }
+}
+
+impl<T: Eq> Eq for Spanned<T> {}
+
+impl<T> Spanned<T> {
+    /// Indices of command line arguments consumed to produce the value
+    ///
+    /// Empty if the value doesn't come from a command line
+    #[must_use]
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
+
+    /// Original text of the command line item the value was parsed from
+    #[must_use]
+    pub fn os_str(&self) -> Option<&OsStr> {
+        self.os.as_deref()
+    }
+
+    /// Extract the value, discarding the location
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+
+    /// Render an error about this value the same way `bpaf` renders its own validation errors
+    ///
+    /// Resulting [`ParseFailure`] can be printed with
+    /// [`print_message`](ParseFailure::print_message) or inspected in tests. Message uses
+    /// [`check_failed`](crate::Messages::check_failed) from the catalog of the parser.
+    #[must_use]
+    pub fn error<M>(&self, message: M) -> ParseFailure
+    where
+        M: ToString,
+    {
+        let m = self.messages;
+        let template = match self.os {
+            Some(_) => m.check_failed(),
+            None => m.check_failed_unnamed(),
+        };
+        let mut doc = Doc::default();
+        fill(&mut doc, template, |doc, n| match (n, &self.os) {
+            (0, Some(os)) => {
+                doc.token(Token::BlockStart(Block::TermRef));
+                doc.invalid(&os.to_string_lossy());
+                doc.token(Token::BlockEnd(Block::TermRef));
+            }
+            (1, _) => doc.text(&message.to_string()),
+            _ => {}
+        });
+        ParseFailure::Stderr(doc)
+    }
+}
+
+/// Parser that reports where the value comes from, created with
+/// [`with_source`](Parser::with_source)
+pub struct ParseWithSource<P> {
+    pub(crate) inner: P,
+}
+
+impl<P, T> Parser<(T, ValueSource)> for ParseWithSource<P>
+where
+    P: Parser<T>,
+{
+    fn eval(&self, args: &mut State) -> Result<(T, ValueSource), Error> {
+        // source set by the inner parser must not leak into parsers around this one
+        let outer = args.source.take();
+        let len = args.len();
+        let res = self.inner.eval(args);
+        let source = match args.source {
+            Some(source) => source,
+            None if args.len() != len => ValueSource::Argv,
+            None => ValueSource::Fallback,
+        };
+        args.source = outer;
+        Ok((res?, source))
+    }
+
+    fn meta(&self) -> Meta {
+        self.inner.meta()
+    }
+}
+
+/// Where a value comes from, created with [`with_source`](Parser::with_source)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ValueSource {
+    /// Value is parsed from the command line
+    Argv,
+    /// Value is taken from an environment variable, see [`env`](crate::NamedArg::env)
+    Env,
+    /// Value is produced by [`fallback`](Parser::fallback),
+    /// [`fallback_with`](Parser::fallback_with) or is a value parser produces for an absent
+    /// item, such as `false` for a [`switch`](crate::NamedArg::switch)
+    Fallback,
+    /// Value is produced by [`pure`](crate::pure) or [`pure_with`](crate::pure_with)
+    Pure,
+}
+
 /// Create parser from a function, [`construct!`](crate::construct!) uses it internally
 pub struct ParseCon<P> {
     /// inner parser closure
@@ -891,6 +1322,7 @@ where
     fn eval(&self, args: &mut State) -> Result<T, Error> {
         let res = (self.inner)(self.failfast, args);
         args.current = None;
+        args.current_name = None;
         res
     }
 
@@ -1017,6 +1449,7 @@ where
             for ci in comp_items {
                 let is_meta = ci.is_metavar();
                 if let Some(is_arg) = is_meta {
+                    let prefix = ci.value_prefix().to_owned();
                     let suggestions = (self.op)(&res);
                     // strip metavar when completion makes a single good suggestion
                     if suggestions.len() != 1 {
@@ -1025,7 +1458,7 @@ where
                     for (replacement, description) in suggestions {
                         let group = self.group.clone();
                         comp.push_value(
-                            replacement.into(),
+                            format!("{}{}", prefix, replacement.into()),
                             description.map(Into::into),
                             group,
                             depth,
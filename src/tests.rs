@@ -26,14 +26,14 @@ fn wtf_shenanigans_1() {
 
         let mut os_string = OsString::new();
         match i_c {
-            ArgType::Short => os_string.push("-"),
+            ArgType::Short | ArgType::Prefixed(_) => os_string.push("-"),
             ArgType::Long => os_string.push("--"),
         }
         os_string.push(&i_prefix);
         os_string.push("=");
         os_string.push(&i_suffix);
 
-        let (o_c, o_prefix, o_suffix) = split_os_argument(&os_string).unwrap();
+        let (o_c, o_prefix, o_suffix) = split_os_argument(&os_string, &[]).unwrap();
         assert_eq!(i_c, o_c);
         assert_eq!(i_prefix.to_str().unwrap(), o_prefix);
         assert_eq!(Arg::ArgWord(i_suffix), o_suffix.unwrap());
@@ -55,19 +55,19 @@ fn wtf_shenanigans_2() {
 
         let mut os_string = OsString::new();
         match i_c {
-            ArgType::Short => os_string.push("-"),
+            ArgType::Short | ArgType::Prefixed(_) => os_string.push("-"),
             ArgType::Long => os_string.push("--"),
         }
         os_string.push(&i_prefix);
         os_string.push("=");
         os_string.push(&i_suffix);
 
-        let (o_c, o_prefix, o_suffix) = split_os_argument(&os_string).unwrap();
+        let (o_c, o_prefix, o_suffix) = split_os_argument(&os_string, &[]).unwrap();
         assert_eq!(i_c, o_c);
         assert_eq!(i_prefix.to_str().unwrap(), o_prefix);
         assert_eq!(Arg::ArgWord(i_suffix.clone()), o_suffix.unwrap());
 
-        let (o_c, o_prefix, o_suffix) = split_os_argument_fallback(&os_string).unwrap();
+        let (o_c, o_prefix, o_suffix) = split_os_argument_fallback(&os_string, &[]).unwrap();
         assert_eq!(i_c, o_c);
         assert_eq!(i_prefix.to_str().unwrap(), o_prefix);
         assert_eq!(Arg::ArgWord(i_suffix.clone()), o_suffix.unwrap());
@@ -78,11 +78,55 @@ fn wtf_shenanigans_2() {
 fn fallback_with_strange_args_produces_same_results() {
     use crate::args::{split_os_argument, split_os_argument_fallback};
     let s = std::ffi::OsString::from("-Obits=2048");
-    let r1 = split_os_argument(&s);
-    let r2 = split_os_argument_fallback(&s);
+    let r1 = split_os_argument(&s, &[]);
+    let r2 = split_os_argument_fallback(&s, &[]);
     assert_eq!(r1, r2);
 }
 
+#[test]
+fn prefixed_names_split() {
+    use crate::args::{split_os_argument, split_os_argument_fallback, ArgType};
+    use crate::item::NamePrefix;
+    use std::ffi::OsString;
+
+    let declared = [
+        (NamePrefix::SingleDash, "name"),
+        (NamePrefix::Plus, "e"),
+        (NamePrefix::Slash, "opt"),
+    ];
+    let single = ArgType::Prefixed(NamePrefix::SingleDash);
+    for (input, expected) in [
+        ("-name", Some((single, "name", None))),
+        ("-name=*.rs", Some((single, "name", Some("*.rs")))),
+        ("-nam", Some((ArgType::Short, "nam", None))),
+        ("-nam=x", Some((ArgType::Short, "n", Some("am=x")))),
+        ("+e", Some((ArgType::Prefixed(NamePrefix::Plus), "e", None))),
+        ("+x", None),
+        (
+            "/opt=1",
+            Some((ArgType::Prefixed(NamePrefix::Slash), "opt", Some("1"))),
+        ),
+        ("/opt/bin", None),
+        ("/", None),
+    ] {
+        let os = OsString::from(input);
+        let expected = expected.map(|(ty, name, word): (_, &str, Option<&str>)| {
+            (
+                ty,
+                name.to_owned(),
+                word.map(|w| Arg::ArgWord(OsString::from(w))),
+            )
+        });
+        assert_eq!(split_os_argument(&os, &declared), expected, "{}", input);
+        assert_eq!(
+            split_os_argument_fallback(&os, &declared),
+            expected,
+            "{}",
+            input
+        );
+    }
+}
+
 #[test]
 fn de_yoda() {
     use bpaf::*;
@@ -1583,3 +1583,388 @@ fn positionals_with_no_completions_are_not_duplicated() {
 \tBETA\t\tBeta argument\n\n"
     );
 }
+
+#[test]
+fn abbreviated_long_names() {
+    let verbose = long("verbose").help("Verbose").switch();
+    let verify = long("verify").help("Verify").switch();
+    let parser = construct!(verbose, verify)
+        .to_options()
+        .allow_abbreviations();
+
+    let r = parser
+        .run_inner(Args::from(&["--ve"]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(
+        r,
+        "--verbose\t--verbose\t\tVerbose\n--verify\t--verify\t\tVerify\n\n"
+    );
+
+    let r = parser
+        .run_inner(Args::from(&["--ve", "--verb"]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(r, "--verbose");
+}
+
+#[test]
+fn single_dash_long_names() {
+    let name = long("name")
+        .single_dash()
+        .help("Pattern")
+        .argument::<String>("PATTERN")
+        .complete(|_| vec![("*.rs", None)]);
+    let newer = long("newer").single_dash().help("Newer").switch();
+    let parser = construct!(name, newer).to_options();
+
+    let r = parser
+        .run_inner(Args::from(&["-n"]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(
+        r,
+        "-name\t-name=PATTERN\t\tPattern\n-newer\t-newer\t\tNewer\n\n"
+    );
+
+    let r = parser
+        .run_inner(Args::from(&["-na"]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(r, "-name");
+
+    let r = parser
+        .run_inner(Args::from(&["-name="]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(r, "-name=*.rs");
+
+    let r = parser
+        .run_inner(Args::from(&["--na"]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(r, "--na\n");
+}
+
+#[test]
+fn plus_prefixed_names() {
+    let backing = long("backing")
+        .prefix(NamePrefix::Plus)
+        .help("Enable backing")
+        .switch();
+    let bell = long("bell").prefix(NamePrefix::Plus).help("Bell").switch();
+    let parser = construct!(backing, bell).to_options();
+
+    let r = parser
+        .run_inner(Args::from(&["+b"]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(
+        r,
+        "+backing\t+backing\t\tEnable backing\n+bell\t+bell\t\tBell\n\n"
+    );
+
+    let r = parser
+        .run_inner(Args::from(&["+ba"]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(r, "+backing");
+}
+
+#[test]
+fn partial_command_line() {
+    let name = long("name").help("Name").argument::<String>("NAME");
+    let file = long("file")
+        .help("File")
+        .argument::<String>("FILE")
+        .complete(|s: &String| vec![(format!("{}.txt", s), None)]);
+    let parser = construct!(name, file).to_options();
+
+    let r = parser
+        .run_inner(Args::from_partial_command_line("--name Bob --fi").set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(r, "--file");
+
+    let r = parser
+        .run_inner(Args::from_partial_command_line("--file 'My Doc").set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(r, "My Doc.txt");
+
+    let r = parser
+        .run_inner(Args::from_partial_command_line("--name 'Bob B' ").set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(r, "--file");
+}
+
+#[test]
+fn negatable_switch() {
+    let color = long("color").help("Use colors").negatable_switch();
+    let parser = color.to_options();
+
+    let r = parser
+        .run_inner(Args::from(&[""]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(
+        r,
+        "--color\t--color\t\tUse colors\n--no-color\t--no-color\t\tUse colors\n\n"
+    );
+
+    let r = parser
+        .run_inner(Args::from(&["--no"]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(r, "--no-color");
+
+    // same as a regular switch: once consumed it is not offered again
+    let r = parser
+        .run_inner(Args::from(&["--color", ""]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(r, "\n");
+
+    let r = parser
+        .run_inner(Args::from(&["--colo"]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(r, "--color");
+}
+
+#[test]
+fn delimited_argument() {
+    let ports = long("ports")
+        .help("Ports")
+        .argument::<String>("PORT")
+        .delimited(',')
+        .complete(|xs: &Vec<String>| {
+            let last = xs.last().map_or("", String::as_str);
+            ["80", "443", "8080"]
+                .iter()
+                .filter(|p| p.starts_with(last))
+                .map(|p| (*p, None))
+                .collect::<Vec<_>>()
+        });
+    let parser = ports.to_options();
+
+    let r = parser
+        .run_inner(Args::from(&["--ports", "44"]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(r, "443");
+
+    let r = parser
+        .run_inner(Args::from(&["--ports", "443,8"]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(
+        r,
+        "\tPORT\t\tPorts\n443,80\t443,80\t\t\n443,8080\t443,8080\t\t\n\n"
+    );
+
+    let r = parser
+        .run_inner(Args::from(&["--ports=80,44"]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(r, "--ports=80,443");
+}
+
+#[test]
+fn key_value_argument() {
+    let parser = long("set")
+        .help("Set a variable")
+        .key_value::<String, String>("KEY", "VALUE")
+        .complete_key(|k| {
+            ["level", "name"]
+                .iter()
+                .filter(|n| n.starts_with(k))
+                .map(|n| (*n, None))
+                .collect()
+        })
+        .complete_value(|k, v| match k {
+            "level" => ["high", "low"]
+                .iter()
+                .filter(|l| l.starts_with(v))
+                .map(|l| (*l, None))
+                .collect(),
+            _ => Vec::new(),
+        })
+        .collect::<Vec<_>>()
+        .to_options();
+
+    let r = parser
+        .run_inner(Args::from(&["--set", ""]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(
+        r,
+        "\tKEY\t\tSet a variable\nlevel=\tlevel=\t\t\nname=\tname=\t\t\n\n"
+    );
+
+    let r = parser
+        .run_inner(Args::from(&["--set", "na"]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(r, "name=");
+
+    let r = parser
+        .run_inner(Args::from(&["--set", "level=h"]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(r, "level=high");
+
+    let r = parser
+        .run_inner(Args::from(&["--set", "name="]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(r, "\tVALUE\t\tSet a variable\n\n");
+
+    let r = parser
+        .run_inner(Args::from(&["--set=level=l"]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(r, "--set=level=low");
+}
+
+#[test]
+fn possible_values() {
+    let mode = long("mode")
+        .help("Processing mode")
+        .argument::<String>("MODE")
+        .possible_values(["fast", "faster", "slow"]);
+    let tags = long("tags")
+        .argument::<String>("TAG")
+        .possible_values(["red", "green"])
+        .delimited(',')
+        .optional();
+    let shell = positional::<String>("SHELL").possible_values(["bash", "zsh"]);
+    let parser = construct!(mode, tags, shell).to_options();
+
+    let r = parser
+        .run_inner(Args::from(&["--mode", "s"]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(r, "slow");
+
+    let r = parser
+        .run_inner(Args::from(&["--mode", "fa"]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(
+        r,
+        "\tMODE\t\tProcessing mode\nfast\tfast\t\t\nfaster\tfaster\t\t\n\n"
+    );
+
+    let r = parser
+        .run_inner(Args::from(&["--tags", "red,g"]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(r, "red,green");
+
+    let r = parser
+        .run_inner(Args::from(&["--mode", "fast", "z"]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(r, "zsh");
+}
+
+#[test]
+fn deprecated_items_are_skipped() {
+    let jobs = long("jobs").argument::<u32>("N").optional();
+    let threads = long("threads")
+        .deprecated("use --jobs instead")
+        .argument::<u32>("N")
+        .optional();
+    let verbose = long("verbose").switch();
+    let verbose_old = long("verbosity").deprecated("use --verbose").switch();
+    let parser = construct!(jobs, threads, verbose, verbose_old).to_options();
+
+    let r = parser
+        .run_inner(Args::from(&["--"]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(r, "--jobs\t--jobs=N\t\t\n--verbose\t--verbose\t\t\n\n");
+
+    let r = parser
+        .run_inner(Args::from(&["--ver"]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(r, "--verbose");
+}
+
+#[test]
+fn visible_aliases_are_completed() {
+    let color = long("color").visible_alias("colour").switch();
+    let jobs = long("jobs")
+        .alias("threads")
+        .argument::<u32>("N")
+        .optional();
+    let remove = pure(())
+        .to_options()
+        .command("remove")
+        .visible_alias("rm")
+        .alias("del");
+    let parser = construct!(color, jobs, remove).to_options();
+
+    let r = parser
+        .run_inner(Args::from(&["--col"]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(r, "--color\t--color\t\t\n--colour\t--colour\t\t\n\n");
+
+    let r = parser
+        .run_inner(Args::from(&["--t"]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(r, "--t\n");
+
+    let r = parser
+        .run_inner(Args::from(&["r"]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(r, "remove\tremove\t\t\nrm\trm\t\t\n\n");
+}
+
+#[test]
+fn and_then_completion_uses_parsed_value() {
+    let package = long("package").argument::<String>("PKG").optional();
+    let parser = package
+        .and_then(None, |package| {
+            let bins = match package.as_deref() {
+                Some("client") => vec!["cli", "gui"],
+                _ => vec!["cli", "gui", "srv"],
+            };
+            long("bin").argument::<String>("BIN").possible_values(bins)
+        })
+        .to_options();
+
+    let r = parser
+        .run_inner(Args::from(&["--bin", ""]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(r, "\tBIN\t\t\ncli\tcli\t\t\ngui\tgui\t\t\nsrv\tsrv\t\t\n\n");
+
+    let r = parser
+        .run_inner(Args::from(&["--package", "client", "--bin", ""]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(r, "\tBIN\t\t\ncli\tcli\t\t\ngui\tgui\t\t\n\n");
+}
+
+#[test]
+fn completion_exit_code() {
+    let parser = short('a')
+        .switch()
+        .to_options()
+        .exit_codes(|kind| match kind {
+            ErrorKind::Completion => 0,
+            _ => 2,
+        });
+    let err = parser
+        .run_structured(Args::from(&[""]).set_comp(0))
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Completion);
+    assert_eq!(parser.exit_code(err.kind()), 0);
+}
@@ -123,3 +123,51 @@ Available commands:
     let r = parser.run_inner(&["one"]).unwrap();
     assert_eq!(r, One);
 }
+
+#[test]
+fn negatable_switch() {
+    #[derive(Bpaf, Debug, Clone)]
+    #[bpaf(options)]
+    struct Opts {
+        /// Use colors
+        #[bpaf(short, long, negatable_switch)]
+        color: bool,
+    }
+
+    let parser = opts();
+    assert!(parser.run_inner(&["-c"]).unwrap().color);
+    assert!(!parser.run_inner(&["--color", "--no-color"]).unwrap().color);
+
+    let help = parser.run_inner(&["--help"]).unwrap_err().unwrap_stdout();
+    let expected = "\
+Usage: [-c]
+
+Available options:
+    -c, --[no-]color  Use colors
+    -h, --help        Prints help information
+";
+    assert_eq!(help, expected);
+}
+
+#[test]
+fn with_source() {
+    use bpaf::ValueSource;
+
+    #[derive(Bpaf, Debug, Clone)]
+    #[bpaf(options)]
+    struct Opts {
+        #[bpaf(argument("N"), fallback(4), with_source)]
+        jobs: (u32, ValueSource),
+        #[bpaf(with_source)]
+        name: (Option<String>, ValueSource),
+    }
+
+    let parser = opts();
+    let r = parser.run_inner(&["--jobs", "8"]).unwrap();
+    assert_eq!(r.jobs, (8, ValueSource::Argv));
+    assert_eq!(r.name, (None, ValueSource::Fallback));
+
+    let r = parser.run_inner(&["--name", "Bob"]).unwrap();
+    assert_eq!(r.jobs, (4, ValueSource::Fallback));
+    assert_eq!(r.name, (Some("Bob".to_owned()), ValueSource::Argv));
+}
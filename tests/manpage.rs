@@ -128,3 +128,48 @@ fn very_nested() {
     #[cfg(unix)]
     assert!(write_updated(&roff, "tests/very_nested.1").unwrap());
 }
+
+struct German;
+impl Messages for German {
+    fn manpage_name(&self) -> &str {
+        "BEZEICHNUNG"
+    }
+    fn manpage_synopsis(&self) -> &str {
+        "ÜBERSICHT"
+    }
+    fn available_options(&self) -> &str {
+        "Verfügbare Optionen:"
+    }
+    fn help_flag(&self) -> &str {
+        "Hilfe anzeigen"
+    }
+}
+
+#[test]
+fn localised() {
+    let options = short('d')
+        .help("dragon")
+        .switch()
+        .to_options()
+        .descr("I am a program")
+        .messages(&German);
+    let roff = options.render_manpage("app", Section::General, None, None, None);
+    assert!(roff.contains(".SH BEZEICHNUNG\n"));
+    assert!(roff.contains(".SH ÜBERSICHT\n"));
+    assert!(roff.contains(".SS VERFÜGBARE\\ OPTIONEN:\n"));
+    assert!(roff.contains("Hilfe anzeigen"));
+    assert!(!roff.contains("SYNOPSIS"));
+}
+
+#[test]
+fn range_constraint() {
+    let jobs = long("jobs")
+        .help("Number of parallel jobs")
+        .argument::<u32>("N")
+        .range(1..=64);
+    let nice = long("nice").argument::<i32>("N").range(..20);
+    let options = construct!(jobs, nice).to_options();
+    let roff = options.render_manpage("app", Section::General, None, None, None);
+    assert!(roff.contains("\\fRNumber of parallel jobs\\fP\\fR [1..=64]\\fP\n"));
+    assert!(roff.contains("\\fR[..20]\\fP\n"));
+}
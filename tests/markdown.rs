@@ -254,3 +254,60 @@ Available options:
 
     assert_eq!(r, expected);
 }
+
+#[test]
+fn range_constraint() {
+    let jobs = long("jobs")
+        .help("Number of parallel jobs")
+        .argument::<u32>("N")
+        .range(1..=64);
+    let size = long("size").argument::<u32>("S").range(1..);
+    let opts = construct!(jobs, size).to_options();
+    let r = opts.render_markdown("ml");
+
+    let expected = "\
+# ml
+
+**Usage**: **`ml`** **`--jobs`**=_`N`_ **`--size`**=_`S`_
+
+**Available options:**
+- **`    --jobs`**=_`N`_ &mdash; \n  Number of parallel jobs [1..=64]
+- **`    --size`**=_`S`_ &mdash; \n  [1..]
+- **`-h`**, **`--help`** &mdash; \n  Prints help information
+
+
+";
+    assert_eq!(r, expected);
+}
+
+struct German;
+impl Messages for German {
+    fn usage(&self) -> &str {
+        "Verwendung"
+    }
+    fn available_options(&self) -> &str {
+        "Verfügbare Optionen:"
+    }
+    fn command_summary(&self) -> &str {
+        "Befehlsübersicht"
+    }
+    fn help_flag(&self) -> &str {
+        "Hilfe anzeigen"
+    }
+}
+
+#[test]
+fn localised() {
+    let serve = short('p')
+        .argument::<u16>("PORT")
+        .to_options()
+        .command("serve");
+    let options = serve.to_options().messages(&German);
+    let md = options.render_markdown("app");
+    assert!(md.contains("Befehlsübersicht"));
+    assert!(md.contains("**Verwendung**: **`app`** _`COMMAND ...`_"));
+    assert!(md.contains("**Verwendung**: **`app`** **`serve`** **`-p`**=_`PORT`_"));
+    assert!(md.contains("**Verfügbare Optionen:**"));
+    assert!(md.contains("Hilfe anzeigen"));
+    assert!(!md.contains("Available options"));
+}
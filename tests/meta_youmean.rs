@@ -307,3 +307,52 @@ fn pure_conflict() {
     let r = parser.run_inner(&["-a"]).unwrap();
     assert!(r);
 }
+
+#[test]
+fn no_typo_fixes_for_abbreviations() {
+    let verbase = long("verbase").switch();
+    let verbose = long("verbose")
+        .switch()
+        .to_options()
+        .command("fast")
+        .to_options()
+        .command("run");
+    let parser = construct!(verbase, verbose)
+        .to_options()
+        .allow_abbreviations();
+
+    // names of subcommands are not abbreviated at the top level
+    let r = parser.run_inner(&["--verbo"]).unwrap_err().unwrap_stderr();
+    let expected = "no such flag: `--verbo`, did you mean `--verbase`?";
+    assert_eq!(r, expected);
+
+    let r = parser
+        .run_inner(&["--verba", "run", "fast", "--verbo"])
+        .unwrap();
+    assert_eq!(r, (true, true));
+}
+
+#[test]
+fn abbreviations_are_per_command() {
+    let verbose = long("verbose").switch();
+    let name = long("name").argument::<String>("NAME").optional();
+    let verbatim = long("verbatim").switch();
+    let cmd = verbatim.to_options().command("build").optional();
+    let parser = construct!(verbose, name, cmd)
+        .to_options()
+        .allow_abbreviations();
+
+    let r = parser.run_inner(&["--verb"]).unwrap();
+    assert_eq!(r, (true, None, None));
+
+    let r = parser.run_inner(&["build", "--verb"]).unwrap();
+    assert_eq!(r, (false, None, Some(true)));
+
+    // parent names are used if the command doesn't have a match
+    let r = parser.run_inner(&["build", "--na", "x"]).unwrap();
+    assert_eq!(r, (false, Some("x".to_owned()), Some(false)));
+
+    // argument value that looks like a command doesn't switch to the command names
+    let r = parser.run_inner(&["--name", "build", "--verb"]).unwrap();
+    assert_eq!(r, (true, Some("build".to_owned()), None));
+}
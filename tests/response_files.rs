@@ -0,0 +1,231 @@
+use bpaf::*;
+use std::{
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+fn s(s: &str) -> String {
+    s.to_owned()
+}
+
+/// Fresh directory for response files, tests and test runs can go in parallel
+fn temp_dir() -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let dir = std::env::temp_dir().join(format!("bpaf_rf_{}_{}", std::process::id(), n));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Write a response file into a directory of its own
+fn response_file(name: &str, contents: &str) -> String {
+    let path = temp_dir().join(name);
+    std::fs::write(&path, contents).unwrap();
+    path.to_str().unwrap().to_owned()
+}
+
+fn parser() -> OptionParser<(bool, Option<String>, Vec<String>)> {
+    let verbose = short('v').switch();
+    let name = long("name").argument::<String>("NAME").optional();
+    let files = positional::<String>("FILE").many();
+    construct!(verbose, name, files)
+        .to_options()
+        .expand_response_files()
+}
+
+#[test]
+fn response_file_basic() {
+    let path = response_file(
+        "rf_basic.txt",
+        "-v\n\n--name 'Bob Smith'  # not a comment\n",
+    );
+
+    let r = parser()
+        .run_inner(&[s("a"), format!("@{}", path), s("b")][..])
+        .unwrap();
+    assert_eq!(
+        r,
+        (
+            true,
+            Some("Bob Smith".to_owned()),
+            vec![
+                "a".to_owned(),
+                "#".to_owned(),
+                "not".to_owned(),
+                "a".to_owned(),
+                "comment".to_owned(),
+                "b".to_owned()
+            ]
+        )
+    );
+}
+
+#[test]
+fn response_file_disabled_by_default() {
+    let path = response_file("rf_disabled.txt", "-v");
+    let arg = format!("@{}", path);
+    let parser = positional::<String>("FILE").to_options();
+
+    let r = parser.run_inner(&[arg.clone()][..]).unwrap();
+    assert_eq!(r, arg);
+}
+
+#[test]
+fn response_file_nested() {
+    let inner = response_file("rf_nested_inner.txt", "--name inner");
+    let outer = response_file("rf_nested_outer.txt", &format!("x\n@{}\ny", inner));
+
+    let r = parser().run_inner(&[format!("@{}", outer)][..]).unwrap();
+    assert_eq!(
+        r,
+        (
+            false,
+            Some("inner".to_owned()),
+            vec!["x".to_owned(), "y".to_owned()]
+        )
+    );
+}
+
+#[test]
+fn response_file_nested_relative() {
+    let dir = temp_dir();
+    std::fs::create_dir_all(dir.join("sub")).unwrap();
+    std::fs::write(dir.join("sub").join("inner.txt"), "--name inner").unwrap();
+    std::fs::write(dir.join("outer.txt"), "x\n@sub/inner.txt").unwrap();
+    let outer = dir.join("outer.txt");
+
+    // nested path is relative to the outer file, not to the current directory
+    let r = parser()
+        .run_inner(&[format!("@{}", outer.to_str().unwrap())][..])
+        .unwrap();
+    assert_eq!(r, (false, Some(s("inner")), vec![s("x")]));
+}
+
+#[test]
+fn response_file_cycle() {
+    let dir = temp_dir();
+    let a = dir.join("a.txt");
+    let a = a.to_str().unwrap();
+    std::fs::write(a, "-v\n@a.txt").unwrap();
+
+    let r = parser()
+        .run_inner(&[format!("@{}", a)][..])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(r, format!("{}:2: response file `a.txt` includes itself", a));
+}
+
+#[test]
+fn response_file_indirect_cycle() {
+    let dir = temp_dir();
+    let a = dir.join("a.txt");
+    std::fs::write(&a, "-v\n@b.txt").unwrap();
+    std::fs::write(dir.join("b.txt"), "\n\n@a.txt").unwrap();
+
+    let r = parser()
+        .run_inner(&[format!("@{}", a.to_str().unwrap())][..])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(
+        r,
+        "b.txt:3: response file `a.txt` includes itself through `b.txt`"
+    );
+}
+
+#[test]
+fn response_file_missing() {
+    let path = temp_dir().join("does_not_exist.txt");
+    let path = path.to_str().unwrap();
+
+    let r = parser()
+        .run_inner(&[format!("@{}", path)][..])
+        .unwrap_err()
+        .unwrap_stderr();
+    let expected = format!("couldn't read response file `{}`: ", path);
+    assert!(r.starts_with(&expected), "{}", r);
+}
+
+#[test]
+fn response_file_bad_quote() {
+    let path = response_file("rf_bad_quote.txt", "-v\n--name \"Bob\n");
+
+    let r = parser()
+        .run_inner(&[format!("@{}", path)][..])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(r, format!("{}:2: unterminated quote", path));
+}
+
+#[test]
+fn response_file_after_double_dash() {
+    let path = response_file("rf_double_dash.txt", "-v");
+    let arg = format!("@{}", path);
+
+    let r = parser().run_inner(&[s("--"), arg.clone()][..]).unwrap();
+    assert_eq!(r, (false, None, vec![arg]));
+}
+
+#[test]
+fn response_file_lone_at() {
+    let r = parser().run_inner(&["@"]).unwrap();
+    assert_eq!(r, (false, None, vec!["@".to_owned()]));
+}
+
+#[cfg(feature = "autocomplete")]
+#[test]
+fn response_file_completion() {
+    let path = response_file("rf_completion.txt", "-v");
+
+    let r = parser()
+        .run_inner(Args::from(&[format!("@{}", path), s("--n")][..]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(r, "--name");
+
+    // broken response file doesn't prevent completion
+    let r = parser()
+        .run_inner(Args::from(&["@/bpaf/does/not/exist", "--n"]).set_comp(0))
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(r, "--name");
+}
+
+#[test]
+fn response_file_spans() {
+    let path = response_file("rf_spans.txt", "-v\n--name bob");
+    let verbose = short('v').switch();
+    let name = long("name").argument::<String>("NAME").spanned();
+    let parser = construct!(verbose, name)
+        .to_options()
+        .expand_response_files();
+
+    // items from a response file point to the @path argument
+    let (verbose, name) = parser.run_inner(&[format!("@{}", path)][..]).unwrap();
+    assert!(verbose);
+    assert_eq!(name.value, "bob");
+    assert_eq!(name.indices(), &[0]);
+}
+
+#[test]
+fn response_file_localised() {
+    struct German;
+    impl Messages for German {
+        fn response_file_location(&self) -> &str {
+            "{0}, Zeile {1}: {2}"
+        }
+        fn unterminated_quote(&self) -> &str {
+            "nicht geschlossenes Anführungszeichen"
+        }
+    }
+
+    let path = response_file("rf_localised.txt", "-v\n--name \"Bob\n");
+    let r = parser()
+        .messages(&German)
+        .run_inner(&[format!("@{}", path)][..])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(
+        r,
+        format!("{}, Zeile 2: nicht geschlossenes Anführungszeichen", path)
+    );
+}
@@ -1827,3 +1827,1847 @@ fn flag_like_commands() {
         "Usage: --add -a\n\nAvailable options:\n    -a\n    -h, --help  Prints help information\n";
     assert_eq!(r, expected);
 }
+
+#[test]
+fn names_computed_at_runtime() {
+    let names = ["speed", "dir", "run", "DIR", "BPAF_SPEED"].map(String::from);
+    let [speed, dir, run, meta, env_name] = names;
+    let speed = long(speed).env(env_name).argument::<u32>("").metavar("N");
+    let dir = long(dir).argument::<String>("").metavar(meta.clone());
+    let file = positional::<String>("").metavar(meta);
+    let parser = construct!(speed, dir, file)
+        .to_options()
+        .command(run)
+        .to_options();
+
+    let r = parser
+        .run_inner(&["run", "--speed", "12", "--dir", "a", "b"])
+        .unwrap();
+    assert_eq!(r, (12, "a".to_owned(), "b".to_owned()));
+
+    let r = parser
+        .run_inner(&["run", "--help"])
+        .unwrap_err()
+        .unwrap_stdout();
+    let expected = "Usage: run --speed=N --dir=DIR DIR\n\nAvailable options:\n        --speed=N  [env:BPAF_SPEED: N/A]\n        --dir=DIR\n    -h, --help     Prints help information\n";
+    assert_eq!(r, expected);
+
+    let r = parser
+        .run_inner(&["run", "--sped", "12"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(r, "no such flag: `--sped`, did you mean `--speed`?");
+
+    let (key, value) = (String::from("NAME"), String::from("VAL"));
+    let parser = short('D')
+        .key_value::<String, u32>("", "")
+        .metavar(key, value)
+        .to_options();
+    let r = parser.run_inner(&["--help"]).unwrap_err().unwrap_stdout();
+    assert!(r.starts_with("Usage: -D=<NAME=VAL>\n"), "{}", r);
+}
+
+#[test]
+fn names_from_static_slice() {
+    let flags = ["apple", "banana"]
+        .iter()
+        .map(|name| long(name).req_flag(*name).boxed());
+    let parser = choice(flags).to_options();
+
+    let r = parser.run_inner(&["--banana"]).unwrap();
+    assert_eq!(r, "banana");
+}
+
+#[test]
+fn long_name_abbreviations() {
+    let verbose = long("verbose").switch();
+    let verify = long("verify").switch();
+    let name = long("name").argument::<String>("NAME").optional();
+    let parser = construct!(verbose, verify, name).to_options();
+
+    let r = parser.run_inner(&["--verb"]).unwrap_err().unwrap_stderr();
+    assert_eq!(r, "no such flag: `--verb`, did you mean `--verbose`?");
+
+    let parser = parser.allow_abbreviations();
+
+    let r = parser.run_inner(&["--verb", "--verif"]).unwrap();
+    assert_eq!(r, (true, true, None));
+
+    let r = parser.run_inner(&["--na=Bob"]).unwrap();
+    assert_eq!(r, (false, false, Some("Bob".to_owned())));
+
+    let r = parser.run_inner(&["--n", "Bob", "--verbose"]).unwrap();
+    assert_eq!(r, (true, false, Some("Bob".to_owned())));
+
+    let r = parser.run_inner(&["--ve"]).unwrap_err().unwrap_stderr();
+    assert_eq!(
+        r,
+        "`--ve` is ambiguous, it can be `--verbose` or `--verify`"
+    );
+
+    let r = parser.run_inner(&["--he"]).unwrap_err().unwrap_stdout();
+    assert!(r.starts_with("Usage: "));
+
+    // strictly positional items are left alone
+    let r = parser
+        .run_inner(&["--", "--verb"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(r, "`--verb` is not expected in this context");
+}
+
+#[test]
+fn long_name_abbreviations_exact_match() {
+    let a = long("verb").switch();
+    let b = long("verbose").switch();
+    let parser = construct!(a, b).to_options().allow_abbreviations();
+
+    let r = parser.run_inner(&["--verb"]).unwrap();
+    assert_eq!(r, (true, false));
+
+    let r = parser.run_inner(&["--verbo"]).unwrap();
+    assert_eq!(r, (false, true));
+
+    let r = parser.run_inner(&["--ver"]).unwrap_err().unwrap_stderr();
+    assert_eq!(r, "`--ver` is ambiguous, it can be `--verb` or `--verbose`");
+}
+
+#[test]
+fn long_name_abbreviations_with_aliases() {
+    let color = long("color").visible_alias("colour").switch();
+    let columns = long("columns").argument::<usize>("N").optional();
+    let parser = construct!(color, columns)
+        .to_options()
+        .allow_abbreviations();
+
+    let r = parser.run_inner(&["--colo"]).unwrap();
+    assert_eq!(r, (true, None));
+
+    let r = parser.run_inner(&["--colu", "3"]).unwrap();
+    assert_eq!(r, (false, Some(3)));
+
+    let r = parser.run_inner(&["--col"]).unwrap_err().unwrap_stderr();
+    assert_eq!(
+        r,
+        "`--col` is ambiguous, it can be `--color`, `--colour` or `--columns`"
+    );
+
+    let parser = long("color")
+        .visible_alias("colour")
+        .switch()
+        .to_options()
+        .allow_abbreviations();
+    assert!(parser.run_inner(&["--col"]).unwrap());
+}
+
+#[test]
+fn single_dash_long_names() {
+    let name = long("name")
+        .single_dash()
+        .help("file name pattern")
+        .argument::<String>("PATTERN")
+        .optional();
+    let print = long("print").single_dash().switch();
+    let flags = short('n')
+        .switch()
+        .many()
+        .map(|xs| xs.into_iter().filter(|x| *x).count());
+    let parser = construct!(name, print, flags).to_options();
+
+    let r = parser.run_inner(&["-name", "*.rs", "-print"]).unwrap();
+    assert_eq!(r, (Some("*.rs".to_owned()), true, 0));
+
+    let r = parser.run_inner(&["-name=*.rs"]).unwrap();
+    assert_eq!(r, (Some("*.rs".to_owned()), false, 0));
+
+    // undeclared names are still groups of short flags
+    let r = parser.run_inner(&["-nn"]).unwrap();
+    assert_eq!(r, (None, false, 2));
+
+    let r = parser
+        .run_inner(&["--name", "*.rs"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(r, "no such flag: `--name`, did you mean `-name`?");
+
+    let r = parser.run_inner(&["-prnt"]).unwrap_err().unwrap_stderr();
+    assert_eq!(r, "no such flag: `-prnt`, did you mean `-print`?");
+
+    let r = parser.run_inner(&["--help"]).unwrap_err().unwrap_stdout();
+    let expected = "\
+Usage: [-name=PATTERN] [-print] [-n]...
+
+Available options:
+        -name=PATTERN  file name pattern
+        -print
+    -n
+    -h, --help         Prints help information
+";
+    assert_eq!(r, expected);
+}
+
+#[test]
+fn single_dash_long_names_win_over_short_names() {
+    let name = long("name").single_dash().switch();
+    let n = short('n').switch();
+    let a = short('a').switch();
+    let m = short('m').switch();
+    let e = short('e').switch();
+    let parser = construct!(name, n, a, m, e).to_options();
+
+    let r = parser.run_inner(&["-name"]).unwrap();
+    assert_eq!(r, (true, false, false, false, false));
+
+    let r = parser.run_inner(&["-mean"]).unwrap();
+    assert_eq!(r, (false, true, true, true, true));
+}
+
+#[test]
+fn hidden_single_dash_long_names() {
+    let name = long("name")
+        .single_dash()
+        .argument::<String>("N")
+        .hide()
+        .optional();
+    let verbose = long("verbose").switch();
+    let parser = construct!(name, verbose).to_options();
+
+    let r = parser.run_inner(&["-name", "a"]).unwrap();
+    assert_eq!(r, (Some("a".to_owned()), false));
+
+    let r = parser.run_inner(&["--help"]).unwrap_err().unwrap_stdout();
+    let expected = "\
+Usage: [--verbose]
+
+Available options:
+        --verbose
+    -h, --help     Prints help information
+";
+    assert_eq!(r, expected);
+}
+
+#[test]
+fn plus_and_slash_prefixes() {
+    let on = short('e').help("Exit on errors").req_flag(true);
+    let off = long("e")
+        .prefix(NamePrefix::Plus)
+        .help("Keep going on errors")
+        .req_flag(false);
+    let exit = construct!([on, off]).fallback(false);
+    let opt = long("opt")
+        .prefix(NamePrefix::Slash)
+        .help("Optimization level")
+        .argument::<u8>("LEVEL")
+        .optional();
+    let files = positional::<String>("FILE").many();
+    let parser = construct!(exit, opt, files).to_options();
+
+    let r = parser
+        .run_inner(&["-e", "/opt", "2", "/tmp", "+x"])
+        .unwrap();
+    assert_eq!(r, (true, Some(2), vec!["/tmp".to_owned(), "+x".to_owned()]));
+
+    let r = parser.run_inner(&["+e", "/opt=3"]).unwrap();
+    assert_eq!(r, (false, Some(3), Vec::new()));
+
+    let r = parser.run_inner(&["/opt"]).unwrap_err().unwrap_stderr();
+    assert_eq!(r, "`/opt` requires an argument `LEVEL`");
+
+    let r = parser
+        .run_inner(&["--opt", "1"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(r, "no such flag: `--opt`, did you mean `/opt`?");
+
+    let r = parser.run_inner(&["--help"]).unwrap_err().unwrap_stdout();
+    let expected = "\
+Usage: [-e | +e] [/opt=LEVEL] [FILE]...
+
+Available options:
+    -e              Exit on errors
+        +e          Keep going on errors
+        /opt=LEVEL  Optimization level
+    -h, --help      Prints help information
+";
+    assert_eq!(r, expected);
+}
+
+#[test]
+fn spanned_values() {
+    let verbose = short('v').switch().spanned();
+    let level = short('l').argument::<u32>("LEVEL").spanned();
+    let name = long("name")
+        .argument::<String>("NAME")
+        .fallback("anon".to_owned())
+        .spanned();
+    let files = positional::<String>("FILE").many().spanned();
+    let parser = construct!(verbose, level, name, files).to_options();
+
+    let (verbose, level, name, files) =
+        parser.run_inner(&["-vl3", "a", "--name=bob", "b"]).unwrap();
+    assert!(verbose.value);
+    assert_eq!(verbose.indices(), &[0]);
+    assert_eq!(verbose.os_str().unwrap(), "-v");
+
+    assert_eq!(level.value, 3);
+    assert_eq!(level.indices(), &[0]);
+    assert_eq!(level.os_str().unwrap(), "3");
+
+    assert_eq!(name.value, "bob");
+    assert_eq!(name.indices(), &[2]);
+    assert_eq!(
+        name.error("no such user").unwrap_stderr(),
+        "`bob`: no such user"
+    );
+
+    assert_eq!(files.value, vec!["a".to_owned(), "b".to_owned()]);
+    assert_eq!(files.indices(), &[1, 3]);
+    assert_eq!(files.os_str().unwrap(), "b");
+
+    let (_, level, name, files) = parser.run_inner(&["-l", "3"]).unwrap();
+    assert_eq!(level.indices(), &[0, 1]);
+    assert_eq!(name.value, "anon");
+    assert_eq!(name.indices(), &[] as &[usize]);
+    assert_eq!(name.os_str(), None);
+    assert_eq!(
+        name.error("no such user").unwrap_stderr(),
+        "check failed: no such user"
+    );
+    assert_eq!(files.into_inner(), Vec::<String>::new());
+}
+
+#[test]
+fn args_from_command_line() {
+    let name = long("name").argument::<String>("NAME");
+    let files = positional::<String>("FILE").many();
+    let parser = construct!(name, files).to_options();
+
+    let args = Args::from_command_line(
+        r#"--name "Bob \"B\" Smith" 'a b' c\ d \
+        e"#,
+    )
+    .unwrap();
+    let (name, files) = parser.run_inner(args).unwrap();
+    assert_eq!(name, "Bob \"B\" Smith");
+    assert_eq!(files, ["a b", "c d", "e"]);
+
+    let err = Args::from_command_line("--name \"Bob").err().unwrap();
+    assert_eq!(err.offset(), 7);
+    assert_eq!(err.to_string(), "unterminated quote at character 7");
+
+    let err = Args::from_command_line("--name Bob\\").err().unwrap();
+    assert_eq!(err.offset(), 10);
+    assert_eq!(
+        err.to_string(),
+        "backslash at the end of the line escapes nothing at character 10"
+    );
+}
+
+#[test]
+fn negatable_switch() {
+    let color = long("color").help("Use colors").negatable_switch();
+    let verbose = short('v').long("verbose").negatable_switch();
+    let parser = construct!(color, verbose).to_options();
+
+    let r = parser.run_inner(&[]).unwrap();
+    assert_eq!(r, (false, false));
+
+    let r = parser.run_inner(&["--color", "-v"]).unwrap();
+    assert_eq!(r, (true, true));
+
+    let r = parser
+        .run_inner(&["--no-color", "--color", "-v", "--no-verbose"])
+        .unwrap();
+    assert_eq!(r, (true, false));
+
+    let r = parser
+        .run_inner(&["--color", "--no-verbose", "--no-color", "--verbose"])
+        .unwrap();
+    assert_eq!(r, (false, true));
+
+    let r = parser.run_inner(&["--help"]).unwrap_err().unwrap_stdout();
+    let expected = "\
+Usage: [--[no-]color] [-v]
+
+Available options:
+        --[no-]color    Use colors
+    -v, --[no-]verbose
+    -h, --help          Prints help information
+";
+    assert_eq!(r, expected);
+
+    let r = parser
+        .run_inner(&["--no-colour"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(r, "no such flag: `--no-colour`, did you mean `--no-color`?");
+}
+
+#[test]
+fn negatable_switch_with_relations() {
+    let color = long("color").negatable_switch();
+    let plain = long("plain").conflicts_with("--color").switch();
+    let parser = construct!(color, plain).to_options();
+
+    let r = parser.run_inner(&["--no-color", "--plain"]).unwrap();
+    assert_eq!(r, (false, true));
+
+    let r = parser
+        .run_inner(&["--color", "--no-color", "--plain"])
+        .unwrap();
+    assert_eq!(r, (false, true));
+
+    let r = parser
+        .run_inner(&["--color", "--plain"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(r, "`--plain` cannot be used at the same time as `--color`");
+}
+
+#[test]
+fn negatable_switch_with_abbreviations() {
+    let color = long("color").negatable_switch();
+    let parser = color.to_options().allow_abbreviations();
+
+    assert!(!parser.run_inner(&["--col", "--no-c"]).unwrap());
+    assert!(parser.run_inner(&["--no-col", "--co"]).unwrap());
+}
+
+#[test]
+fn delimited_argument() {
+    let ports = short('p')
+        .long("ports")
+        .help("Ports to listen on")
+        .argument::<u16>("PORT")
+        .delimited(',')
+        .many()
+        .map(|xs| xs.concat());
+    let tags = long("tags").argument::<String>("tag").delimited(':');
+    let parser = construct!(ports, tags).to_options();
+
+    let r = parser
+        .run_inner(&["--ports", "80,443", "--tags=a:b", "-p", "8080"])
+        .unwrap();
+    assert_eq!(
+        r,
+        (vec![80, 443, 8080], vec!["a".to_owned(), "b".to_owned()])
+    );
+
+    let r = parser.run_inner(&["--tags", ""]).unwrap();
+    assert_eq!(r, (Vec::new(), vec![String::new()]));
+
+    let r = parser
+        .run_inner(&["-p", "80,443,x80", "--tags", "a"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(
+        r,
+        "couldn't parse element 3 (`x80`) of `80,443,x80`: invalid digit found in string"
+    );
+
+    let r = parser.run_inner(&["--help"]).unwrap_err().unwrap_stdout();
+    let expected = "\
+Usage: [-p=PORT,...]... --tags=<tag>:...
+
+Available options:
+    -p, --ports=PORT,...  Ports to listen on
+        --tags=<tag>:...
+    -h, --help            Prints help information
+";
+    assert_eq!(r, expected);
+}
+
+#[test]
+fn key_value_argument() {
+    use std::collections::{BTreeMap, HashMap};
+
+    let parser = short('D')
+        .help("Define a variable")
+        .key_value::<String, u32>("KEY", "VALUE")
+        .collect::<BTreeMap<_, _>>()
+        .to_options();
+
+    let r = parser
+        .run_inner(&["-D", "width=80", "-Dheight=25", "-D", "width=100"])
+        .unwrap();
+    let expected = [("height".to_owned(), 25), ("width".to_owned(), 100)];
+    assert_eq!(r, expected.into_iter().collect::<BTreeMap<_, _>>());
+
+    let r = parser.run_inner(&[]).unwrap();
+    assert_eq!(r, BTreeMap::new());
+
+    let r = parser
+        .run_inner(&["-D", "width"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(r, "couldn't parse `width`: expected KEY=VALUE");
+
+    let r = parser
+        .run_inner(&["-D", "width=wide"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(
+        r,
+        "couldn't parse `width=wide`: invalid value `wide`: invalid digit found in string"
+    );
+
+    let r = parser.run_inner(&["--help"]).unwrap_err().unwrap_stdout();
+    let expected = "\
+Usage: [-D=<KEY=VALUE>]...
+
+Available options:
+    -D=<KEY=VALUE>  Define a variable
+    -h, --help      Prints help information
+";
+    assert_eq!(r, expected);
+
+    let parser = long("set")
+        .key_value::<String, String>("KEY", "VALUE")
+        .collect::<HashMap<_, _>>()
+        .duplicates(DuplicateKeys::Reject)
+        .to_options();
+
+    let r = parser
+        .run_inner(&["--set", "a=b=c", "--set", "x="])
+        .unwrap();
+    assert_eq!(r["a"], "b=c");
+    assert_eq!(r["x"], "");
+
+    let r = parser
+        .run_inner(&["--set", "name=Bob", "--set=name=Bill"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(
+        r,
+        "key `name` is set more than once: `name=Bob` and `name=Bill`"
+    );
+
+    let parser = long("set")
+        .key_value::<String, u8>("KEY", "VALUE")
+        .collect::<Vec<_>>()
+        .duplicates(DuplicateKeys::KeepAll)
+        .to_options();
+
+    let r = parser
+        .run_inner(&["--set", "a=1", "--set", "b=2", "--set", "a=3"])
+        .unwrap();
+    let expected = vec![
+        ("a".to_owned(), 1),
+        ("b".to_owned(), 2),
+        ("a".to_owned(), 3),
+    ];
+    assert_eq!(r, expected);
+
+    let parser = long("set")
+        .key_value::<char, u8>("KEY", "VALUE")
+        .to_options();
+    let r = parser
+        .run_inner(&["--set", "ab=1"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(
+        r,
+        "couldn't parse `ab=1`: invalid key `ab`: too many characters in string"
+    );
+}
+
+#[test]
+fn conflicts_with_and_requires() {
+    let json = long("json")
+        .help("Output JSON")
+        .conflicts_with("--yaml")
+        .conflicts_with("-t")
+        .requires("--output")
+        .switch();
+    let yaml = short('y').long("yaml").switch();
+    let toml = short('t').switch();
+    let output = short('o')
+        .long("output")
+        .requires("-v")
+        .argument::<String>("FILE")
+        .optional();
+    let verbose = short('v').switch();
+    let parser = construct!(json, yaml, toml, output, verbose).to_options();
+
+    let r = parser.run_inner(&["--json", "-o", "x", "-v"]).unwrap();
+    assert_eq!(r, (true, false, false, Some("x".to_owned()), true));
+
+    let r = parser
+        .run_inner(&["--yaml", "-vt", "--json", "--output=x"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(r, "`--json` cannot be used at the same time as `--yaml`");
+
+    let r = parser
+        .run_inner(&["-y", "--json", "--output=x"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(r, "`--json` cannot be used at the same time as `-y`");
+
+    let r = parser
+        .run_inner(&["--json", "-v"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(r, "`--json` requires `--output` to be present");
+
+    let r = parser
+        .run_inner(&["--output", "x"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(r, "`--output` requires `-v` to be present");
+
+    let r = parser.run_inner(&["--help"]).unwrap_err().unwrap_stdout();
+    let expected = "\
+Usage: [--json] [-y] [-t] [-o=FILE] [-v]
+
+Available options:
+        --json         Output JSON [conflicts with: --yaml, -t] [requires: --output]
+    -y, --yaml
+    -t
+    -o, --output=FILE  [requires: -v]
+    -v
+    -h, --help         Prints help information
+";
+    assert_eq!(r, expected);
+}
+
+#[test]
+fn relations_are_checked_per_command() {
+    let quiet = short('q').conflicts_with("-x").switch();
+    let extra = short('x').switch();
+    let inner = construct!(quiet, extra).to_options().command("cmd");
+    let verbose = short('v').conflicts_with("-q").switch();
+    let parser = construct!(verbose, inner).to_options();
+
+    // -q belongs to the subcommand, -v can't see it
+    let r = parser.run_inner(&["-v", "cmd", "-q"]).unwrap();
+    assert_eq!(r, (true, (true, false)));
+
+    let r = parser
+        .run_inner(&["-v", "cmd", "-q", "-x"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(r, "`-x` cannot be used at the same time as `-q`");
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "\"--yml\" is used in `conflicts_with` or `requires`")]
+fn relations_with_unknown_names() {
+    let json = long("json").conflicts_with("--yml").switch();
+    let yaml = long("yaml").switch();
+    let parser = construct!(json, yaml).to_options();
+    let _ = parser.run_inner(&["--json"]);
+}
+
+#[test]
+fn bounded_many() {
+    let files = positional::<String>("FILE").many().at_least(2).at_most(3);
+    let parser = files.to_options();
+
+    let r = parser.run_inner(&["a", "b"]).unwrap();
+    assert_eq!(r, vec!["a".to_owned(), "b".to_owned()]);
+
+    let r = parser.run_inner(&["a"]).unwrap_err().unwrap_stderr();
+    assert_eq!(r, "expected `FILE` at least 2 times, got 1");
+
+    let r = parser
+        .run_inner(&["a", "b", "c", "d"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(r, "expected `FILE` at most 3 times, `d` is one too many");
+
+    let r = parser.run_inner(&["--help"]).unwrap_err().unwrap_stdout();
+    let expected = "\
+Usage: FILE{2,3}
+
+Available options:
+    -h, --help  Prints help information
+";
+    assert_eq!(r, expected);
+
+    let verbose = short('v')
+        .help("Verbosity")
+        .req_flag(())
+        .many()
+        .at_most(2)
+        .map(|xs| xs.len());
+    let name = long("name")
+        .argument::<String>("NAME")
+        .collect::<std::collections::BTreeSet<_>>()
+        .exactly(2);
+    let parser = construct!(verbose, name).to_options();
+
+    let r = parser
+        .run_inner(&["-vv", "--name", "a", "--name", "b"])
+        .unwrap();
+    assert_eq!(r.0, 2);
+
+    let r = parser
+        .run_inner(&["-v", "--name", "a", "-v", "-v"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(r, "expected `-v` at most 2 times, `-v` is one too many");
+
+    let r = parser
+        .run_inner(&["--name", "a"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(r, "expected `--name=NAME` exactly 2 times, got 1");
+
+    let r = parser
+        .run_inner(&["--name", "a", "--name", "b", "--name=c"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(
+        r,
+        "expected `--name=NAME` exactly 2 times, `--name` is one too many"
+    );
+
+    let r = parser.run_inner(&["--help"]).unwrap_err().unwrap_stdout();
+    let expected = "\
+Usage: [-v]{0,2} --name=NAME{2}
+
+Available options:
+    -v               Verbosity
+        --name=NAME
+    -h, --help       Prints help information
+";
+    assert_eq!(r, expected);
+}
+
+#[test]
+fn bounded_some_and_collect() {
+    let files = positional::<String>("FILE")
+        .some("need files")
+        .at_least(2)
+        .at_most(3);
+    let parser = files.to_options();
+
+    let r = parser.run_inner(&[]).unwrap_err().unwrap_stderr();
+    assert_eq!(r, "need files");
+
+    let r = parser.run_inner(&["a"]).unwrap_err().unwrap_stderr();
+    assert_eq!(r, "expected `FILE` at least 2 times, got 1");
+
+    let r = parser
+        .run_inner(&["a", "b", "c", "d"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(r, "expected `FILE` at most 3 times, `d` is one too many");
+
+    let r = parser.run_inner(&["--help"]).unwrap_err().unwrap_stdout();
+    assert_eq!(r.lines().next(), Some("Usage: FILE{2,3}"));
+
+    // upper bound alone keeps the shape of the parser without bounds
+    let some = short('s').argument::<u8>("S").some("need -s").at_most(2);
+    let collect = short('c')
+        .argument::<u8>("C")
+        .collect::<Vec<_>>()
+        .at_most(2);
+    let many = short('m').argument::<u8>("M").many().at_most(2);
+    let parser = construct!(some, collect, many).to_options();
+
+    let r = parser.run_inner(&["--help"]).unwrap_err().unwrap_stdout();
+    assert_eq!(
+        r.lines().next(),
+        Some("Usage: -s=S{1,2} -c=C{0,2} [-m=M]{0,2}")
+    );
+}
+
+#[test]
+fn possible_values() {
+    let mode = long("mode")
+        .help("Processing mode")
+        .argument::<String>("MODE")
+        .possible_values(["fast", "slow"]);
+    let tags = long("tags")
+        .argument::<String>("TAG")
+        .possible_values(["red", "green", "blue"])
+        .delimited(',')
+        .optional();
+    let shell = positional::<String>("SHELL").possible_values(["bash", "zsh"]);
+    let parser = construct!(mode, tags, shell).to_options();
+
+    let r = parser
+        .run_inner(&["--mode=slow", "--tags", "red,blue", "zsh"])
+        .unwrap();
+    assert_eq!(
+        r,
+        (
+            "slow".to_owned(),
+            Some(vec!["red".to_owned(), "blue".to_owned()]),
+            "zsh".to_owned()
+        )
+    );
+
+    let r = parser
+        .run_inner(&["--mode", "flow", "bash"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(r, "`flow` is not a valid value, did you mean `slow`?");
+
+    let r = parser
+        .run_inner(&["--mode", "fast", "--tags", "red,yellow", "bash"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(
+        r,
+        "`yellow` is not a valid value, expected one of `red`, `green`, `blue`"
+    );
+
+    let r = parser
+        .run_inner(&["--mode", "fast", "bsah"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(r, "`bsah` is not a valid value, did you mean `bash`?");
+
+    let r = parser.run_inner(&["--help"]).unwrap_err().unwrap_stdout();
+    let expected = "\
+Usage: --mode=MODE [--tags=TAG,...] SHELL
+
+Available positional items:
+    SHELL               [possible values: bash, zsh]
+
+Available options:
+        --mode=MODE     Processing mode [possible values: fast, slow]
+        --tags=TAG,...  [possible values: red, green, blue]
+    -h, --help          Prints help information
+";
+    assert_eq!(r, expected);
+}
+
+#[test]
+fn range_constraint() {
+    let jobs = long("jobs")
+        .help("Number of parallel jobs")
+        .argument::<u32>("N")
+        .range(1..=64);
+    let ratio = long("ratio")
+        .argument::<f64>("R")
+        .range(0.0..1.0)
+        .optional();
+    let size = long("size").argument::<u32>("S").range(1..).optional();
+    let nice = long("nice").argument::<i32>("N").range(..20).optional();
+    let parser = construct!(jobs, ratio, size, nice).to_options();
+
+    let r = parser.run_inner(&["--jobs", "8"]).unwrap();
+    assert_eq!(r, (8, None, None, None));
+
+    let r = parser
+        .run_inner(&["--jobs", "0"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(r, "`0` is out of range, expected at least `1`");
+
+    let r = parser
+        .run_inner(&["--jobs", "65"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(r, "`65` is out of range, expected at most `64`");
+
+    let r = parser
+        .run_inner(&["--jobs", "1", "--ratio", "1"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(r, "`1` is out of range, expected less than `1`");
+
+    let r = parser.run_inner(&["--help"]).unwrap_err().unwrap_stdout();
+    let expected = "\
+Usage: --jobs=N [--ratio=R] [--size=S] [--nice=N]
+
+Available options:
+        --jobs=N   Number of parallel jobs [1..=64]
+        --ratio=R  [0..1]
+        --size=S   [1..]
+        --nice=N   [..20]
+    -h, --help     Prints help information
+";
+    assert_eq!(r, expected);
+}
+
+#[test]
+fn error_context_chain() {
+    let port = long("port")
+        .argument::<u16>("PORT")
+        .guard(|p| *p > 1024, "port must be above 1024");
+    let host = long("host").argument::<String>("HOST");
+    let db = construct!(host, port).context("while reading database options");
+    let verbose = short('v').switch();
+    let serve = construct!(db, verbose)
+        .context("while reading server options")
+        .to_options()
+        .command("serve");
+    let parser = construct!(serve).to_options();
+
+    let r = parser
+        .run_inner(&["serve", "--host", "db", "--port", "80"])
+        .unwrap_err()
+        .unwrap_stderr();
+    let expected = "`serve`: while reading server options: while reading database options: \
+`80`: port must be above 1024";
+    assert_eq!(r, expected);
+
+    // missing items are reported as is
+    let r = parser
+        .run_inner(&["serve", "--host", "db"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(
+        r,
+        "expected `--port=PORT`, pass `--help` for usage information"
+    );
+
+    let r = parser
+        .run_inner(&["serve", "--host", "db", "--port", "8080", "-v"])
+        .unwrap();
+    assert_eq!(r, (("db".to_owned(), 8080), true));
+}
+
+#[test]
+fn value_source() {
+    std::env::set_var("BPAF_TEST_VALUE_SOURCE", "12");
+    let jobs = long("jobs").argument::<u32>("N").fallback(4).with_source();
+    let port = long("port")
+        .env("BPAF_TEST_VALUE_SOURCE")
+        .argument::<u16>("PORT")
+        .with_source();
+    let verbose = short('v').switch().with_source();
+    let mode = pure("fast").with_source();
+    let parser = construct!(jobs, port, verbose, mode).to_options();
+
+    let r = parser.run_inner(&[]).unwrap();
+    assert_eq!(
+        r,
+        (
+            (4, ValueSource::Fallback),
+            (12, ValueSource::Env),
+            (false, ValueSource::Fallback),
+            ("fast", ValueSource::Pure)
+        )
+    );
+
+    let r = parser
+        .run_inner(&["--port", "80", "-v", "--jobs", "8"])
+        .unwrap();
+    assert_eq!(
+        r,
+        (
+            (8, ValueSource::Argv),
+            (80, ValueSource::Argv),
+            (true, ValueSource::Argv),
+            ("fast", ValueSource::Pure)
+        )
+    );
+
+    // source of an inner value doesn't leak into the outer one
+    let jobs = long("jobs").argument::<u32>("N").fallback(4).with_source();
+    let name = long("name").argument::<String>("NAME");
+    let parser = construct!(jobs, name).with_source().to_options();
+    let r = parser.run_inner(&["--name", "x"]).unwrap();
+    assert_eq!(
+        r,
+        (
+            ((4, ValueSource::Fallback), "x".to_owned()),
+            ValueSource::Argv
+        )
+    );
+}
+
+#[test]
+fn deprecated_items() {
+    use std::{cell::RefCell, rc::Rc};
+
+    let jobs = long("jobs").help("Number of jobs").argument::<u32>("N");
+    let threads = long("threads")
+        .short('t')
+        .deprecated("use --jobs instead")
+        .argument::<u32>("N");
+    let jobs = construct!([jobs, threads]);
+    let run = pure(()).to_options().command("run");
+    let start = pure(())
+        .to_options()
+        .command("start")
+        .deprecated("use `run` instead");
+    let cmd = construct!([run, start]);
+
+    let warnings = Rc::new(RefCell::new(Vec::new()));
+    let sink = warnings.clone();
+    let parser = construct!(jobs, cmd)
+        .to_options()
+        .on_warning(move |w| sink.borrow_mut().push(w.monochrome(true)));
+
+    let r = parser.run_inner(&["--jobs", "2", "run"]).unwrap();
+    assert_eq!(r, (2, ()));
+    assert!(warnings.borrow().is_empty());
+
+    let r = parser.run_inner(&["-t", "2", "start"]).unwrap();
+    assert_eq!(r, (2, ()));
+    assert_eq!(
+        warnings.borrow_mut().split_off(0),
+        [
+            "`-t` is deprecated, use --jobs instead",
+            "`start` is deprecated, use `run` instead"
+        ]
+    );
+
+    // no warnings if parsing fails
+    parser.run_inner(&["--threads", "2"]).unwrap_err();
+    assert!(warnings.borrow().is_empty());
+
+    let r = parser.run_inner(&["--help"]).unwrap_err().unwrap_stdout();
+    let expected = "\
+Usage: --jobs=N COMMAND ...
+
+Available options:
+        --jobs=N  Number of jobs
+    -h, --help    Prints help information
+
+Available commands:
+    run
+";
+    assert_eq!(r, expected);
+}
+
+#[test]
+fn deprecated_names_are_still_split() {
+    let quiet = short('q').deprecated("use --silent").switch();
+    let verbose = short('v').switch();
+    let name = long("name")
+        .single_dash()
+        .deprecated("use --pattern")
+        .argument::<String>("N")
+        .optional();
+    let parser = construct!(quiet, verbose, name).to_options();
+
+    let r = parser.run_inner(&["-vq"]).unwrap();
+    assert_eq!(r, (true, true, None));
+
+    let r = parser.run_inner(&["-qv"]).unwrap();
+    assert_eq!(r, (true, true, None));
+
+    let r = parser.run_inner(&["-name", "a"]).unwrap();
+    assert_eq!(r, (false, false, Some("a".to_owned())));
+
+    let r = parser.run_inner(&["--help"]).unwrap_err().unwrap_stdout();
+    let expected = "\
+Usage: [-v]
+
+Available options:
+    -v
+    -h, --help  Prints help information
+";
+    assert_eq!(r, expected);
+}
+
+#[test]
+fn aliases() {
+    let color = long("color")
+        .visible_alias("colour")
+        .short('c')
+        .help("Use colors")
+        .switch();
+    let jobs = long("jobs")
+        .alias("threads")
+        .visible_short_alias('j')
+        .help("Number of jobs")
+        .argument::<u32>("N")
+        .fallback(1);
+    let remove = pure(())
+        .to_options()
+        .descr("Remove a file")
+        .command("remove")
+        .visible_alias("rm")
+        .alias("del");
+    let parser = construct!(color, jobs, remove).to_options();
+
+    let r = parser.run_inner(&["--colour", "remove"]).unwrap();
+    assert_eq!(r, (true, 1, ()));
+
+    let r = parser.run_inner(&["--threads", "3", "rm"]).unwrap();
+    assert_eq!(r, (false, 3, ()));
+
+    let r = parser.run_inner(&["-j", "4", "del"]).unwrap();
+    assert_eq!(r, (false, 4, ()));
+
+    let r = parser
+        .run_inner(&["--colourr", "rm"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(r, "no such flag: `--colourr`, did you mean `--colour`?");
+
+    let r = parser.run_inner(&["--help"]).unwrap_err().unwrap_stdout();
+    let expected = "\
+Usage: [-c] [--jobs=N] COMMAND ...
+
+Available options:
+    -c, --color   Use colors [aliases: --colour]
+        --jobs=N  Number of jobs [aliases: -j]
+    -h, --help    Prints help information
+
+Available commands:
+    remove        Remove a file [aliases: rm]
+";
+    assert_eq!(r, expected);
+}
+
+#[test]
+fn aliases_are_not_main_names() {
+    let verbose = short('v').alias("verbose").help("Be verbose").switch();
+    let name = long("name")
+        .visible_alias("pattern")
+        .single_dash()
+        .help("File name pattern")
+        .argument::<String>("PAT")
+        .optional();
+    let color = long("color").alias("colour").negatable_switch();
+    let parser = construct!(verbose, name, color).to_options();
+
+    let r = parser
+        .run_inner(&["--verbose", "-pattern", "*.rs", "--no-colour"])
+        .unwrap();
+    assert_eq!(r, (true, Some("*.rs".to_owned()), false));
+
+    let r = parser
+        .run_inner(&["--pattern", "*.rs"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(r, "no such flag: `--pattern`, did you mean `-pattern`?");
+
+    let r = parser.run_inner(&["--help"]).unwrap_err().unwrap_stdout();
+    let expected = "\
+Usage: [-v] [-name=PAT] [--[no-]color]
+
+Available options:
+    -v                Be verbose
+        -name=PAT     File name pattern [aliases: -pattern]
+        --[no-]color
+    -h, --help        Prints help information
+";
+    assert_eq!(r, expected);
+}
+
+#[test]
+fn and_then_dependent_parser() {
+    let package = long("package")
+        .help("Package to use")
+        .argument::<String>("PKG")
+        .optional();
+    let parser = package
+        .and_then(None, |package| {
+            let bins = match package.as_deref() {
+                Some("client") => vec!["cli", "gui"],
+                _ => vec!["cli", "gui", "srv"],
+            };
+            long("bin")
+                .help("Binary to run")
+                .argument::<String>("BIN")
+                .possible_values(bins)
+                .map(move |bin| (package.clone(), bin))
+        })
+        .to_options();
+
+    let r = parser.run_inner(&["--bin", "srv"]).unwrap();
+    assert_eq!(r, (None, "srv".to_owned()));
+
+    let r = parser
+        .run_inner(&["--bin", "srv", "--package", "client"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(
+        r,
+        "`srv` is not a valid value, expected one of `cli`, `gui`"
+    );
+
+    let r = parser
+        .run_inner(&["--package", "client"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(
+        r,
+        "expected `--bin=BIN`, pass `--help` for usage information"
+    );
+
+    let r = parser.run_inner(&["--help"]).unwrap_err().unwrap_stdout();
+    let expected = "\
+Usage: [--package=PKG] --bin=BIN
+
+Available options:
+        --package=PKG  Package to use
+        --bin=BIN      Binary to run [possible values: cli, gui, srv]
+    -h, --help         Prints help information
+";
+    assert_eq!(r, expected);
+}
+
+#[test]
+fn structured_errors() {
+    let token = long("token").argument::<String>("TOKEN");
+    let port = long("port").argument::<u16>("PORT").fallback(80);
+    let json = long("json").switch();
+    let yaml = long("yaml").conflicts_with("--json").switch();
+    let parser = construct!(token, port, json, yaml)
+        .to_options()
+        .version("1.0");
+
+    let err = parser.run_structured(&["--port", "81"]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::MissingItem);
+    assert_eq!(err.names(), ["--token"]);
+    assert_eq!(err.index(), None);
+    assert_eq!(
+        err.to_string(),
+        "expected `--token=TOKEN`, pass `--help` for usage information"
+    );
+
+    let err = parser
+        .run_structured(&["--token", "t", "--port=http"])
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidValue);
+    assert_eq!(err.index(), Some(2));
+    assert_eq!(err.names(), ["--port"]);
+    assert_eq!(err.value(), Some("http"));
+    assert_eq!(err.message(), Some("invalid digit found in string"));
+    assert_eq!(
+        err.render().unwrap_stderr(),
+        "couldn't parse `http`: invalid digit found in string"
+    );
+
+    let err = parser
+        .run_structured(&["--token", "t", "--json", "--yaml"])
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Conflict);
+    assert_eq!(err.index(), Some(3));
+    assert_eq!(err.names(), ["--yaml", "--json"]);
+
+    let err = parser
+        .run_structured(&["--token", "t", "--jsno"])
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnknownName);
+    assert_eq!(err.value(), Some("--jsno"));
+    assert_eq!(err.names(), ["--json"]);
+
+    let err = parser.run_structured(&["--token"]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::MissingValue);
+    assert_eq!(err.index(), Some(0));
+    assert_eq!(err.names(), ["--token"]);
+
+    let err = parser.run_structured(&["--help"]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Help);
+    let err = parser.run_structured(&["--version"]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Version);
+    assert_eq!(ParseFailure::from(err).unwrap_stdout(), "Version: 1.0\n");
+
+    fn is_error<E: std::error::Error + Send + Sync + 'static>(_: &E) {}
+    is_error(&parser.run_structured(&[]).unwrap_err());
+}
+
+#[test]
+fn structured_errors_name_failed_items() {
+    let port = long("port")
+        .env("BPAF_TEST_NAMED_PORT")
+        .argument::<u16>("PORT")
+        .range(1..)
+        .fallback(80);
+    let mode = short('m')
+        .argument::<String>("MODE")
+        .possible_values(["fast", "slow"])
+        .optional();
+    let set = long("set")
+        .key_value::<String, String>("KEY", "VALUE")
+        .collect::<std::collections::HashMap<_, _>>()
+        .duplicates(DuplicateKeys::Reject);
+    let file = positional::<String>("FILE")
+        .guard(|f| !f.is_empty(), "empty file name")
+        .optional();
+    let parser = construct!(port, mode, set, file).to_options();
+
+    let err = parser.run_structured(&["--port", "0"]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::OutOfRange);
+    assert_eq!(err.names(), ["--port"]);
+
+    let err = parser.run_structured(&["-m", "quick"]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidChoice);
+    assert_eq!(err.names(), ["-m"]);
+
+    let err = parser
+        .run_structured(&["--set", "a=1", "--set", "a=2"])
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::DuplicateKey);
+    assert_eq!(err.names(), ["--set"]);
+
+    let err = parser.run_structured(&[""]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::ValidationFailed);
+    assert_eq!(err.names(), ["FILE"]);
+
+    std::env::set_var("BPAF_TEST_NAMED_PORT", "http");
+    let err = parser.run_structured(&[]).unwrap_err();
+    std::env::remove_var("BPAF_TEST_NAMED_PORT");
+    assert_eq!(err.kind(), ErrorKind::InvalidValue);
+    assert_eq!(err.index(), None);
+    assert_eq!(err.names(), ["--port"]);
+
+    // value built from several items has no single name
+    let a = short('a').argument::<u32>("A");
+    let b = short('b').argument::<u32>("B");
+    let parser = construct!(a, b)
+        .guard(|(a, b)| a < b, "a must be less than b")
+        .to_options();
+    let err = parser.run_structured(&["-a", "2", "-b", "1"]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::ValidationFailed);
+    assert!(err.names().is_empty());
+}
+
+#[test]
+fn structured_errors_in_commands() {
+    let port = long("port")
+        .argument::<u16>("PORT")
+        .context("while reading server options");
+    let parser = port.to_options().command("serve").to_options();
+
+    let err = parser
+        .run_structured(&["serve", "--port", "http"])
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidValue);
+    assert_eq!(err.path(), ["serve"]);
+    assert_eq!(err.context(), ["while reading server options"]);
+    assert_eq!(err.index(), Some(2));
+    assert_eq!(
+        err.to_string(),
+        "`serve`: while reading server options: couldn't parse `http`: invalid digit found in string"
+    );
+
+    let err = parser.run_structured(&["serve", "--help"]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Help);
+    assert_eq!(err.path(), ["serve"]);
+}
+
+struct German;
+impl Messages for German {
+    fn usage(&self) -> &str {
+        "Verwendung"
+    }
+    fn available_options(&self) -> &str {
+        "Verfügbare Optionen:"
+    }
+    fn available_commands(&self) -> &str {
+        "Verfügbare Befehle:"
+    }
+    fn help_flag(&self) -> &str {
+        "Hilfe anzeigen"
+    }
+    fn version(&self) -> &str {
+        "Version {0}"
+    }
+    fn deprecated(&self) -> &str {
+        "{0} ist veraltet, {1}"
+    }
+    fn expected(&self) -> &str {
+        "{0} fehlt, {2} zeigt die Hilfe"
+    }
+    fn one_of_two(&self) -> &str {
+        "{0} oder {1}"
+    }
+    fn parse_failed(&self) -> &str {
+        "{0} ist ungültig: {1}"
+    }
+    fn note_default(&self) -> &str {
+        "Standard"
+    }
+    fn check_failed(&self) -> &str {
+        "{0} ist abgelehnt: {1}"
+    }
+    fn key_value_expected(&self) -> &str {
+        "{0}={1} erwartet"
+    }
+    fn key_value_invalid_key(&self) -> &str {
+        "ungültiger Schlüssel `{0}`: {1}"
+    }
+}
+
+#[test]
+fn localised_messages() {
+    let port = long("port").help("Port").argument::<u16>("PORT");
+    let old = long("old").deprecated("nimm --port").switch();
+    let serve = construct!(port, old)
+        .to_options()
+        .command("serve")
+        .help("Server starten");
+    let json = long("json").switch();
+    let yaml = long("yaml").switch();
+    let format = construct!([json, yaml]);
+    let parser = construct!(format, serve)
+        .to_options()
+        .version("1.0")
+        .messages(&German);
+
+    let r = parser.run_inner(&["--help"]).unwrap_err().unwrap_stdout();
+    let expected = "\
+Verwendung: ([--json] | [--yaml]) COMMAND ...
+
+Verfügbare Optionen:
+        --json
+        --yaml
+    -h, --help     Hilfe anzeigen
+    -V, --version  Prints version information
+
+Verfügbare Befehle:
+    serve          Server starten
+";
+    assert_eq!(r, expected);
+
+    let r = parser
+        .run_inner(&["--version"])
+        .unwrap_err()
+        .unwrap_stdout();
+    assert_eq!(r, "Version 1.0\n");
+
+    // subcommands use the catalog of the top level parser
+    let r = parser
+        .run_inner(&["serve", "--help"])
+        .unwrap_err()
+        .unwrap_stdout();
+    let expected = "\
+Verwendung: serve --port=PORT
+
+Verfügbare Optionen:
+        --port=PORT  Port
+    -h, --help       Hilfe anzeigen
+";
+    assert_eq!(r, expected);
+
+    let r = parser
+        .run_inner(&["serve", "--port", "http"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(r, "`http` ist ungültig: invalid digit found in string");
+
+    let r = parser.run_inner(&["serve"]).unwrap_err().unwrap_stderr();
+    assert_eq!(r, "`--port=PORT` fehlt, `--help` zeigt die Hilfe");
+
+    let r = parser.run_inner(&[]).unwrap_err().unwrap_stderr();
+    assert_eq!(r, "`COMMAND ...` fehlt, `--help` zeigt die Hilfe");
+
+    let err = parser.run_structured(&["serve"]).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "`--port=PORT` fehlt, `--help` zeigt die Hilfe"
+    );
+
+    // untranslated messages stay in English
+    let r = parser
+        .run_inner(&["serve", "--port", "1", "--port", "2"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(
+        r,
+        "argument `--port` cannot be used multiple times in this context"
+    );
+}
+
+#[test]
+fn localised_notes() {
+    let jobs = long("jobs")
+        .help("Jobs")
+        .argument::<u32>("N")
+        .range(1..16)
+        .fallback(4)
+        .display_fallback();
+    let parser = jobs.to_options().messages(&German);
+
+    let r = parser.run_inner(&["--help"]).unwrap_err().unwrap_stdout();
+    let expected = "\
+Verwendung: [--jobs=N]
+
+Verfügbare Optionen:
+        --jobs=N  Jobs [1..16]
+                  [Standard: 4]
+    -h, --help    Hilfe anzeigen
+";
+    assert_eq!(r, expected);
+}
+
+#[test]
+fn localised_value_messages() {
+    let define = short('D').key_value::<u32, String>("KEY", "VALUE");
+    let parser = define.to_options().messages(&German);
+
+    let r = parser.run_inner(&["-D", "x"]).unwrap_err().unwrap_stderr();
+    assert_eq!(r, "`x` ist ungültig: KEY=VALUE erwartet");
+
+    let r = parser
+        .run_inner(&["-D", "x=1"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(
+        r,
+        "`x=1` ist ungültig: ungültiger Schlüssel `x`: invalid digit found in string"
+    );
+
+    let parser = short('n')
+        .argument::<u32>("N")
+        .spanned()
+        .to_options()
+        .messages(&German);
+    let n = parser.run_inner(&["-n", "3"]).unwrap();
+    let r = n.error("must be even").unwrap_stderr();
+    assert_eq!(r, "`3` ist abgelehnt: must be even");
+}
+
+#[test]
+fn localised_warnings() {
+    use std::sync::mpsc::channel;
+    let old = long("old").deprecated("nimm --new").switch();
+    let (tx, rx) = channel();
+    let parser = old
+        .to_options()
+        .messages(&German)
+        .on_warning(move |w| tx.send(w.monochrome(true)).unwrap());
+
+    assert!(parser.run_inner(&["--old"]).unwrap());
+    let w: Vec<_> = rx.try_iter().collect();
+    assert_eq!(w, ["`--old` ist veraltet, nimm --new"]);
+}
+
+#[test]
+fn accumulate_errors() {
+    let name = long("name").argument::<String>("NAME");
+    let port = long("port")
+        .argument::<u16>("PORT")
+        .guard(|p| *p > 1024, "port must be unprivileged");
+    let user = long("user").argument::<String>("USER");
+    let parser = construct!(name, port, user).to_options();
+
+    // default mode reports the first failure only
+    let r = parser
+        .run_inner(&["--port", "80"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(
+        r,
+        "expected `--name=NAME`, pass `--help` for usage information"
+    );
+
+    let parser = parser.accumulate_errors();
+    let r = parser
+        .run_inner(&["--port", "80"])
+        .unwrap_err()
+        .unwrap_stderr();
+    let expected = "\
+found 3 problems:
+  expected `--name=NAME`, pass `--help` for usage information
+  `80`: port must be unprivileged
+  expected `--user=USER`, pass `--help` for usage information";
+    assert_eq!(r, expected);
+
+    let err = parser.run_structured(&["--port", "80"]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::MissingItem);
+    let kinds = err
+        .others()
+        .iter()
+        .map(ParseError::kind)
+        .collect::<Vec<_>>();
+    assert_eq!(kinds, [ErrorKind::ValidationFailed, ErrorKind::MissingItem]);
+    assert_eq!(err.others()[0].index(), Some(1));
+
+    // a single failure is reported as usual
+    let r = parser
+        .run_inner(&["--name", "Bob", "--port", "8080"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(
+        r,
+        "expected `--user=USER`, pass `--help` for usage information"
+    );
+
+    let r = parser
+        .run_inner(&["--name", "Bob", "--port", "8080", "--user", "bob"])
+        .unwrap();
+    assert_eq!(r, ("Bob".to_owned(), 8080, "bob".to_owned()));
+
+    // help still takes priority
+    let r = parser.run_inner(&["--help"]).unwrap_err().unwrap_stdout();
+    assert!(r.starts_with("Usage: --name=NAME --port=PORT --user=USER"));
+}
+
+#[test]
+fn accumulate_errors_nested() {
+    let verbose = short('v').switch();
+    let port = long("port").argument::<u16>("PORT");
+    let host = long("host").argument::<String>("HOST");
+    let server = construct!(port, host)
+        .context("while reading server options")
+        .to_options()
+        .command("serve");
+    let parser = construct!(verbose, server).to_options().accumulate_errors();
+
+    let r = parser
+        .run_inner(&["serve", "--port", "x"])
+        .unwrap_err()
+        .unwrap_stderr();
+    let expected = "\
+found 2 problems:
+  `serve`: while reading server options: couldn't parse `x`: invalid digit found in string
+  `serve`: while reading server options: expected `--host=HOST`, pass `--help` for usage information";
+    assert_eq!(r, expected);
+
+    // missing items of alternatives are still listed together
+    let json = long("json").switch();
+    let yaml = long("yaml").req_flag(true);
+    let toml = long("toml").req_flag(true);
+    let format = construct!([yaml, toml]);
+    let out = long("out").argument::<String>("OUT");
+    let parser = construct!(json, format, out)
+        .to_options()
+        .accumulate_errors();
+    let r = parser.run_inner(&[]).unwrap_err().unwrap_stderr();
+    let expected = "\
+found 2 problems:
+  expected `--yaml` or `--toml`, pass `--help` for usage information
+  expected `--out=OUT`, pass `--help` for usage information";
+    assert_eq!(r, expected);
+}
+
+#[test]
+fn accumulate_errors_optional() {
+    std::env::set_var("ACC_OPT_PORT", "x");
+    let name = long("name").argument::<String>("NAME");
+    let port = long("port").env("ACC_OPT_PORT").argument::<u16>("PORT");
+    let server = construct!(name, port).optional();
+    let verbose = short('v').switch();
+    let parser = construct!(server, verbose).to_options().accumulate_errors();
+
+    // a parse failure next to a missing item is not swallowed by optional
+    // even if nothing was consumed from the command line
+    let r = parser.run_inner(&["-v"]).unwrap_err().unwrap_stderr();
+    let expected = "\
+found 2 problems:
+  expected `--name=NAME`, pass `--help` for usage information
+  couldn't parse: invalid digit found in string";
+    assert_eq!(r, expected);
+
+    // all the accumulated failures are about missing items
+    std::env::remove_var("ACC_OPT_PORT");
+    let r = parser.run_inner(&["-v"]).unwrap();
+    assert_eq!(r, (None, true));
+}
+
+#[test]
+fn caret_diagnostics() {
+    let name = long("name").argument::<String>("NAME");
+    let port = long("port").argument::<u16>("PORT");
+    let tag = long("tag").argument::<String>("TAG").many();
+    let parser = construct!(name, port, tag).to_options().caret_diagnostics();
+
+    // program name and quoting
+    let args = Args::from(&["--name", "Bob Smith", "--port", "1x"]).set_name("app");
+    let r = parser.run_inner(args).unwrap_err().unwrap_stderr();
+    let expected = "\
+couldn't parse `1x`: invalid digit found in string
+  |
+  | app --name 'Bob Smith' --port 1x
+  |                               ^^ invalid digit found in string";
+    assert_eq!(r, expected);
+
+    // repeated items point to the first use
+    let r = parser
+        .run_inner(&["--name", "a", "--port", "1", "--name", "b"])
+        .unwrap_err()
+        .unwrap_stderr();
+    let expected = "\
+argument `--name` cannot be used multiple times in this context
+  |
+  | --name a --port 1 --name b
+  | ------            ^^^^^^
+  | |
+  | first used here";
+    assert_eq!(r, expected);
+
+    // failures without a location are left alone
+    let r = parser
+        .run_inner(&["--port", "1"])
+        .unwrap_err()
+        .unwrap_stderr();
+    assert_eq!(
+        r,
+        "expected `--name=NAME`, pass `--help` for usage information"
+    );
+    let err = parser.run_structured(&["--port", "1"]).unwrap_err();
+    assert_eq!(err.related_index(), None);
+}
+
+#[test]
+fn caret_diagnostics_long_command_line() {
+    let name = long("name").argument::<String>("NAME");
+    let port = long("port").argument::<u16>("PORT");
+    let tag = long("tag").argument::<String>("TAG").many();
+    let parser = construct!(name, tag, port)
+        .to_options()
+        .max_width(40)
+        .caret_diagnostics();
+
+    let mut args = vec!["--name", "Bob"];
+    for _ in 0..10 {
+        args.extend(["--tag", "important"]);
+    }
+    args.extend(["--port", "http", "--tag", "last"]);
+    let r = parser.run_inner(&args[..]).unwrap_err().unwrap_stderr();
+    let expected = "\
+couldn't parse `http`: invalid digit found in string
+  |
+  | --name ... --port http --tag last
+  |                   ^^^^
+  |                   |
+  |                   invalid digit found
+  |                   in string";
+    assert_eq!(r, expected);
+
+    let err = parser.run_structured(&args[..]).unwrap_err();
+    assert_eq!(err.index(), Some(23));
+}
+
+#[test]
+fn caret_diagnostics_with_others() {
+    let json = long("json").switch();
+    let yaml = long("yaml").switch();
+    let format = construct!([json, yaml]);
+    let port = long("port").argument::<u16>("PORT");
+    let server = construct!(port).to_options().command("serve");
+    let parser = construct!(format, server)
+        .to_options()
+        .accumulate_errors()
+        .caret_diagnostics();
+
+    let r = parser
+        .run_inner(&["--yaml", "serve", "--port", "x"])
+        .unwrap_err()
+        .unwrap_stderr();
+    let expected = "\
+couldn't parse `x`: invalid digit found in string
+  |
+  | --yaml serve --port x
+  |                     ^ invalid digit found in string";
+    assert_eq!(r, expected);
+
+    let min = long("min").argument::<u16>("MIN");
+    let max = long("max").argument::<u16>("MAX");
+    let parser = construct!(min, max)
+        .to_options()
+        .accumulate_errors()
+        .caret_diagnostics();
+    let r = parser
+        .run_inner(&["--min", "x", "--max", "y"])
+        .unwrap_err()
+        .unwrap_stderr();
+    let expected = "\
+found 2 problems:
+  couldn't parse `x`: invalid digit found in string
+    |
+    | --min x --max y
+    |       ^ invalid digit found in string
+  couldn't parse `y`: invalid digit found in string
+    |
+    | --min x --max y
+    |               ^ invalid digit found in string";
+    assert_eq!(r, expected);
+}
+
+#[test]
+fn json_help_and_version() {
+    let verbose = short('v').long("verbose").help("Be \"loud\"").switch();
+    let jobs = short('j')
+        .argument::<usize>("JOBS")
+        .fallback(4)
+        .display_fallback();
+    let file = positional::<String>("FILE").help("Input file");
+    let build = pure(())
+        .to_options()
+        .descr("Build things")
+        .command("build")
+        .short('b');
+    let parser = construct!(verbose, jobs, build, file)
+        .to_options()
+        .descr("Does things\n\nand more things")
+        .version("1.2.3");
+
+    let err = parser.run_structured(&["--help"]).unwrap_err();
+    let expected = concat!(
+        r#"{"kind":"help","message":"#,
+        r#""Does things\n\nUsage: [-v] [-j=JOBS] COMMAND ... FILE\n\nAvailable positional items:\n"#,
+        r#"    FILE           Input file\n\nAvailable options:\n"#,
+        r#"    -v, --verbose  Be \"loud\"\n    -j=JOBS\n                   [default: 4]\n"#,
+        r#"    -h, --help     Prints help information\n"#,
+        r#"    -V, --version  Prints version information\n\nAvailable commands:\n"#,
+        r#"    build, b       Build things\n","#,
+        r#""index":null,"related_index":null,"names":[],"value":null,"user_message":null,"#,
+        r#""path":[],"context":[],"help":{"usage":"[-v] [-j=JOBS] COMMAND ... FILE","#,
+        r#""description":"Does things\nand more things","header":null,"footer":null,"items":["#,
+        r#"{"kind":"flag","short":"-v","long":"--verbose","aliases":[],"help":"Be \"loud\"","env":null,"default":null},"#,
+        r#"{"kind":"argument","short":"-j","long":null,"aliases":[],"metavar":"JOBS","help":null,"env":null,"possible_values":[],"default":"4"},"#,
+        r#"{"kind":"command","name":"build","short":"b","aliases":[],"help":"Build things","default":null},"#,
+        r#"{"kind":"positional","metavar":"FILE","help":"Input file","possible_values":[],"default":null},"#,
+        r#"{"kind":"flag","short":"-h","long":"--help","aliases":[],"help":"Prints help information","env":null,"default":null},"#,
+        r#"{"kind":"flag","short":"-V","long":"--version","aliases":[],"help":"Prints version information","env":null,"default":null}"#,
+        r#"]},"others":[]}"#
+    );
+    assert_eq!(err.to_json(), expected);
+
+    let err = parser.run_structured(&["-V"]).unwrap_err();
+    let json = err.to_json();
+    assert!(json.starts_with(r#"{"kind":"version","message":"Version: 1.2.3\n""#));
+    assert!(json.ends_with(r#""version":"1.2.3","others":[]}"#));
+}
+
+#[test]
+fn json_errors() {
+    let name = long("name").argument::<String>("NAME");
+    let port = long("port").argument::<u16>("PORT");
+    let serve = construct!(name, port).to_options().command("serve");
+    let parser = serve.to_options().accumulate_errors().caret_diagnostics();
+
+    let err = parser
+        .run_structured(&["serve", "--port", "x\ty"])
+        .unwrap_err();
+    let expected = concat!(
+        r#"{"kind":"missing_item","#,
+        r#""message":"expected `--name=NAME`, pass `--help` for usage information","#,
+        r#""index":null,"related_index":null,"names":["--name"],"value":null,"user_message":null,"#,
+        r#""path":["serve"],"context":[],"others":[{"kind":"invalid_value","#,
+        r#""message":"couldn't parse `x\ty`: invalid digit found in string","#,
+        r#""index":2,"related_index":null,"names":["--port"],"value":"x\ty","#,
+        r#""user_message":"invalid digit found in string","path":["serve"],"context":[],"others":[]}]}"#
+    );
+    assert_eq!(err.to_json(), expected);
+}
+
+#[test]
+fn exit_codes() {
+    let verbose = short('v').switch();
+    let port = long("port")
+        .argument::<u16>("PORT")
+        .guard(|p| *p > 1024, "port must be above 1024");
+    let token = env("BPAF_EXIT_CODES_TOKEN").argument::<String>("TOKEN");
+    let parser = construct!(verbose, port, token).to_options().version("1.0");
+
+    let code = |parser: &OptionParser<_>, args: &[&str]| {
+        let err = parser.run_structured(args).unwrap_err();
+        parser.exit_code(err.kind())
+    };
+
+    // defaults match ParseFailure::exit_code
+    assert_eq!(code(&parser, &["--help"]), 0);
+    assert_eq!(code(&parser, &["--port", "80"]), 1);
+    let failure = parser.run_inner(&["--port", "80"]).unwrap_err();
+    assert_eq!(failure.exit_code(), 1);
+
+    let parser = parser.exit_codes(|kind| match kind {
+        ErrorKind::MissingEnv => 3,
+        kind => kind.sysexits(),
+    });
+    assert_eq!(code(&parser, &["--help"]), 0);
+    assert_eq!(code(&parser, &["-V"]), 0);
+    assert_eq!(code(&parser, &["--porty"]), 64);
+    assert_eq!(code(&parser, &["--port"]), 64);
+    assert_eq!(code(&parser, &["--port", "http"]), 65);
+    assert_eq!(code(&parser, &["--port", "80"]), 65);
+    assert_eq!(code(&parser, &["--port", "8080"]), 3);
+}